@@ -0,0 +1,533 @@
+use crate::module_info::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// API surface changes between two versions of a package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiDiff {
+    pub package: String,
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+    pub changes: Vec<ApiChange>,
+}
+
+/// A single added, removed or changed item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiChange {
+    /// `module:symbol` path, or the module path for added/removed modules
+    pub path: String,
+    pub item: ApiItem,
+    pub change: ChangeType,
+    pub severity: Severity,
+    /// Signature or definition in the old version
+    pub before: Option<String>,
+    /// Signature or definition in the new version
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiItem {
+    Module,
+    Export,
+    Function,
+    Class,
+    Constructor,
+    Method,
+    Property,
+    Type,
+    Constant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeType {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Whether existing consumers can be broken by a change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Breaking,
+    Additive,
+}
+
+impl ApiDiff {
+    /// Compare two explored trees of the same package
+    pub fn compare(old: &NodeModuleInfo, new: &NodeModuleInfo) -> Self {
+        let mut changes = Vec::new();
+        diff_module(&old.name, old, new, &mut changes);
+        Self {
+            package: new.name.clone(),
+            from_version: old.version.clone(),
+            to_version: new.version.clone(),
+            changes,
+        }
+    }
+
+    pub fn breaking(&self) -> impl Iterator<Item = &ApiChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.severity == Severity::Breaking)
+    }
+
+    pub fn additive(&self) -> impl Iterator<Item = &ApiChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.severity == Severity::Additive)
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.breaking().next().is_some()
+    }
+
+    /// Render as a Markdown report for upgrade pull requests
+    pub fn to_markdown(&self) -> String {
+        let version = |v: &Option<String>| v.clone().unwrap_or_else(|| "?".to_string());
+        let mut output = format!(
+            "## `{}` {} → {}\n",
+            self.package,
+            version(&self.from_version),
+            version(&self.to_version)
+        );
+
+        if self.changes.is_empty() {
+            output.push_str("\nNo API changes.\n");
+            return output;
+        }
+
+        for (title, severity) in [
+            ("Breaking changes", Severity::Breaking),
+            ("Additions", Severity::Additive),
+        ] {
+            let changes: Vec<&ApiChange> = self
+                .changes
+                .iter()
+                .filter(|change| change.severity == severity)
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\n### {} ({})\n\n", title, changes.len()));
+            output.push_str("| Change | Kind | Symbol | Details |\n");
+            output.push_str("| --- | --- | --- | --- |\n");
+            for change in changes {
+                output.push_str(&format!(
+                    "| {} | {} | `{}` | {} |\n",
+                    change.change.label(),
+                    change.item.label(),
+                    change.path,
+                    markdown_details(change)
+                ));
+            }
+        }
+
+        output
+    }
+}
+
+impl ApiItem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ApiItem::Module => "module",
+            ApiItem::Export => "export",
+            ApiItem::Function => "function",
+            ApiItem::Class => "class",
+            ApiItem::Constructor => "constructor",
+            ApiItem::Method => "method",
+            ApiItem::Property => "property",
+            ApiItem::Type => "type",
+            ApiItem::Constant => "constant",
+        }
+    }
+}
+
+impl ChangeType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeType::Added => "added",
+            ChangeType::Removed => "removed",
+            ChangeType::Changed => "changed",
+        }
+    }
+}
+
+fn markdown_details(change: &ApiChange) -> String {
+    // Pipes would end the table cell
+    let code = |text: &str| format!("`{}`", text.replace('|', "\\|"));
+    match (&change.before, &change.after) {
+        (Some(before), Some(after)) => format!("{} → {}", code(before), code(after)),
+        (Some(before), None) => code(before),
+        (None, Some(after)) => code(after),
+        (None, None) => String::new(),
+    }
+}
+
+fn diff_module(
+    path: &str,
+    old: &NodeModuleInfo,
+    new: &NodeModuleInfo,
+    changes: &mut Vec<ApiChange>,
+) {
+    let symbol_path = |name: &str| format!("{}:{}", path, name);
+
+    diff_functions(
+        &symbol_path,
+        ApiItem::Function,
+        &old.functions,
+        &new.functions,
+        changes,
+    );
+
+    for name in names(&old.classes, &new.classes, |c| &c.name) {
+        let before = old.classes.iter().find(|c| c.name == name);
+        let after = new.classes.iter().find(|c| c.name == name);
+        match (before, after) {
+            (Some(before), Some(after)) => diff_class(&symbol_path(&name), before, after, changes),
+            (Some(_), None) => changes.push(removed(symbol_path(&name), ApiItem::Class, None)),
+            (None, Some(_)) => changes.push(added(symbol_path(&name), ApiItem::Class, None)),
+            (None, None) => {}
+        }
+    }
+
+    for name in names(&old.types, &new.types, |t| &t.name) {
+        let before = old
+            .types
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.definition.clone());
+        let after = new
+            .types
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.definition.clone());
+        diff_value(symbol_path(&name), ApiItem::Type, before, after, changes);
+    }
+
+    for name in names(&old.constants, &new.constants, |c| &c.name) {
+        let constant_type = |constants: &[ConstantInfo]| {
+            constants
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| c.value_type.clone().unwrap_or_default())
+        };
+        diff_value(
+            symbol_path(&name),
+            ApiItem::Constant,
+            constant_type(&old.constants),
+            constant_type(&new.constants),
+            changes,
+        );
+    }
+
+    // Export names without a definition of their own (re-exports from
+    // dependencies, `module.exports` properties) are only tracked by name
+    let defined = |name: &String| old.defines(name) || new.defines(name);
+    let old_exports: BTreeSet<&String> = old.exports.iter().filter(|n| !defined(n)).collect();
+    let new_exports: BTreeSet<&String> = new.exports.iter().filter(|n| !defined(n)).collect();
+    for name in old_exports.difference(&new_exports) {
+        if !new.submodules.contains_key(name.as_str()) {
+            changes.push(removed(symbol_path(name), ApiItem::Export, None));
+        }
+    }
+    for name in new_exports.difference(&old_exports) {
+        if !old.submodules.contains_key(name.as_str()) {
+            changes.push(added(symbol_path(name), ApiItem::Export, None));
+        }
+    }
+
+    let submodules: BTreeSet<&String> =
+        old.submodules.keys().chain(new.submodules.keys()).collect();
+    for name in submodules {
+        let module_path = format!("{}/{}", path, name);
+        match (old.submodules.get(name), new.submodules.get(name)) {
+            (Some(before), Some(after)) => diff_module(&module_path, before, after, changes),
+            (Some(_), None) => changes.push(removed(module_path, ApiItem::Module, None)),
+            (None, Some(_)) => changes.push(added(module_path, ApiItem::Module, None)),
+            (None, None) => {}
+        }
+    }
+}
+
+fn diff_class(path: &str, old: &ClassInfo, new: &ClassInfo, changes: &mut Vec<ApiChange>) {
+    let heritage = |class: &ClassInfo| class.extends.clone().unwrap_or_default();
+    if heritage(old) != heritage(new) {
+        changes.push(ApiChange {
+            path: path.to_string(),
+            item: ApiItem::Class,
+            change: ChangeType::Changed,
+            severity: Severity::Breaking,
+            before: old.extends.as_ref().map(|base| format!("extends {}", base)),
+            after: new.extends.as_ref().map(|base| format!("extends {}", base)),
+        });
+    }
+
+    let constructor = |class: &ClassInfo| class.constructor.iter().cloned().collect::<Vec<_>>();
+    diff_functions(
+        &|_: &str| format!("{}.constructor", path),
+        ApiItem::Constructor,
+        &constructor(old),
+        &constructor(new),
+        changes,
+    );
+
+    let member_path = |name: &str| format!("{}.{}", path, name);
+    diff_functions(
+        &member_path,
+        ApiItem::Method,
+        &old.methods,
+        &new.methods,
+        changes,
+    );
+
+    for name in names(&old.properties, &new.properties, |p| &p.name) {
+        let property_type = |properties: &[PropertyInfo]| {
+            properties
+                .iter()
+                .find(|p| p.name == name)
+                .map(|p| p.property_type.clone().unwrap_or_default())
+        };
+        diff_value(
+            member_path(&name),
+            ApiItem::Property,
+            property_type(&old.properties),
+            property_type(&new.properties),
+            changes,
+        );
+    }
+}
+
+/// Compare same-named functions; overloads are compared as a set
+fn diff_functions(
+    path: &dyn Fn(&str) -> String,
+    item: ApiItem,
+    old: &[FunctionInfo],
+    new: &[FunctionInfo],
+    changes: &mut Vec<ApiChange>,
+) {
+    for name in names(old, new, |f| &f.name) {
+        let before: Vec<&FunctionInfo> = old.iter().filter(|f| f.name == name).collect();
+        let after: Vec<&FunctionInfo> = new.iter().filter(|f| f.name == name).collect();
+        let render = |functions: &[&FunctionInfo]| {
+            functions
+                .iter()
+                .map(|f| f.to_signature(f.name.clone(), SignatureKind::Function).declaration())
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+
+        match (before.is_empty(), after.is_empty()) {
+            (false, true) => changes.push(removed(path(&name), item, Some(render(&before)))),
+            (true, false) => changes.push(added(path(&name), item, Some(render(&after)))),
+            (false, false) => {
+                let severity = if let ([before], [after]) = (before.as_slice(), after.as_slice()) {
+                    compare_signatures(before, after)
+                } else {
+                    // Dropping an overload breaks its callers; adding one does not
+                    let keys = |fs: &[&FunctionInfo]| -> BTreeSet<String> {
+                        fs.iter().map(|f| signature_key(f)).collect()
+                    };
+                    let (old_keys, new_keys) = (keys(&before), keys(&after));
+                    if old_keys == new_keys {
+                        None
+                    } else if old_keys.is_subset(&new_keys) {
+                        Some(Severity::Additive)
+                    } else {
+                        Some(Severity::Breaking)
+                    }
+                };
+                if let Some(severity) = severity {
+                    changes.push(ApiChange {
+                        path: path(&name),
+                        item,
+                        change: ChangeType::Changed,
+                        severity,
+                        before: Some(render(&before)),
+                        after: Some(render(&after)),
+                    });
+                }
+            }
+            (true, true) => {}
+        }
+    }
+}
+
+/// Classify a change between two signatures; `None` when callers see no
+/// difference (e.g. only parameter names changed)
+fn compare_signatures(old: &FunctionInfo, new: &FunctionInfo) -> Option<Severity> {
+    if signature_key(old) == signature_key(new) {
+        return None;
+    }
+    // A return type that was unknown before (untyped JavaScript) is not a change
+    let return_changed = old.return_type.is_some() && old.return_type != new.return_type;
+    if return_changed || old.type_params != new.type_params {
+        return Some(Severity::Breaking);
+    }
+
+    let required =
+        |param: &Parameter| !param.is_optional && param.default_value.is_none() && !param.is_rest;
+    for (index, new_param) in new.parameters.iter().enumerate() {
+        match old.parameters.get(index) {
+            Some(old_param)
+                if (old_param.param_type.is_some()
+                    && old_param.param_type != new_param.param_type)
+                    || old_param.is_rest != new_param.is_rest
+                    || (required(new_param) && !required(old_param)) =>
+            {
+                return Some(Severity::Breaking);
+            }
+            Some(_) => {}
+            // Callers never pass a new trailing parameter, so it must be optional
+            None if required(new_param) => return Some(Severity::Breaking),
+            None => {}
+        }
+    }
+    if new.parameters.len() < old.parameters.len() {
+        return Some(Severity::Breaking);
+    }
+    Some(Severity::Additive)
+}
+
+/// The parts of a signature callers depend on; parameter names are excluded
+fn signature_key(function: &FunctionInfo) -> String {
+    let params: Vec<String> = function
+        .parameters
+        .iter()
+        .map(|param| {
+            format!(
+                "{}{}:{}",
+                if param.is_rest { "..." } else { "" },
+                if param.is_optional || param.default_value.is_some() {
+                    "?"
+                } else {
+                    ""
+                },
+                param.param_type.as_deref().unwrap_or("")
+            )
+        })
+        .collect();
+    format!(
+        "<{}>({})=>{}",
+        function.type_params.join(","),
+        params.join(","),
+        function.return_type.as_deref().unwrap_or("")
+    )
+}
+
+fn diff_value(
+    path: String,
+    item: ApiItem,
+    before: Option<String>,
+    after: Option<String>,
+    changes: &mut Vec<ApiChange>,
+) {
+    let non_empty = |value: Option<String>| value.filter(|v| !v.is_empty());
+    match (before, after) {
+        (Some(before), None) => changes.push(removed(path, item, non_empty(Some(before)))),
+        (None, Some(after)) => changes.push(added(path, item, non_empty(Some(after)))),
+        (Some(before), Some(after)) if before != after => changes.push(ApiChange {
+            path,
+            item,
+            change: ChangeType::Changed,
+            severity: Severity::Breaking,
+            before: non_empty(Some(before)),
+            after: non_empty(Some(after)),
+        }),
+        _ => {}
+    }
+}
+
+/// Names present in either version, in order of first appearance
+fn names<T>(old: &[T], new: &[T], name: impl Fn(&T) -> &String) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for item in old.iter().chain(new) {
+        if !names.contains(name(item)) {
+            names.push(name(item).clone());
+        }
+    }
+    names
+}
+
+fn added(path: String, item: ApiItem, after: Option<String>) -> ApiChange {
+    ApiChange {
+        path,
+        item,
+        change: ChangeType::Added,
+        severity: Severity::Additive,
+        before: None,
+        after,
+    }
+}
+
+fn removed(path: String, item: ApiItem, before: Option<String>) -> ApiChange {
+    ApiChange {
+        path,
+        item,
+        change: ChangeType::Removed,
+        severity: Severity::Breaking,
+        before,
+        after: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast_parser::AstParser;
+
+    fn parse(source: &str) -> NodeModuleInfo {
+        AstParser::new().parse_content(source, "pkg").unwrap()
+    }
+
+    fn find<'a>(diff: &'a ApiDiff, path: &str) -> &'a ApiChange {
+        diff.changes
+            .iter()
+            .find(|change| change.path == path)
+            .unwrap_or_else(|| panic!("no change for {}", path))
+    }
+
+    #[test]
+    fn test_function_changes_are_classified() {
+        let old = parse(
+            "export function a(x: string): void {}\nexport function b(x: string) {}\nexport function c(x: string) {}\nexport function d(x: string) {}\nexport function gone() {}",
+        );
+        let new = parse(
+            "export function a(input: string): void {}\nexport function b(x: string, y?: number) {}\nexport function c(x: string, y: number) {}\nexport function d(x: number) {}\nexport function fresh() {}",
+        );
+        let diff = ApiDiff::compare(&old, &new);
+
+        assert!(!diff.changes.iter().any(|change| change.path == "pkg:a"));
+        assert_eq!(find(&diff, "pkg:b").severity, Severity::Additive);
+        assert_eq!(find(&diff, "pkg:c").severity, Severity::Breaking);
+        assert_eq!(find(&diff, "pkg:d").severity, Severity::Breaking);
+        assert_eq!(find(&diff, "pkg:gone").change, ChangeType::Removed);
+        assert_eq!(find(&diff, "pkg:fresh").severity, Severity::Additive);
+        assert_eq!(
+            find(&diff, "pkg:c").after.as_deref(),
+            Some("c(x: string, y: number)")
+        );
+    }
+
+    #[test]
+    fn test_class_members_and_markdown() {
+        let old = parse("export class Server { listen(port: number) {} close() {} }");
+        let mut new = parse("export class Server { listen(port: number, host?: string) {} }");
+        new.version = Some("2.0.0".to_string());
+        let diff = ApiDiff::compare(&old, &new);
+
+        assert_eq!(find(&diff, "pkg:Server.close").severity, Severity::Breaking);
+        assert_eq!(
+            find(&diff, "pkg:Server.listen").severity,
+            Severity::Additive
+        );
+        assert!(diff.is_breaking());
+
+        let markdown = diff.to_markdown();
+        assert!(markdown.starts_with("## `pkg` ? → 2.0.0"));
+        assert!(markdown.contains("### Breaking changes (1)"));
+        assert!(markdown.contains("| removed | method | `pkg:Server.close` |"));
+    }
+}
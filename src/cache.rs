@@ -0,0 +1,573 @@
+use crate::extract::{extract_tarball, ExtractLimits, ExtractReport};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tempfile::TempDir;
+
+/// Registry metadata is re-fetched after this long unless overridden
+/// with `PRETTY_NODE_CACHE_TTL` (seconds)
+const DEFAULT_METADATA_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// On-disk cache of registry metadata and package tarballs
+///
+/// Layout under the cache root:
+/// - `metadata/<name>.json` — registry documents, expired after a TTL
+/// - `content/<algorithm>/<digest>` — tarballs, addressed by integrity hash
+/// - `content/unverified/<name>@<version>` — tarballs published without one
+/// - `index/<name>@<version>.json` — which tarball a release maps to
+/// - `packages/<name>@<version>/` — the unpacked tarball
+///
+/// Releases from registries other than the public one are keyed by
+/// registry URL and name (`<registry><name>@<version>`), so a private
+/// registry serving the same name and version never shares their files.
+pub struct PackageCache {
+    root: PathBuf,
+    metadata_ttl: Duration,
+}
+
+/// A cached package release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub name: String,
+    pub version: String,
+    /// Registry the release came from; `None` for the public registry
+    #[serde(default)]
+    pub registry: Option<String>,
+    /// Subresource integrity string the tarball is stored under, if the
+    /// registry published one
+    pub integrity: Option<String>,
+    pub tarball_url: String,
+    /// Tarball size in bytes
+    pub size: u64,
+    /// Unix timestamps in seconds
+    pub stored_at: u64,
+    pub last_used: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MetadataRecord {
+    fetched_at: u64,
+    data: Value,
+}
+
+impl PackageCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            metadata_ttl: DEFAULT_METADATA_TTL,
+        }
+    }
+
+    /// The cache configured by the environment, or `None` when disabled
+    /// with `PRETTY_NODE_NO_CACHE` or no cache directory can be determined
+    ///
+    /// The root is `$PRETTY_NODE_CACHE_DIR`, else `$XDG_CACHE_HOME/pretty-node`,
+    /// else `~/.cache/pretty-node`.
+    pub fn from_env() -> Option<Self> {
+        if env::var("PRETTY_NODE_NO_CACHE").is_ok() {
+            return None;
+        }
+        let root = env::var_os("PRETTY_NODE_CACHE_DIR")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("XDG_CACHE_HOME")
+                    .filter(|dir| !dir.is_empty())
+                    .map(|dir| PathBuf::from(dir).join("pretty-node"))
+            })
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache/pretty-node"))
+            })?;
+
+        let mut cache = Self::new(root);
+        if let Some(ttl) = env::var("PRETTY_NODE_CACHE_TTL")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+        {
+            cache.metadata_ttl = Duration::from_secs(ttl);
+        }
+        Some(cache)
+    }
+
+    pub fn with_metadata_ttl(mut self, ttl: Duration) -> Self {
+        self.metadata_ttl = ttl;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// A registry document fetched less than the TTL ago
+    pub fn get_metadata(&self, key: &str) -> Option<Value> {
+        let record = self.read_metadata(key)?;
+        let age = now().saturating_sub(record.fetched_at);
+        (age < self.metadata_ttl.as_secs()).then_some(record.data)
+    }
+
+    /// A registry document regardless of its age, for when the registry
+    /// cannot be reached
+    pub fn get_stale_metadata(&self, key: &str) -> Option<Value> {
+        self.read_metadata(key).map(|record| record.data)
+    }
+
+    pub fn put_metadata(&self, key: &str, data: &Value) -> Result<()> {
+        let record = MetadataRecord {
+            fetched_at: now(),
+            data: data.clone(),
+        };
+        write_atomic(&self.metadata_path(key), &serde_json::to_vec(&record)?)
+    }
+
+    /// Directory of an unpacked cached release, re-extracting it from the
+    /// content store if only the tarball is left
+    pub fn get_package(&self, registry: Option<&str>, name: &str, version: &str) -> Option<PathBuf> {
+        let mut entry = self.entry(registry, name, version)?;
+        let package_dir = self.package_dir(registry, name, version);
+        if !package_dir.exists() {
+            let tarball = fs::read(self.tarball_path(&entry)).ok()?;
+            // The content store is trusted no more than the network
+            crate::integrity::verify(name, &tarball, entry.integrity.as_deref(), None).ok()?;
+            self.unpack(&tarball, &package_dir).ok()?;
+        }
+
+        entry.last_used = now();
+        // Best effort: a read-only cache still serves hits
+        let _ = self.write_entry(&entry);
+        Some(package_dir)
+    }
+
+    /// Store a downloaded tarball and unpack it; returns the unpacked
+    /// directory and what extraction skipped (nothing if it was already
+    /// unpacked)
+    pub fn put_package(
+        &self,
+        registry: Option<&str>,
+        name: &str,
+        version: &str,
+        integrity: Option<&str>,
+        tarball_url: &str,
+        tarball: &[u8],
+    ) -> Result<(PathBuf, ExtractReport)> {
+        let timestamp = now();
+        let entry = CacheEntry {
+            name: name.to_string(),
+            version: version.to_string(),
+            registry: registry.map(str::to_string),
+            integrity: integrity.map(str::to_string),
+            tarball_url: tarball_url.to_string(),
+            size: tarball.len() as u64,
+            stored_at: timestamp,
+            last_used: timestamp,
+        };
+
+        write_atomic(&self.tarball_path(&entry), tarball)?;
+        // A replaced tarball (the registry republished, or the old one was
+        // stale) must not be served from the previous unpacked tree; without
+        // an integrity there is no telling, so the tree is always rebuilt
+        let package_dir = self.package_dir(registry, name, version);
+        let replaced = self.entry(registry, name, version).is_some_and(|existing| {
+            existing.integrity.is_none() || existing.integrity != entry.integrity
+        });
+        if replaced && package_dir.exists() {
+            fs::remove_dir_all(&package_dir)?;
+        }
+        let mut report = ExtractReport::default();
+        if !package_dir.exists() {
+            report = self.unpack(tarball, &package_dir)?;
+        }
+
+        self.write_entry(&entry)?;
+        Ok((package_dir, report))
+    }
+
+    /// All cached releases, sorted by name and version
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let index_dir = self.root.join("index");
+        if !index_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for file in fs::read_dir(&index_dir)? {
+            let path = file?.path();
+            if let Some(entry) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<CacheEntry>(&content).ok())
+            {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version)));
+        Ok(entries)
+    }
+
+    /// Remove everything in the cache
+    pub fn clean(&self) -> Result<()> {
+        for dir in ["metadata", "content", "index", "packages", "tmp"] {
+            let path = self.root.join(dir);
+            if path.exists() {
+                fs::remove_dir_all(&path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove releases not used within `max_age`, tarballs no longer
+    /// referenced by any release, and metadata fetched before the cutoff.
+    /// Returns the removed releases.
+    pub fn prune(&self, max_age: Duration) -> Result<Vec<CacheEntry>> {
+        let cutoff = now().saturating_sub(max_age.as_secs());
+        let (removed, kept): (Vec<_>, Vec<_>) = self
+            .entries()?
+            .into_iter()
+            .partition(|entry| entry.last_used < cutoff);
+
+        for entry in &removed {
+            let registry = entry.registry.as_deref();
+            let package_dir = self.package_dir(registry, &entry.name, &entry.version);
+            if package_dir.exists() {
+                fs::remove_dir_all(&package_dir)?;
+            }
+            fs::remove_file(self.entry_path(registry, &entry.name, &entry.version))?;
+        }
+
+        let referenced: Vec<PathBuf> = kept.iter().map(|entry| self.tarball_path(entry)).collect();
+        for entry in &removed {
+            let content = self.tarball_path(entry);
+            if !referenced.contains(&content) && content.exists() {
+                fs::remove_file(content)?;
+            }
+        }
+
+        let metadata_dir = self.root.join("metadata");
+        if metadata_dir.exists() {
+            for file in fs::read_dir(&metadata_dir)? {
+                let path = file?.path();
+                let fetched_at = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<MetadataRecord>(&content).ok())
+                    .map(|record| record.fetched_at)
+                    .unwrap_or(0);
+                if fetched_at < cutoff {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// The cached release `name@version` from `registry`, if any
+    pub fn entry(&self, registry: Option<&str>, name: &str, version: &str) -> Option<CacheEntry> {
+        let content = fs::read_to_string(self.entry_path(registry, name, version)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn read_metadata(&self, key: &str) -> Option<MetadataRecord> {
+        let content = fs::read_to_string(self.metadata_path(key)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn write_entry(&self, entry: &CacheEntry) -> Result<()> {
+        write_atomic(
+            &self.entry_path(entry.registry.as_deref(), &entry.name, &entry.version),
+            &serde_json::to_vec_pretty(entry)?,
+        )
+    }
+
+    /// Unpack into a scratch directory first so readers never see a partial tree
+    fn unpack(&self, tarball: &[u8], package_dir: &Path) -> Result<ExtractReport> {
+        let scratch_root = self.root.join("tmp");
+        fs::create_dir_all(&scratch_root)?;
+        let scratch = TempDir::new_in(&scratch_root)?;
+        let report = extract_tarball(tarball, scratch.path(), &ExtractLimits::default())?;
+
+        if let Some(parent) = package_dir.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match fs::rename(scratch.path(), package_dir) {
+            Ok(()) => Ok(report),
+            // Another process unpacked the same release first
+            Err(_) if package_dir.exists() => Ok(report),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn metadata_path(&self, key: &str) -> PathBuf {
+        self.root
+            .join("metadata")
+            .join(format!("{}.json", escape_name(key)))
+    }
+
+    fn entry_path(&self, registry: Option<&str>, name: &str, version: &str) -> PathBuf {
+        self.root
+            .join("index")
+            .join(format!("{}.json", release_key(registry, name, version)))
+    }
+
+    fn package_dir(&self, registry: Option<&str>, name: &str, version: &str) -> PathBuf {
+        self.root
+            .join("packages")
+            .join(release_key(registry, name, version))
+    }
+
+    /// Where a release's tarball is stored: by integrity hash, or without
+    /// a published hash by the release itself
+    fn tarball_path(&self, entry: &CacheEntry) -> PathBuf {
+        match &entry.integrity {
+            Some(integrity) => self.content_path(integrity),
+            None => self.root.join("content/unverified").join(release_key(
+                entry.registry.as_deref(),
+                &entry.name,
+                &entry.version,
+            )),
+        }
+    }
+
+    /// `sha512-<base64>` is stored as `content/sha512/<base64url>`
+    fn content_path(&self, integrity: &str) -> PathBuf {
+        let (algorithm, digest) = integrity.split_once('-').unwrap_or(("unknown", integrity));
+        self.root
+            .join("content")
+            .join(escape_name(algorithm))
+            .join(digest.replace('+', "-").replace('/', "_").replace('=', ""))
+    }
+}
+
+/// Parse a duration such as `30m`, `12h`, `7d` or `2w`; bare numbers are seconds
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| anyhow!("Invalid duration '{}': expected e.g. 30m, 12h, 7d", text))?;
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(anyhow!(
+                "Invalid duration unit '{}': use s, m, h, d or w",
+                unit
+            ))
+        }
+    };
+    let total = amount
+        .checked_mul(seconds)
+        .ok_or_else(|| anyhow!("Invalid duration '{}': too long", text))?;
+    Ok(Duration::from_secs(total))
+}
+
+/// `<name>@<version>`, prefixed with the registry URL for other registries
+fn release_key(registry: Option<&str>, name: &str, version: &str) -> String {
+    let name = match registry {
+        Some(registry) => format!("{}{}", registry, name),
+        None => name.to_string(),
+    };
+    format!("{}@{}", escape_name(&name), escape_name(version))
+}
+
+/// Scoped names contain a slash; keep cache file names flat
+fn escape_name(name: &str) -> String {
+    name.replace('%', "%25").replace('/', "%2f")
+}
+
+fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid cache path {}", path.display()))?;
+    fs::create_dir_all(parent)?;
+    let scratch = tempfile::NamedTempFile::new_in(parent)?;
+    fs::write(scratch.path(), content)?;
+    scratch
+        .persist(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    fn tarball(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_package_round_trip_and_prune() {
+        use base64::Engine;
+        use sha2::Digest;
+
+        let temp_dir = TempDir::new().unwrap();
+        let cache = PackageCache::new(temp_dir.path().to_path_buf());
+        let bytes = tarball(&[("package/index.js", "export const a = 1;")]);
+        let integrity = format!(
+            "sha512-{}",
+            base64::engine::general_purpose::STANDARD.encode(sha2::Sha512::digest(&bytes))
+        );
+        let content = cache.content_path(&integrity);
+        assert!(content.starts_with(temp_dir.path().join("content/sha512")));
+
+        let dir = cache
+            .put_package(
+                None,
+                "@scope/pkg",
+                "1.0.0",
+                Some(&integrity),
+                "https://registry/pkg.tgz",
+                &bytes,
+            )
+            .unwrap()
+            .0;
+        assert!(dir.join("package/index.js").exists());
+        assert!(content.exists());
+
+        // The unpacked tree is rebuilt from the content store
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(cache.get_package(None, "@scope/pkg", "1.0.0"), Some(dir.clone()));
+        assert!(dir.join("package/index.js").exists());
+        assert!(cache.get_package(None, "@scope/pkg", "2.0.0").is_none());
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "@scope/pkg");
+        assert_eq!(entries[0].size, bytes.len() as u64);
+
+        assert!(cache.prune(Duration::from_secs(3600)).unwrap().is_empty());
+        let mut entry = entries[0].clone();
+        entry.last_used = 0;
+        cache.write_entry(&entry).unwrap();
+        assert_eq!(cache.prune(Duration::from_secs(3600)).unwrap().len(), 1);
+        assert!(cache.entries().unwrap().is_empty());
+        assert!(!dir.exists());
+        assert!(!content.exists());
+    }
+
+    #[test]
+    fn test_tampered_content_is_not_served() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = PackageCache::new(temp_dir.path().to_path_buf());
+        let bytes = tarball(&[("package/index.js", "export const a = 1;")]);
+        let integrity = "sha512-m3HSJL1i83hdltRq0+o9czGb+8KJDKra4t/3JRlnPKcjI8PZm6XBHXx6zG4UuMXaDEZjR1wuXDre9G9zvN7AQw==";
+
+        let dir = cache
+            .put_package(
+                None,
+                "pkg",
+                "1.0.0",
+                Some(integrity),
+                "https://registry/pkg.tgz",
+                &bytes,
+            )
+            .unwrap()
+            .0;
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(cache.get_package(None, "pkg", "1.0.0"), None);
+    }
+
+    #[test]
+    fn test_registries_do_not_share_releases() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = PackageCache::new(temp_dir.path().to_path_buf());
+        let private = "https://npm.internal.test/";
+        let public_tarball = tarball(&[("package/index.js", "export const origin = 'public';")]);
+        let private_tarball = tarball(&[("package/index.js", "export const origin = 'private';")]);
+
+        let public_dir = cache
+            .put_package(None, "pkg", "1.0.0", None, "https://registry/pkg.tgz", &public_tarball)
+            .unwrap()
+            .0;
+        assert_eq!(cache.get_package(Some(private), "pkg", "1.0.0"), None);
+        let private_dir = cache
+            .put_package(
+                Some(private),
+                "pkg",
+                "1.0.0",
+                None,
+                "https://npm.internal.test/pkg.tgz",
+                &private_tarball,
+            )
+            .unwrap()
+            .0;
+        assert_ne!(public_dir, private_dir);
+
+        let read = |dir: PathBuf| fs::read_to_string(dir.join("package/index.js")).unwrap();
+        assert!(read(cache.get_package(None, "pkg", "1.0.0").unwrap()).contains("public"));
+        assert!(read(cache.get_package(Some(private), "pkg", "1.0.0").unwrap()).contains("private"));
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.integrity.is_none()));
+    }
+
+    #[test]
+    fn test_replaced_tarball_without_integrity() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = PackageCache::new(temp_dir.path().to_path_buf());
+        let put = |content: &str| {
+            let bytes = tarball(&[("package/index.js", content)]);
+            cache
+                .put_package(None, "pkg", "1.0.0", None, "https://registry/pkg.tgz", &bytes)
+                .unwrap()
+                .0
+        };
+
+        let dir = put("export const release = 'first';");
+        assert_eq!(put("export const release = 'second';"), dir);
+        let read = |dir: PathBuf| fs::read_to_string(dir.join("package/index.js")).unwrap();
+        assert!(read(cache.get_package(None, "pkg", "1.0.0").unwrap()).contains("second"));
+    }
+
+    #[test]
+    fn test_metadata_ttl() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = PackageCache::new(temp_dir.path().to_path_buf());
+        let document = serde_json::json!({"name": "pkg"});
+        cache.put_metadata("pkg", &document).unwrap();
+        assert_eq!(cache.get_metadata("pkg"), Some(document.clone()));
+
+        let expired = PackageCache::new(temp_dir.path().to_path_buf())
+            .with_metadata_ttl(Duration::from_secs(0));
+        assert_eq!(expired.get_metadata("pkg"), None);
+        assert_eq!(expired.get_stale_metadata("pkg"), Some(document));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("7d").unwrap(), Duration::from_secs(604800));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("3y").is_err());
+        assert!(parse_duration("99999999999999999w").is_err());
+    }
+}
@@ -0,0 +1,521 @@
+use crate::api_diff::ApiDiff;
+use crate::module_info::{
+    ClassInfo, DocTags, FunctionInfo, NodeModuleInfo, SignatureInfo, SignatureKind,
+};
+use crate::output_format::OutputFormatter;
+use crate::search::SearchResult;
+use anyhow::{anyhow, Result};
+
+/// TypeScript declarations synthesized from the explored API, one
+/// `declare module` block per module. Whatever the package leaves untyped
+/// is declared `any`, so the output type-checks as a starting point for
+/// hand-written typings.
+pub struct DtsFormatter;
+
+impl DtsFormatter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn format_module(&self, module: &NodeModuleInfo, path: &str, output: &mut String) {
+        // `export =` can't be combined with other exports; the assigned
+        // symbol is the module, so everything else is declared unexported
+        let export = if module.export_assignment.is_some() {
+            ""
+        } else {
+            "export "
+        };
+
+        // `default` is declared as `_default` and exported as the default;
+        // names that aren't identifiers can't be declared at all
+        let export_as = |name: &str| if name == "default" { "" } else { export };
+        let mut body = Vec::new();
+        let mut has_default = false;
+        for function in &module.functions {
+            let Some(local) = local_name(&function.name) else {
+                body.push(not_declared(&function.name));
+                continue;
+            };
+            has_default |= function.name == "default";
+            body.push(with_doc(
+                function.doc_comment.as_deref(),
+                function.doc_tags.as_ref(),
+                format!(
+                    "{}function {};",
+                    export_as(&function.name),
+                    function_declaration(function, &local)
+                ),
+            ));
+        }
+        for class in &module.classes {
+            let Some(local) = local_name(&class.name) else {
+                body.push(not_declared(&class.name));
+                continue;
+            };
+            has_default |= class.name == "default";
+            body.push(with_doc(
+                class.doc_comment.as_deref(),
+                class.doc_tags.as_ref(),
+                format!("{}{}", export_as(&class.name), class_declaration(class, &local)),
+            ));
+        }
+        for type_info in &module.types {
+            let mut definition = type_info.definition.clone();
+            if !definition.ends_with('}') {
+                definition.push(';');
+            }
+            body.push(with_doc(
+                type_info.doc_comment.as_deref(),
+                type_info.doc_tags.as_ref(),
+                format!("{}{}", export, definition),
+            ));
+        }
+        for constant in &module.constants {
+            let Some(local) = local_name(&constant.name) else {
+                body.push(not_declared(&constant.name));
+                continue;
+            };
+            has_default |= constant.name == "default";
+            body.push(with_doc(
+                constant.doc_comment.as_deref(),
+                constant.doc_tags.as_ref(),
+                format!(
+                    "{}const {}: {};",
+                    export_as(&constant.name),
+                    local,
+                    constant.value_type.as_deref().unwrap_or("any")
+                ),
+            ));
+        }
+        match &module.export_assignment {
+            Some(assigned) => match local_name(assigned) {
+                Some(local) => body.push(format!("export = {};", local)),
+                None => body.push(not_declared(assigned)),
+            },
+            None if has_default => body.push("export default _default;".to_string()),
+            None => {}
+        }
+
+        output.push_str(&format!("declare module \"{}\" {{\n", path));
+        for (i, declaration) in body.iter().enumerate() {
+            if i > 0 {
+                output.push('\n');
+            }
+            output.push_str(&indent(declaration, "  "));
+        }
+        output.push_str("}\n");
+
+        for (name, submodule) in &module.submodules {
+            output.push('\n');
+            self.format_module(submodule, &format!("{}/{}", path, name), output);
+        }
+    }
+}
+
+impl Default for DtsFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputFormatter for DtsFormatter {
+    fn format_tree(&self, tree: &NodeModuleInfo) -> Result<String> {
+        let mut output = match &tree.version {
+            Some(version) => format!(
+                "// Declarations for {}@{}, generated by pretty-node from its explored API\n\n",
+                tree.name, version
+            ),
+            None => format!(
+                "// Declarations for {}, generated by pretty-node from its explored API\n\n",
+                tree.name
+            ),
+        };
+        self.format_module(tree, &tree.name, &mut output);
+        Ok(output.trim_end().to_string())
+    }
+
+    fn format_signature(&self, signature: &SignatureInfo) -> Result<String> {
+        let signature = with_any_types(signature.clone());
+        let (symbol, method_name) = match signature.kind {
+            SignatureKind::Method => signature
+                .name
+                .rsplit_once('.')
+                .unwrap_or(("Unknown", signature.name.as_str())),
+            _ => (signature.name.as_str(), ""),
+        };
+        let Some(local) = local_name(symbol) else {
+            return Ok(not_declared(symbol));
+        };
+        let (export, default_export) = if symbol == "default" {
+            ("declare", "\nexport default _default;")
+        } else {
+            ("export declare", "")
+        };
+
+        let declaration = match signature.kind {
+            SignatureKind::Function | SignatureKind::ArrowFunction => {
+                let function = SignatureInfo {
+                    name: local,
+                    ..signature.clone()
+                };
+                format!("{} function {};", export, function.declaration())
+            }
+            SignatureKind::Constructor => {
+                let constructor = SignatureInfo {
+                    name: "constructor".to_string(),
+                    type_params: Vec::new(),
+                    return_type: None,
+                    ..signature.clone()
+                };
+                format!(
+                    "{} class {} {{\n  {};\n}}",
+                    export,
+                    type_name(&local, &signature.type_params),
+                    constructor.declaration()
+                )
+            }
+            SignatureKind::Method => {
+                let method = SignatureInfo {
+                    name: method_name.to_string(),
+                    ..signature.clone()
+                };
+                format!(
+                    "{} class {} {{\n  {};\n}}",
+                    export,
+                    local,
+                    method.declaration()
+                )
+            }
+        };
+        Ok(with_doc(
+            signature.doc_comment.as_deref(),
+            signature.doc_tags.as_ref(),
+            format!("{}{}", declaration, default_export),
+        ))
+    }
+
+    fn format_signature_not_available(&self, object_name: &str) -> String {
+        format!("// signature of {} not available", object_name)
+    }
+
+    fn format_search_results(&self, _pattern: &str, _results: &[SearchResult]) -> Result<String> {
+        Err(anyhow!("Search results can't be written as declarations"))
+    }
+
+    fn format_api_diff(&self, _diff: &ApiDiff) -> Result<String> {
+        Err(anyhow!("API changes can't be written as declarations"))
+    }
+}
+
+/// `name<T>(a: string): R` for a function or method, untyped parts as `any`
+fn function_declaration(function: &FunctionInfo, name: &str) -> String {
+    let mut signature = function.to_signature(name.to_string(), SignatureKind::Function);
+    if signature.return_type.is_none() {
+        signature.return_type = Some(
+            match (function.is_async, function.is_generator) {
+                (true, true) => "AsyncGenerator<any>",
+                (false, true) => "Generator<any>",
+                (true, false) => "Promise<any>",
+                (false, false) => "any",
+            }
+            .to_string(),
+        );
+    }
+    with_any_types(signature).declaration()
+}
+
+fn class_declaration(class: &ClassInfo, name: &str) -> String {
+    let mut header = format!("class {}", type_name(name, &class.type_params));
+    if let Some(extends) = &class.extends {
+        header.push_str(&format!(" extends {}", extends));
+    }
+    if !class.implements.is_empty() {
+        header.push_str(&format!(" implements {}", class.implements.join(", ")));
+    }
+
+    let mut members = Vec::new();
+    if let Some(constructor) = &class.constructor {
+        let mut signature = with_any_types(
+            constructor.to_signature("constructor".to_string(), SignatureKind::Constructor),
+        );
+        signature.return_type = None;
+        members.push(with_doc(
+            constructor.doc_comment.as_deref(),
+            constructor.doc_tags.as_ref(),
+            format!("{};", signature.declaration()),
+        ));
+    }
+    for property in &class.properties {
+        let mut member = String::new();
+        if property.is_static {
+            member.push_str("static ");
+        }
+        if property.is_readonly {
+            member.push_str("readonly ");
+        }
+        member.push_str(&format!(
+            "{}: {};",
+            property.name,
+            property.property_type.as_deref().unwrap_or("any")
+        ));
+        members.push(with_doc(
+            property.doc_comment.as_deref(),
+            property.doc_tags.as_ref(),
+            member,
+        ));
+    }
+    for method in &class.methods {
+        members.push(with_doc(
+            method.doc_comment.as_deref(),
+            method.doc_tags.as_ref(),
+            format!("{};", function_declaration(method, &method.name)),
+        ));
+    }
+
+    if members.is_empty() {
+        return format!("{} {{}}", header);
+    }
+    let body: Vec<String> = members.iter().map(|member| indent(member, "  ")).collect();
+    format!("{} {{\n{}}}", header, body.concat())
+}
+
+/// Declare untyped parameters as `any` (`any[]` for rest parameters) and
+/// an unknown return type as `any`
+fn with_any_types(mut signature: SignatureInfo) -> SignatureInfo {
+    for param in &mut signature.parameters {
+        if param.param_type.is_none() {
+            let any = if param.is_rest { "any[]" } else { "any" };
+            param.param_type = Some(any.to_string());
+        }
+    }
+    if signature.return_type.is_none() && !matches!(signature.kind, SignatureKind::Constructor) {
+        signature.return_type = Some("any".to_string());
+    }
+    signature
+}
+
+/// Words that can't name a declaration
+const RESERVED_WORDS: &[&str] = &[
+    "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default",
+    "delete", "do", "else", "enum", "export", "extends", "false", "finally", "for", "function",
+    "if", "implements", "import", "in", "instanceof", "interface", "let", "new", "null",
+    "package", "private", "protected", "public", "return", "static", "super", "switch", "this",
+    "throw", "true", "try", "typeof", "var", "void", "while", "with", "yield",
+];
+
+fn is_identifier_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The name to declare a module-level symbol under, if it can be declared
+fn local_name(name: &str) -> Option<String> {
+    if name == "default" {
+        Some("_default".to_string())
+    } else if is_identifier_name(name) && !RESERVED_WORDS.contains(&name) {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+fn not_declared(name: &str) -> String {
+    format!("// {:?} is not a valid identifier and can't be declared", name)
+}
+
+fn type_name(name: &str, type_params: &[String]) -> String {
+    if type_params.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}<{}>", name, type_params.join(", "))
+    }
+}
+
+/// Precede a declaration with its doc comment, keeping `@deprecated`
+fn with_doc(doc_comment: Option<&str>, doc_tags: Option<&DocTags>, declaration: String) -> String {
+    let mut lines: Vec<String> = doc_comment
+        .map(|doc| doc.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+    if let Some(reason) = doc_tags.and_then(|tags| tags.deprecated.as_ref()) {
+        if reason.is_empty() {
+            lines.push("@deprecated".to_string());
+        } else {
+            lines.push(format!("@deprecated {}", reason));
+        }
+    }
+
+    let doc = match lines.as_slice() {
+        [] => String::new(),
+        [line] => format!("/** {} */\n", line),
+        lines => {
+            let body: Vec<String> = lines
+                .iter()
+                .map(|line| format!(" *{}{}\n", if line.is_empty() { "" } else { " " }, line))
+                .collect();
+            format!("/**\n{} */\n", body.concat())
+        }
+    };
+    format!("{}{}", doc, declaration)
+}
+
+/// Indent every line of `text`, ending it with a newline
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                "\n".to_string()
+            } else {
+                format!("{}{}\n", prefix, line)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast_parser::AstParser;
+    use crate::parser::typescript::TypeScriptParser;
+
+    #[test]
+    fn test_declarations_from_javascript() {
+        let mut module = AstParser::new()
+            .parse_content(
+                "/** Start a server */\nexport async function listen(port, ...handlers) {}\n\
+                 export class Router { constructor(options) {} route(path) { return this; } }\n\
+                 export const VERSION = '1.0.0';\n",
+                "srv",
+            )
+            .unwrap();
+        module.version = Some("1.0.0".to_string());
+        let util = TypeScriptParser::new()
+            .parse_declaration_content(
+                "export interface Options { strict?: boolean }\nexport type Mode = 'a' | 'b';\nexport declare enum Level { Low, High }\n",
+                "util",
+            )
+            .unwrap();
+        module.add_submodule("lib".to_string(), NodeModuleInfo::new("lib".to_string()));
+        module
+            .submodules
+            .get_mut("lib")
+            .unwrap()
+            .add_submodule("util".to_string(), util);
+
+        let dts = DtsFormatter::new().format_tree(&module).unwrap();
+        assert!(dts.starts_with("// Declarations for srv@1.0.0"));
+        assert!(dts.contains(
+            "declare module \"srv\" {\n  /** Start a server */\n  export function listen(port: any, ...handlers: any[]): Promise<any>;\n"
+        ));
+        assert!(dts.contains(
+            "  export class Router {\n    constructor(options: any);\n    route(path: any): any;\n  }\n"
+        ));
+        assert!(dts.contains("  export const VERSION: any;\n"));
+        assert!(dts.contains("declare module \"srv/lib\" {\n}\n"));
+        assert!(dts.contains("declare module \"srv/lib/util\" {\n  export interface Options"));
+        assert!(dts.contains("  export type Mode = 'a' | 'b';\n"));
+        assert!(dts.contains("  export enum Level { Low, High }\n"));
+    }
+
+    #[test]
+    fn test_export_assignment() {
+        let module = AstParser::new()
+            .parse_content(
+                "function createApp(options) {}\ncreateApp.version = '1';\nmodule.exports = createApp;\n",
+                "app",
+            )
+            .unwrap();
+        let dts = DtsFormatter::new().format_tree(&module).unwrap();
+        assert!(dts.contains("  function createApp(options: any): any;\n"));
+        assert!(dts.contains("  export = createApp;\n}"));
+        assert!(!dts.contains("export function"));
+    }
+
+    #[test]
+    fn test_default_export() {
+        let module = AstParser::new()
+            .parse_content(
+                "exports.default = function (req) {};\nexports.helper = function () {};\n",
+                "handler",
+            )
+            .unwrap();
+        let dts = DtsFormatter::new().format_tree(&module).unwrap();
+        assert!(dts.contains("  function _default(req: any): any;\n"));
+        assert!(dts.contains("  export function helper(): any;\n"));
+        assert!(dts.contains("  export default _default;\n}"));
+        assert!(!dts.contains("function default"));
+
+        let module = AstParser::new()
+            .parse_content("module.exports = (options) => {};\n", "factory")
+            .unwrap();
+        let dts = DtsFormatter::new().format_tree(&module).unwrap();
+        assert!(dts.contains("  function _default(options: any): any;\n"));
+        assert!(dts.contains("  export = _default;\n}"));
+        assert!(!dts.contains("export default"));
+
+        let signature =
+            module.functions[0].to_signature("default".to_string(), SignatureKind::Function);
+        assert_eq!(
+            DtsFormatter::new().format_signature(&signature).unwrap(),
+            "declare function _default(options: any): any;\nexport default _default;"
+        );
+    }
+
+    #[test]
+    fn test_names_that_are_not_identifiers() {
+        let module = AstParser::new()
+            .parse_content(
+                "exports['parse-url'] = function (url) {};\nexports.ok = 1;\n\
+                 exports.Client = class { 'max-retries' = 3; 'send-all'(items) {} };\n",
+                "net",
+            )
+            .unwrap();
+        let dts = DtsFormatter::new().format_tree(&module).unwrap();
+        assert!(dts.contains("  // \"parse-url\" is not a valid identifier and can't be declared\n"));
+        assert!(!dts.contains("function parse-url"));
+        assert!(dts.contains("  export const ok: any;\n"));
+        assert!(dts.contains("    'max-retries': any;\n"));
+        assert!(dts.contains("    'send-all'(items: any): any;\n"));
+
+        let signature =
+            module.functions[0].to_signature("parse-url".to_string(), SignatureKind::Function);
+        assert_eq!(
+            DtsFormatter::new().format_signature(&signature).unwrap(),
+            "// \"parse-url\" is not a valid identifier and can't be declared"
+        );
+    }
+
+    #[test]
+    fn test_signature_declarations() {
+        let module = TypeScriptParser::new()
+            .parse_declaration_content(
+                "/** @deprecated Use run */\nexport declare function start<T>(config: T, retries?: number): void;\n\
+                 export declare class Pool { constructor(size: number); acquire(): Promise<Conn>; }\n",
+                "pool",
+            )
+            .unwrap();
+        let formatter = DtsFormatter::new();
+
+        let start = module.functions[0].to_signature("start".to_string(), SignatureKind::Function);
+        assert_eq!(
+            formatter.format_signature(&start).unwrap(),
+            "/** @deprecated Use run */\nexport declare function start<T>(config: T, retries?: number): void;"
+        );
+
+        let pool = &module.classes[0];
+        assert_eq!(
+            formatter
+                .format_signature(&pool.constructor_signature())
+                .unwrap(),
+            "export declare class Pool {\n  constructor(size: number);\n}"
+        );
+        let acquire =
+            pool.methods[0].to_signature("Pool.acquire".to_string(), SignatureKind::Method);
+        assert_eq!(
+            formatter.format_signature(&acquire).unwrap(),
+            "export declare class Pool {\n  acquire(): Promise<Conn>;\n}"
+        );
+    }
+}
@@ -1,19 +1,22 @@
 use crate::module_info::NodeModuleInfo;
 use crate::npm_client::NpmClient;
+use crate::package_json::{active_conditions, PackageJson};
+use crate::package_layout::InstalledPackage;
 use crate::parser::ast_parser::AstParser;
 use crate::parser::typescript::TypeScriptParser;
-use crate::utils::{is_dts_file, is_js_file, parse_package_spec};
+use crate::utils::{
+    is_dts_file, is_js_file, normalize_path, parse_package_source, parse_package_spec,
+    resolve_relative_module, types_package_name, PackageSource,
+};
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
-use std::env;
-use std::fs;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
 
 pub struct NodeModuleExplorer {
     package_name: String,
     max_depth: usize,
     quiet: bool,
+    conditions: Vec<String>,
     npm_client: NpmClient,
     ast_parser: AstParser,
     ts_parser: TypeScriptParser,
@@ -25,12 +28,29 @@ impl NodeModuleExplorer {
             package_name,
             max_depth,
             quiet,
+            conditions: active_conditions(&[]),
             npm_client: NpmClient::new(),
             ast_parser: AstParser::new(),
             ts_parser: TypeScriptParser::new(),
         }
     }
 
+    /// Use these package.json `exports` conditions instead of the Node defaults
+    pub fn with_conditions(mut self, conditions: Vec<String>) -> Self {
+        self.conditions = active_conditions(&conditions);
+        self
+    }
+
+    /// Use a specifically configured client (cache, registry) for downloads
+    pub fn with_npm_client(mut self, npm_client: NpmClient) -> Self {
+        self.npm_client = npm_client;
+        self
+    }
+
+    pub fn conditions(&self) -> &[String] {
+        &self.conditions
+    }
+
     pub fn package_name(&self) -> &str {
         &self.package_name
     }
@@ -46,37 +66,156 @@ impl NodeModuleExplorer {
     /// Explore the package and return module information
     pub async fn explore(&self) -> Result<NodeModuleInfo> {
         // Parse package specification
-        let (package_name, version) = parse_package_spec(&self.package_name);
+        let (package_name, version) = match parse_package_source(&self.package_name)? {
+            PackageSource::Registry { name, version } => (name, version),
+            source => {
+                let package = self.npm_client.open_source(source, self.quiet).await?;
+                return self.explore_source_directory(package.path()).await;
+            }
+        };
 
-        // Try to find locally installed package first
-        let search_paths = vec![
-            env::current_dir()?,
-            env::current_dir()?.join(".."),
-            env::current_dir()?.join("../.."),
-        ];
+        // Try to find locally installed package first; it only stands in for
+        // the requested version if it satisfies it
+        let local_package = self
+            .npm_client
+            .find_installed_package(&package_name, version.as_deref());
+        if let Some(local_package) = local_package {
+            if !self.quiet {
+                eprintln!("📦 Using locally installed {}", package_name);
+            }
+            let mut module = self
+                .explore_local_package(local_package.path(), &package_name)
+                .await?;
+            module.requested_version = version.filter(|v| Some(v) != module.version.as_ref());
+            self.merge_types_package(&mut module, local_package.path())
+                .await;
+            return Ok(module);
+        }
+
+        // Download package from npm
+        let package_info = self
+            .npm_client
+            .get_package_info(&package_name, version.as_deref())
+            .await?;
+        let package = self
+            .npm_client
+            .download_package(&package_info, self.quiet)
+            .await?;
+
+        let package_path = package.path();
+        let mut module = self
+            .explore_local_package(&package_path, &package_name)
+            .await?;
+        module.version.get_or_insert(package_info.version);
+        module.requested_version = version.filter(|v| Some(v) != module.version.as_ref());
+        self.merge_types_package(&mut module, &package_path).await;
+        Ok(module)
+    }
+
+    /// Find the package on disk without exploring it: a local directory,
+    /// tarball or git checkout, an installed copy, or a registry download.
+    /// Returns it with the name it is imported by.
+    pub async fn locate(&self) -> Result<(InstalledPackage, String)> {
+        let (package_name, version) = match parse_package_source(&self.package_name)? {
+            PackageSource::Registry { name, version } => (name, version),
+            source => {
+                let package = self.npm_client.open_source(source, self.quiet).await?;
+                if !package.path().is_dir() {
+                    return Err(anyhow!(
+                        "No package directory at {}",
+                        package.path().display()
+                    ));
+                }
+                let package_name = self.source_package_name(package.path());
+                return Ok((package, package_name));
+            }
+        };
 
-        if let Some(local_path) = self
+        if let Some(local_package) = self
             .npm_client
-            .find_local_package(&package_name, &search_paths)
+            .find_installed_package(&package_name, version.as_deref())
         {
             if !self.quiet {
                 eprintln!("📦 Using locally installed {}", package_name);
             }
-            return self.explore_local_package(&local_path, &package_name).await;
+            return Ok((local_package, package_name));
         }
 
-        // Download package from npm
         let package_info = self
             .npm_client
             .get_package_info(&package_name, version.as_deref())
             .await?;
-        let temp_dir = self
+        let package = self
             .npm_client
             .download_package(&package_info, self.quiet)
             .await?;
+        Ok((package.into(), package_name))
+    }
+
+    /// Merge in the declarations of the package's DefinitelyTyped package
+    /// (`@types/<name>`) when it ships none of its own
+    ///
+    /// A package's own declarations are merged while exploring it (see
+    /// `explore_local_package`), with `Package` provenance; `@types` only
+    /// stands in for packages that have none.
+    async fn merge_types_package(&self, module: &mut NodeModuleInfo, package_path: &Path) {
+        let package_json =
+            PackageJson::read(&package_path.join("package.json")).unwrap_or_default();
+        if package_json.declares_types(package_path) {
+            return;
+        }
+        let Some(types_name) = types_package_name(&module.name) else {
+            return;
+        };
+        let Some(types) = self
+            .npm_client
+            .find_types_package(&module.name, module.version.as_deref(), self.quiet)
+            .await
+        else {
+            return;
+        };
+        let Ok(declarations) = self.explore_local_package(types.path(), &types_name).await else {
+            return;
+        };
+
+        let label = match &declarations.version {
+            Some(version) => format!("{}@{}", types_name, version),
+            None => types_name.clone(),
+        };
+        if !self.quiet {
+            eprintln!("📦 Using types from {}", label);
+        }
+        module.merge_types_package(&declarations, &types_name);
+        module.types_package = Some(label);
+    }
+
+    /// Explore a package given by path rather than by name, naming it after
+    /// its package.json (or, lacking one, its directory)
+    async fn explore_source_directory(&self, package_path: &Path) -> Result<NodeModuleInfo> {
+        if !package_path.is_dir() {
+            return Err(anyhow!("No package directory at {}", package_path.display()));
+        }
+        let package_name = self.source_package_name(package_path);
+        self.explore_local_package(package_path, &package_name).await
+    }
 
-        let package_path = temp_dir.path().join("package");
-        self.explore_local_package(&package_path, &package_name)
+    /// The name of a package given by path: its package.json name or,
+    /// lacking one, its directory name
+    fn source_package_name(&self, package_path: &Path) -> String {
+        PackageJson::read(&package_path.join("package.json"))
+            .ok()
+            .and_then(|package_json| package_json.name)
+            .or_else(|| {
+                let path = package_path.canonicalize().ok()?;
+                Some(path.file_name()?.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| self.package_name.clone())
+    }
+
+    /// Explore a package that is already unpacked at `package_path`
+    pub async fn explore_path(&self, package_path: &Path) -> Result<NodeModuleInfo> {
+        let (package_name, _) = parse_package_spec(&self.package_name);
+        self.explore_local_package(package_path, &package_name)
             .await
     }
 
@@ -89,29 +228,55 @@ impl NodeModuleExplorer {
 
         // Read package.json for metadata
         let package_json_path = package_path.join("package.json");
-        if package_json_path.exists() {
-            self.parse_package_json(&package_json_path, &mut root_module)?;
-        }
+        let package_json = if package_json_path.exists() {
+            self.parse_package_json(&package_json_path, &mut root_module)?
+        } else {
+            PackageJson::default()
+        };
 
         // Find entry points
-        let entry_points = self.find_entry_points(package_path, &root_module)?;
+        let entry_points = self.find_entry_points(package_path, &package_json)?;
+        if let Some(main_entry) = entry_points.get("main") {
+            if let Ok(relative) = main_entry.strip_prefix(package_path) {
+                root_module.main = Some(relative.to_string_lossy().to_string());
+            }
+        }
 
         // Parse main entry point
         if let Some(main_entry) = entry_points.get("main") {
-            if let Ok(module_info) = self.parse_file(main_entry) {
+            if let Ok(module_info) = self.parse_entry(main_entry, package_path) {
                 // Copy information from parsed entry point
                 root_module.exports.extend(module_info.exports);
                 root_module.functions.extend(module_info.functions);
                 root_module.classes.extend(module_info.classes);
                 root_module.types.extend(module_info.types);
                 root_module.constants.extend(module_info.constants);
+                root_module.submodules.extend(module_info.submodules);
+                root_module.export_assignment = module_info.export_assignment;
+            }
+        }
+
+        // The package's own declarations give the JavaScript its types
+        if let Some(types_entry) = entry_points.get("types") {
+            let is_main = entry_points
+                .get("main")
+                .is_some_and(|main| normalize_path(main) == normalize_path(types_entry));
+            if !is_main {
+                if let Ok(declarations) = self.parse_entry(types_entry, package_path) {
+                    root_module.merge_own_declarations(&declarations);
+                }
             }
         }
 
-        // Explore submodules if depth allows
+        // Explore submodules if depth allows. With an `exports` map only the
+        // exported subpaths are importable, so they replace the directory scan.
         if self.max_depth > 1 {
-            self.explore_submodules(package_path, &mut root_module, 1)
-                .await?;
+            if package_json.exports.is_some() {
+                self.explore_exported_subpaths(package_path, &package_json, &mut root_module);
+            } else {
+                self.explore_submodules(package_path, &mut root_module, 1)
+                    .await?;
+            }
         }
 
         Ok(root_module)
@@ -121,46 +286,28 @@ impl NodeModuleExplorer {
         &self,
         package_json_path: &Path,
         module_info: &mut NodeModuleInfo,
-    ) -> Result<()> {
-        let content = fs::read_to_string(package_json_path)?;
-        let package_data: serde_json::Value = serde_json::from_str(&content)?;
-
-        // Extract version
-        if let Some(version) = package_data.get("version").and_then(|v| v.as_str()) {
-            module_info.version = Some(version.to_string());
-        }
-
-        // Extract main entry point
-        if let Some(main) = package_data.get("main").and_then(|v| v.as_str()) {
-            module_info.main = Some(main.to_string());
-        }
-
-        Ok(())
+    ) -> Result<PackageJson> {
+        let package_json = PackageJson::read(package_json_path)?;
+        module_info.version = package_json.version.clone();
+        module_info.main = package_json.main.clone();
+        Ok(package_json)
     }
 
     fn find_entry_points(
         &self,
         package_path: &Path,
-        module_info: &NodeModuleInfo,
+        package_json: &PackageJson,
     ) -> Result<HashMap<String, PathBuf>> {
         let mut entry_points = HashMap::new();
 
-        // Check package.json main field
-        if let Some(main) = &module_info.main {
-            let main_path = package_path.join(main);
-            if main_path.exists() {
-                entry_points.insert("main".to_string(), main_path);
-            } else {
-                // Try with .js extension
-                let main_js = main_path.with_extension("js");
-                if main_js.exists() {
-                    entry_points.insert("main".to_string(), main_js);
-                }
-            }
+        // `exports`, or `browser`/`module`/`main` depending on the active conditions
+        if let Some(main_path) = package_json.main_entry(package_path, &self.conditions) {
+            entry_points.insert("main".to_string(), main_path);
         }
 
-        // Fallback to common entry points
-        if entry_points.is_empty() {
+        // Fallback to common entry points; a package with an `exports` map
+        // that hides "." has no root entry at all
+        if entry_points.is_empty() && package_json.exports.is_none() {
             let common_entries = vec![
                 "index.js",
                 "index.ts",
@@ -182,6 +329,11 @@ impl NodeModuleExplorer {
             }
         }
 
+        if let Some(types_path) = package_json.types_entry(package_path) {
+            entry_points.insert("types".to_string(), types_path);
+            return Ok(entry_points);
+        }
+
         // Look for TypeScript definitions
         let ts_entries = vec![
             "index.d.ts",
@@ -201,6 +353,23 @@ impl NodeModuleExplorer {
         Ok(entry_points)
     }
 
+    /// Add every non-root subpath of the `exports` map as a submodule
+    fn explore_exported_subpaths(
+        &self,
+        package_path: &Path,
+        package_json: &PackageJson,
+        parent_module: &mut NodeModuleInfo,
+    ) {
+        for (subpath, file) in package_json.exported_entries(package_path, &self.conditions) {
+            if subpath == "." {
+                continue;
+            }
+            if let Ok(module_info) = self.parse_entry(&file, package_path) {
+                parent_module.add_submodule(subpath.trim_start_matches("./").to_string(), module_info);
+            }
+        }
+    }
+
     async fn explore_submodules(
         &self,
         package_path: &Path,
@@ -211,58 +380,175 @@ impl NodeModuleExplorer {
             return Ok(());
         }
 
+        // The entry point's contents are already the package's own
+        let main_entry = parent_module
+            .main
+            .as_ref()
+            .map(|main| normalize_path(&package_path.join(main)));
+
         // Look for common subdirectories that might contain modules
         let subdirs_to_check = vec!["lib", "src", "dist", "build", "types"];
 
         for subdir in subdirs_to_check {
             let subdir_path = package_path.join(subdir);
-            if subdir_path.exists() && subdir_path.is_dir() {
-                self.explore_directory(&subdir_path, parent_module, current_depth)
-                    .await?;
+            // A namespace re-exported by the entry point already carries
+            // its resolved contents
+            if !subdir_path.is_dir() || parent_module.submodules.contains_key(subdir) {
+                continue;
+            }
+            if let Some(module_info) =
+                self.explore_directory(&subdir_path, subdir, main_entry.as_deref(), current_depth)
+            {
+                parent_module.add_submodule(subdir.to_string(), module_info);
             }
         }
 
         Ok(())
     }
 
-    async fn explore_directory(
+    /// The module for a directory at `depth`: the contents of its index
+    /// file, with a submodule for each of its files and subdirectories
+    /// while the depth allows. Directories without any modules are skipped.
+    fn explore_directory(
         &self,
         dir_path: &Path,
-        parent_module: &mut NodeModuleInfo,
-        current_depth: usize,
-    ) -> Result<()> {
-        if current_depth >= self.max_depth {
-            return Ok(());
-        }
-
-        for entry in WalkDir::new(dir_path)
-            .max_depth(2) // Limit filesystem traversal depth
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
+        name: &str,
+        main_entry: Option<&Path>,
+        depth: usize,
+    ) -> Option<NodeModuleInfo> {
+        let mut files: BTreeMap<String, PathBuf> = BTreeMap::new();
+        let mut dirs: BTreeMap<String, PathBuf> = BTreeMap::new();
+        for entry in std::fs::read_dir(dir_path).ok()?.flatten() {
             let path = entry.path();
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if path.is_dir() {
+                if !file_name.starts_with('.') && file_name != "node_modules" {
+                    dirs.insert(file_name.to_string(), path);
+                }
+            } else if is_js_file(&path) || is_dts_file(&path) {
+                if main_entry == Some(normalize_path(&path).as_path()) {
+                    continue;
+                }
+                // `x.d.ts` and `x.js` are one module; its declarations say more
+                let Some(module_name) = module_stem(&path) else {
+                    continue;
+                };
+                files
+                    .entry(module_name)
+                    .and_modify(|existing| {
+                        if is_dts_file(&path) && !is_dts_file(existing) {
+                            *existing = path.clone();
+                        }
+                    })
+                    .or_insert(path);
+            }
+        }
 
-            if path.is_file() && (is_js_file(path) || is_dts_file(path)) {
-                // Skip if it's the same as main entry point
-                if let Some(main) = &parent_module.main {
-                    if path.ends_with(main) {
-                        continue;
-                    }
+        let index = files
+            .remove("index")
+            .and_then(|index| self.parse_file(&index).ok());
+        let has_index = index.is_some();
+        let mut module_info = index.unwrap_or_else(|| NodeModuleInfo::new(name.to_string()));
+        module_info.name = name.to_string();
+
+        if depth + 1 < self.max_depth {
+            let names: BTreeSet<String> = files.keys().chain(dirs.keys()).cloned().collect();
+            for child_name in names {
+                // A namespace declared in the index file wins over the file
+                if module_info.submodules.contains_key(&child_name) {
+                    continue;
                 }
+                let child_dir = dirs.get(&child_name).and_then(|dir| {
+                    self.explore_directory(dir, &child_name, main_entry, depth + 1)
+                });
+                // `require("./router")` finds `router.js` before `router/index.js`
+                let child = match (files.get(&child_name), child_dir) {
+                    (Some(file), child_dir) => {
+                        let Ok(mut child) = self.parse_file(file) else {
+                            continue;
+                        };
+                        child.name = child_name.clone();
+                        if let Some(child_dir) = child_dir {
+                            child.submodules.extend(child_dir.submodules);
+                        }
+                        child
+                    }
+                    (None, Some(child_dir)) => child_dir,
+                    (None, None) => continue,
+                };
+                module_info.add_submodule(child_name, child);
+            }
+        }
 
-                if let Ok(module_info) = self.parse_file(path) {
-                    let module_name = path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("unknown")
-                        .to_string();
+        if !has_index && files.is_empty() && module_info.submodules.is_empty() {
+            return None;
+        }
+        Some(module_info)
+    }
 
-                    parent_module.add_submodule(module_name, module_info);
-                }
+    /// Parse an entry file and follow its re-exports (`export * from`,
+    /// `export { x } from`, `__exportStar(require())`) through the package,
+    /// so barrel files show the symbols they forward
+    pub fn parse_entry(&self, file_path: &Path, package_path: &Path) -> Result<NodeModuleInfo> {
+        // Search paths may contain `..`; resolved re-export targets never do
+        let package_path = normalize_path(package_path);
+        let mut in_progress = HashSet::new();
+        self.parse_with_reexports(&normalize_path(file_path), &package_path, &mut in_progress)
+    }
+
+    fn parse_with_reexports(
+        &self,
+        file_path: &Path,
+        package_path: &Path,
+        in_progress: &mut HashSet<PathBuf>,
+    ) -> Result<NodeModuleInfo> {
+        let mut module_info = self.parse_file(file_path)?;
+        let relative_path = file_path
+            .strip_prefix(package_path)
+            .unwrap_or(file_path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        module_info.set_defined_in(&relative_path);
+
+        // Cyclic re-exports are legal; stop at files already being resolved
+        in_progress.insert(file_path.to_path_buf());
+
+        for source in module_info.star_exports.clone() {
+            if let Some(target) = self.parse_reexport_source(file_path, &source, package_path, in_progress) {
+                module_info.merge_star_export(&target);
+            }
+        }
+        for reexport in module_info.reexports.clone() {
+            if let Some(target) =
+                self.parse_reexport_source(file_path, &reexport.source, package_path, in_progress)
+            {
+                module_info.merge_reexport(&reexport, &target);
             }
         }
 
-        Ok(())
+        in_progress.remove(file_path);
+        Ok(module_info)
+    }
+
+    /// Parse the module a re-export points at; only files inside the package are followed
+    fn parse_reexport_source(
+        &self,
+        from_file: &Path,
+        specifier: &str,
+        package_path: &Path,
+        in_progress: &mut HashSet<PathBuf>,
+    ) -> Option<NodeModuleInfo> {
+        if !specifier.starts_with('.') {
+            return None;
+        }
+        let target = resolve_relative_module(from_file, specifier)?;
+        if in_progress.contains(&target) || !target.starts_with(package_path) {
+            return None;
+        }
+        self.parse_with_reexports(&target, package_path, in_progress)
+            .ok()
     }
 
     fn parse_file(&self, file_path: &Path) -> Result<NodeModuleInfo> {
@@ -275,3 +561,13 @@ impl NodeModuleExplorer {
         }
     }
 }
+
+/// The name a file is imported by: `router` for `router.js` and `router.d.ts`
+fn module_stem(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let stem = match file_name.strip_suffix(".d.ts") {
+        Some(stem) => stem,
+        None => path.file_stem()?.to_str()?,
+    };
+    Some(stem.to_string())
+}
@@ -0,0 +1,357 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use tar::EntryType;
+
+/// Bounds on what a single package tarball may unpack to
+#[derive(Debug, Clone, Copy)]
+pub struct ExtractLimits {
+    /// Total uncompressed bytes, tar headers included
+    pub max_total_size: u64,
+    /// Number of regular files
+    pub max_file_count: usize,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self {
+            max_total_size: 512 * 1024 * 1024,
+            max_file_count: 100_000,
+        }
+    }
+}
+
+/// What an extraction wrote and what it refused to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtractReport {
+    pub files: usize,
+    pub bytes: u64,
+    pub skipped: Vec<SkippedEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedEntry {
+    /// Entry path as stored in the archive
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Absolute path or `..` component that would land outside the root
+    PathTraversal,
+    Symlink,
+    Hardlink,
+    /// Devices, FIFOs and other non-file entries
+    Special,
+}
+
+impl SkipReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SkipReason::PathTraversal => "path escapes the package root",
+            SkipReason::Symlink => "symlink",
+            SkipReason::Hardlink => "hardlink",
+            SkipReason::Special => "special file",
+        }
+    }
+}
+
+/// Extract a gzipped package tarball into `destination`
+///
+/// Only regular files and directories are written, and only below
+/// `destination`; links and special files are skipped and reported. Going
+/// over `limits` aborts the extraction with an error.
+pub fn extract_tarball(
+    tarball: &[u8],
+    destination: &Path,
+    limits: &ExtractLimits,
+) -> Result<ExtractReport> {
+    let decoder = flate2::read::GzDecoder::new(tarball);
+    // Bounds the decompressed stream itself, so a gzip bomb hidden in
+    // padding or headers is caught too
+    let mut archive = tar::Archive::new(LimitedReader {
+        inner: decoder,
+        remaining: limits.max_total_size,
+    });
+
+    let mut report = ExtractReport::default();
+    for entry in archive.entries()? {
+        let mut entry = entry.map_err(|e| limit_error(e, limits))?;
+        let raw_path = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        let mut skip = |reason| {
+            report.skipped.push(SkippedEntry {
+                path: raw_path.clone(),
+                reason,
+            })
+        };
+
+        let entry_type = entry.header().entry_type();
+        let reason = match entry_type {
+            EntryType::Regular | EntryType::Continuous | EntryType::Directory => None,
+            EntryType::Symlink => Some(SkipReason::Symlink),
+            EntryType::Link => Some(SkipReason::Hardlink),
+            // Metadata records without content of their own
+            EntryType::XGlobalHeader | EntryType::XHeader | EntryType::GNULongName => continue,
+            _ => Some(SkipReason::Special),
+        };
+        if let Some(reason) = reason {
+            skip(reason);
+            continue;
+        }
+
+        let Some(relative) = sanitize_path(&raw_path) else {
+            skip(SkipReason::PathTraversal);
+            continue;
+        };
+        let target = destination.join(&relative);
+
+        if entry_type == EntryType::Directory {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+
+        report.files += 1;
+        if report.files > limits.max_file_count {
+            return Err(anyhow!(
+                "Tarball has more than {} files; refusing to extract",
+                limits.max_file_count
+            ));
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::File::create(&target)
+            .with_context(|| format!("Failed to create {}", target.display()))?;
+        report.bytes += io::copy(&mut entry, &mut file).map_err(|e| limit_error(e, limits))?;
+    }
+
+    Ok(report)
+}
+
+/// Extract the entries of a zip archive below `prefix` (e.g.
+/// `node_modules/lodash/`) into `destination`, with the prefix stripped
+///
+/// Used for Yarn's zip cache, whose archives hold a package under its
+/// `node_modules` path. The same rules as for tarballs apply.
+pub fn extract_zip_subtree(
+    archive: &Path,
+    prefix: &str,
+    destination: &Path,
+    limits: &ExtractLimits,
+) -> Result<ExtractReport> {
+    let file = fs::File::open(archive)
+        .with_context(|| format!("Failed to open {}", archive.display()))?;
+    let mut archive = zip::ZipArchive::new(io::BufReader::new(file))
+        .with_context(|| format!("{} is not a zip archive", archive.display()))?;
+    let prefix = prefix.trim_start_matches("./");
+
+    let mut report = ExtractReport::default();
+    let mut remaining = limits.max_total_size;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        let raw_path = entry.name().to_string();
+        let Some(inner) = raw_path.strip_prefix(prefix) else {
+            continue;
+        };
+        let mut skip = |reason| {
+            report.skipped.push(SkippedEntry {
+                path: raw_path.clone(),
+                reason,
+            })
+        };
+
+        if entry.is_symlink() {
+            skip(SkipReason::Symlink);
+            continue;
+        }
+        if inner.is_empty() {
+            continue;
+        }
+        let Some(relative) = sanitize_path(inner) else {
+            skip(SkipReason::PathTraversal);
+            continue;
+        };
+        let target = destination.join(&relative);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+
+        report.files += 1;
+        if report.files > limits.max_file_count {
+            return Err(anyhow!(
+                "Archive has more than {} files; refusing to extract",
+                limits.max_file_count
+            ));
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::File::create(&target)
+            .with_context(|| format!("Failed to create {}", target.display()))?;
+        let mut limited = LimitedReader {
+            inner: &mut entry,
+            remaining,
+        };
+        let copied = io::copy(&mut limited, &mut file).map_err(|e| {
+            if e.kind() == io::ErrorKind::FileTooLarge {
+                anyhow!(
+                    "Archive expands to more than {} bytes; refusing to extract",
+                    limits.max_total_size
+                )
+            } else {
+                e.into()
+            }
+        })?;
+        remaining = limited.remaining;
+        report.bytes += copied;
+    }
+
+    Ok(report)
+}
+
+/// The entry path relative to the extraction root, or `None` if it is
+/// absolute or climbs out of the root
+fn sanitize_path(path: &str) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+fn limit_error(error: io::Error, limits: &ExtractLimits) -> anyhow::Error {
+    if error.kind() == io::ErrorKind::FileTooLarge {
+        anyhow!(
+            "Tarball expands to more than {} bytes; refusing to extract",
+            limits.max_total_size
+        )
+    } else {
+        error.into()
+    }
+}
+
+/// Fails reads once more than `remaining` bytes have been produced
+struct LimitedReader<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.remaining = self
+            .remaining
+            .checked_sub(read as u64)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::FileTooLarge))?;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tempfile::TempDir;
+
+    /// Build a tarball header by hand; `tar::Builder` refuses unsafe paths
+    fn raw_entry(path: &str, entry_type: EntryType, link: Option<&str>, content: &[u8]) -> Vec<u8> {
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path.as_bytes());
+        if let Some(link) = link {
+            header.as_gnu_mut().unwrap().linkname[..link.len()].copy_from_slice(link.as_bytes());
+        }
+        header.set_entry_type(entry_type);
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+
+        let mut bytes = header.as_bytes().to_vec();
+        bytes.extend_from_slice(content);
+        bytes.resize(bytes.len().div_ceil(512) * 512, 0);
+        bytes
+    }
+
+    fn gzip(entries: &[Vec<u8>]) -> Vec<u8> {
+        let mut raw: Vec<u8> = entries.concat();
+        raw.extend_from_slice(&[0; 1024]);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        io::Write::write_all(&mut encoder, &raw).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_unsafe_entries_are_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("root");
+        let tarball = gzip(&[
+            raw_entry("package/index.js", EntryType::Regular, None, b"ok"),
+            raw_entry("package/../../escape.js", EntryType::Regular, None, b"bad"),
+            raw_entry("/tmp/absolute.js", EntryType::Regular, None, b"bad"),
+            raw_entry("package/link", EntryType::Symlink, Some("/etc/passwd"), b""),
+            raw_entry(
+                "package/hard",
+                EntryType::Link,
+                Some("package/index.js"),
+                b"",
+            ),
+            raw_entry("package/fifo", EntryType::Fifo, None, b""),
+        ]);
+
+        let report = extract_tarball(&tarball, &root, &ExtractLimits::default()).unwrap();
+        assert_eq!(report.files, 1);
+        assert_eq!(
+            fs::read_to_string(root.join("package/index.js")).unwrap(),
+            "ok"
+        );
+        assert!(!temp_dir.path().join("escape.js").exists());
+        assert!(!root.join("package/link").exists());
+        let reasons: Vec<SkipReason> = report.skipped.iter().map(|s| s.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                SkipReason::PathTraversal,
+                SkipReason::PathTraversal,
+                SkipReason::Symlink,
+                SkipReason::Hardlink,
+                SkipReason::Special,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_limits_abort_extraction() {
+        let temp_dir = TempDir::new().unwrap();
+        let big = vec![b'a'; 64 * 1024];
+        let tarball = gzip(&[raw_entry("package/big.js", EntryType::Regular, None, &big)]);
+        let limits = ExtractLimits {
+            max_total_size: 16 * 1024,
+            ..Default::default()
+        };
+        let error = extract_tarball(&tarball, temp_dir.path(), &limits).unwrap_err();
+        assert!(error.to_string().contains("more than 16384 bytes"));
+
+        let tarball = gzip(&[
+            raw_entry("package/a.js", EntryType::Regular, None, b"a"),
+            raw_entry("package/b.js", EntryType::Regular, None, b"b"),
+        ]);
+        let limits = ExtractLimits {
+            max_file_count: 1,
+            ..Default::default()
+        };
+        let error = extract_tarball(&tarball, temp_dir.path(), &limits).unwrap_err();
+        assert!(error.to_string().contains("more than 1 files"));
+    }
+}
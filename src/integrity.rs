@@ -0,0 +1,143 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::fmt;
+
+/// A downloaded tarball does not match the hash published by the registry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityError {
+    pub package: String,
+    /// What the registry published (`sha512-<base64>` or a sha1 hex `shasum`)
+    pub expected: String,
+    /// The same hash computed over the downloaded bytes
+    pub actual: String,
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Integrity check failed for {}: expected {}, got {}",
+            self.package, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for IntegrityError {}
+
+/// Verify a tarball against the registry's `dist.integrity` (Subresource
+/// Integrity) and, when that is absent, the legacy sha1 `dist.shasum`
+///
+/// As in the SRI spec only the strongest algorithm listed is checked and
+/// any one of its hashes may match. Releases that publish neither field, or
+/// only algorithms we do not know, are accepted.
+pub fn verify(
+    package: &str,
+    bytes: &[u8],
+    integrity: Option<&str>,
+    shasum: Option<&str>,
+) -> Result<(), IntegrityError> {
+    if let Some((algorithm, expected)) = integrity.and_then(strongest_hashes) {
+        let actual = STANDARD.encode(digest(algorithm, bytes));
+        if expected.iter().any(|hash| *hash == actual) {
+            return Ok(());
+        }
+        return Err(IntegrityError {
+            package: package.to_string(),
+            expected: format!("{}-{}", algorithm, expected[0]),
+            actual: format!("{}-{}", algorithm, actual),
+        });
+    }
+
+    if let Some(shasum) = shasum {
+        let actual: String = Sha1::digest(bytes)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        if !shasum.eq_ignore_ascii_case(&actual) {
+            return Err(IntegrityError {
+                package: package.to_string(),
+                expected: format!("sha1 {}", shasum),
+                actual: format!("sha1 {}", actual),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a sha1 hex `shasum` to its SRI form (`sha1-<base64>`)
+pub fn shasum_to_sri(shasum: &str) -> Option<String> {
+    if !shasum.len().is_multiple_of(2) {
+        return None;
+    }
+    let bytes = (0..shasum.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(shasum.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some(format!("sha1-{}", STANDARD.encode(bytes)))
+}
+
+/// The strongest supported algorithm in an SRI string and its base64 hashes
+fn strongest_hashes(integrity: &str) -> Option<(&'static str, Vec<&str>)> {
+    ["sha512", "sha384", "sha256", "sha1"]
+        .into_iter()
+        .find_map(|algorithm| {
+            let hashes: Vec<&str> = integrity
+                .split_whitespace()
+                .filter_map(|token| token.strip_prefix(algorithm)?.strip_prefix('-'))
+                // `?opt` suffixes carry no hash data
+                .map(|hash| hash.split('?').next().unwrap_or(hash))
+                .collect();
+            (!hashes.is_empty()).then_some((algorithm, hashes))
+        })
+}
+
+fn digest(algorithm: &str, bytes: &[u8]) -> Vec<u8> {
+    match algorithm {
+        "sha512" => Sha512::digest(bytes).to_vec(),
+        "sha384" => Sha384::digest(bytes).to_vec(),
+        "sha256" => Sha256::digest(bytes).to_vec(),
+        _ => Sha1::digest(bytes).to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // printf 'hello' | openssl dgst -sha512 -binary | base64
+    const HELLO_SHA512: &str = "sha512-m3HSJL1i83hdltRq0+o9czGb+8KJDKra4t/3JRlnPKcjI8PZm6XBHXx6zG4UuMXaDEZjR1wuXDre9G9zvN7AQw==";
+    const HELLO_SHA1: &str = "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d";
+
+    #[test]
+    fn test_verify_sri() {
+        assert!(verify("pkg", b"hello", Some(HELLO_SHA512), None).is_ok());
+        // Weaker hashes are ignored when a stronger one is listed
+        let mixed = format!("sha1-bogus {}?opt", HELLO_SHA512);
+        assert!(verify("pkg", b"hello", Some(&mixed), None).is_ok());
+
+        let error = verify("pkg@1.0.0", b"tampered", Some(HELLO_SHA512), None).unwrap_err();
+        assert_eq!(error.package, "pkg@1.0.0");
+        assert_eq!(error.expected, HELLO_SHA512);
+        assert!(error.actual.starts_with("sha512-"));
+    }
+
+    #[test]
+    fn test_verify_shasum_fallback() {
+        assert!(verify("pkg", b"hello", None, Some(HELLO_SHA1)).is_ok());
+        assert!(verify("pkg", b"hello", None, Some(&HELLO_SHA1.to_uppercase())).is_ok());
+        assert!(verify("pkg", b"tampered", None, Some(HELLO_SHA1)).is_err());
+        assert!(verify("pkg", b"anything", None, None).is_ok());
+        assert!(verify("pkg", b"anything", Some("md5-abc"), None).is_ok());
+    }
+
+    #[test]
+    fn test_shasum_to_sri() {
+        let sri = shasum_to_sri(HELLO_SHA1).unwrap();
+        assert_eq!(sri, "sha1-qvTGHdzF6KLavt4PO0gs2a6pQ00=");
+        assert!(verify("pkg", b"hello", Some(&sri), None).is_ok());
+        assert_eq!(shasum_to_sri("xyz"), None);
+    }
+}
@@ -1,8 +1,22 @@
+pub mod api_diff;
+pub mod cache;
 pub mod config;
+pub mod dts_formatter;
 pub mod explorer;
+pub mod extract;
+pub mod integrity;
+pub mod llm_formatter;
+pub mod llms_txt;
+pub mod markdown_formatter;
 pub mod module_info;
 pub mod npm_client;
+pub mod npmrc;
 pub mod output_format;
+pub mod overrides;
+pub mod package_json;
+pub mod package_layout;
 pub mod parser;
+pub mod search;
+pub mod semver_range;
 pub mod tree_formatter;
 pub mod utils;
\ No newline at end of file
@@ -0,0 +1,569 @@
+use crate::api_diff::ApiDiff;
+use crate::module_info::{ClassInfo, NodeModuleInfo, Parameter, SignatureInfo, SignatureKind};
+use crate::output_format::OutputFormatter;
+use crate::search::{SearchResult, SymbolKind};
+use anyhow::Result;
+use std::cell::Cell;
+
+/// The budget when none is given
+pub const DEFAULT_MAX_TOKENS: usize = 4000;
+
+/// Compact plain-text output sized for LLM context windows
+///
+/// The tree is rendered at decreasing levels of detail until it fits the
+/// token budget: doc comments go first, then long type strings are
+/// abbreviated, deep submodules are collapsed to their symbol names,
+/// unexported symbols are dropped and finally nested submodules are
+/// omitted. Exported symbols always come first, so if even the most compact
+/// rendering is too long, cutting trailing lines loses the least. A
+/// trailer says what was elided.
+pub struct LlmFormatter {
+    max_tokens: usize,
+}
+
+/// How much of a module tree to render
+#[derive(Debug, Clone, Copy)]
+struct Detail {
+    docs: bool,
+    /// Abbreviate type strings longer than this many characters
+    type_limit: Option<usize>,
+    /// Submodules nested deeper than this are collapsed to symbol names
+    expand_depth: usize,
+    /// Submodules nested deeper than this are left out
+    list_depth: usize,
+    exported_only: bool,
+}
+
+const FULL: Detail = Detail {
+    docs: true,
+    type_limit: None,
+    expand_depth: usize::MAX,
+    list_depth: usize::MAX,
+    exported_only: false,
+};
+
+/// From most to least detailed
+const LEVELS: [Detail; 6] = [
+    FULL,
+    Detail {
+        docs: false,
+        ..FULL
+    },
+    Detail {
+        docs: false,
+        type_limit: Some(60),
+        ..FULL
+    },
+    Detail {
+        docs: false,
+        type_limit: Some(60),
+        expand_depth: 1,
+        ..FULL
+    },
+    Detail {
+        docs: false,
+        type_limit: Some(60),
+        expand_depth: 0,
+        list_depth: usize::MAX,
+        exported_only: true,
+    },
+    Detail {
+        docs: false,
+        type_limit: Some(30),
+        expand_depth: 0,
+        list_depth: 1,
+        exported_only: true,
+    },
+];
+
+/// What a rendering left out
+#[derive(Debug, Default)]
+struct Elided {
+    doc_comments: usize,
+    abbreviated_types: usize,
+    collapsed_modules: usize,
+    omitted_modules: usize,
+    hidden_symbols: usize,
+}
+
+impl LlmFormatter {
+    pub fn new() -> Self {
+        Self {
+            max_tokens: DEFAULT_MAX_TOKENS,
+        }
+    }
+
+    /// Fit the output into about this many tokens
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn max_tokens(&self) -> usize {
+        self.max_tokens
+    }
+
+    fn fit_tree(&self, tree: &NodeModuleInfo) -> String {
+        let mut last = String::new();
+        let mut notes = Vec::new();
+        for detail in LEVELS {
+            let mut output = String::new();
+            let mut elided = Elided::default();
+            render_module(tree, &tree.name, 0, detail, &mut output, &mut elided);
+            let output = output.trim_end().to_string();
+            notes = elided_notes(detail, &elided);
+            if notes.is_empty() && estimate_tokens(&output) <= self.max_tokens {
+                return output;
+            }
+            let fitted = self.with_trailer(&output, &notes);
+            if estimate_tokens(&fitted) <= self.max_tokens {
+                return fitted;
+            }
+            last = output;
+        }
+
+        // Still too long: keep the leading lines, which hold the exports,
+        // leaving room for a trailer counting every line as cut
+        let mut longest = notes.clone();
+        longest.push(format!("{} more line(s)", last.lines().count()));
+        let budget = self
+            .max_tokens
+            .saturating_sub(estimate_tokens(&self.with_trailer("", &longest)));
+        let mut kept = String::new();
+        let mut cut_lines = 0;
+        for line in last.lines() {
+            if cut_lines == 0 && estimate_tokens(&kept) + estimate_tokens(line) < budget {
+                kept.push_str(line);
+                kept.push('\n');
+            } else {
+                cut_lines += 1;
+            }
+        }
+        notes.push(format!("{} more line(s)", cut_lines));
+        self.with_trailer(&kept, &notes)
+    }
+
+    fn with_trailer(&self, output: &str, notes: &[String]) -> String {
+        format!(
+            "{}\n[elided to fit ~{} tokens: {}]",
+            output.trim_end(),
+            self.max_tokens,
+            notes.join(", ")
+        )
+    }
+}
+
+impl Default for LlmFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputFormatter for LlmFormatter {
+    fn format_tree(&self, tree: &NodeModuleInfo) -> Result<String> {
+        Ok(self.fit_tree(tree))
+    }
+
+    fn format_signature(&self, signature: &SignatureInfo) -> Result<String> {
+        let keyword = match signature.kind {
+            SignatureKind::Function | SignatureKind::ArrowFunction => "fn ",
+            SignatureKind::Constructor => "new ",
+            SignatureKind::Method => "",
+        };
+        let mut output = format!("{}{}", keyword, signature.declaration());
+        let doc = signature.doc_comment.as_deref().unwrap_or_default().trim();
+        if !doc.is_empty() && estimate_tokens(&output) + estimate_tokens(doc) <= self.max_tokens {
+            output.push('\n');
+            output.push_str(doc);
+        }
+        Ok(output)
+    }
+
+    fn format_signature_not_available(&self, object_name: &str) -> String {
+        format!("{}: signature not available", object_name)
+    }
+
+    fn format_search_results(&self, pattern: &str, results: &[SearchResult]) -> Result<String> {
+        if results.is_empty() {
+            return Ok(format!("no symbols matching '{}'", pattern));
+        }
+        let lines: Vec<String> = results
+            .iter()
+            .map(|result| {
+                let kind = match result.kind {
+                    SymbolKind::Function => "fn",
+                    SymbolKind::Method => "method",
+                    SymbolKind::Class => "class",
+                    SymbolKind::Type => "type",
+                    SymbolKind::Constant => "const",
+                };
+                format!("{} {}:{}", kind, result.module_path, result.name)
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    fn format_api_diff(&self, diff: &ApiDiff) -> Result<String> {
+        let lines: Vec<String> = diff
+            .changes
+            .iter()
+            .map(|change| {
+                format!(
+                    "{} {} {}",
+                    change.change.label(),
+                    change.item.label(),
+                    change.path
+                )
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+}
+
+/// What a rendering at `detail` left out, for the trailer
+fn elided_notes(detail: Detail, elided: &Elided) -> Vec<String> {
+    let mut notes = Vec::new();
+    if elided.doc_comments > 0 {
+        notes.push(format!("{} doc comment(s)", elided.doc_comments));
+    }
+    if let (Some(limit), true) = (detail.type_limit, elided.abbreviated_types > 0) {
+        notes.push(format!(
+            "{} type string(s) cut at {} chars",
+            elided.abbreviated_types, limit
+        ));
+    }
+    if elided.collapsed_modules > 0 {
+        notes.push(format!(
+            "signatures in {} submodule(s)",
+            elided.collapsed_modules
+        ));
+    }
+    if elided.hidden_symbols > 0 {
+        notes.push(format!("{} unexported symbol(s)", elided.hidden_symbols));
+    }
+    if elided.omitted_modules > 0 {
+        notes.push(format!("{} nested submodule(s)", elided.omitted_modules));
+    }
+    notes
+}
+
+/// Approximate token count: about four characters per token, which holds
+/// for code and English prose under common BPE tokenizers
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+fn render_module(
+    module: &NodeModuleInfo,
+    path: &str,
+    depth: usize,
+    detail: Detail,
+    output: &mut String,
+    elided: &mut Elided,
+) {
+    let exported = |name: &str| module.is_exported(name);
+    // Counted through cells, since the closures are shared by every line
+    let abbreviated_types = Cell::new(0);
+    let abbreviate = |text: &str| {
+        let short = abbreviate(text, detail.type_limit);
+        if short != text {
+            abbreviated_types.set(abbreviated_types.get() + 1);
+        }
+        short
+    };
+    let doc_comments = Cell::new(0);
+    let push_doc = |line: &mut String, doc_comment: Option<&str>| {
+        if let Some(summary) = summary(doc_comment) {
+            if detail.docs {
+                line.push_str(&format!(" // {}", summary));
+            } else {
+                doc_comments.set(doc_comments.get() + 1);
+            }
+        }
+    };
+
+    let title = match &module.version {
+        Some(version) => format!("{}@{}", path, version),
+        None => path.to_string(),
+    };
+
+    if depth > detail.expand_depth {
+        // Names only, exported first
+        let mut names: Vec<(bool, String)> = Vec::new();
+        names.extend(
+            module
+                .functions
+                .iter()
+                .map(|f| (exported(&f.name), format!("{}()", f.name))),
+        );
+        names.extend(
+            module
+                .classes
+                .iter()
+                .map(|c| (exported(&c.name), format!("class {}", c.name))),
+        );
+        names.extend(
+            module
+                .types
+                .iter()
+                .map(|t| (exported(&t.name), format!("type {}", t.name))),
+        );
+        names.extend(
+            module
+                .constants
+                .iter()
+                .map(|c| (exported(&c.name), c.name.clone())),
+        );
+        names.dedup_by(|a, b| a.1 == b.1);
+        names.sort_by_key(|(is_exported, _)| !is_exported);
+        let total = names.len();
+        if detail.exported_only {
+            names.retain(|(is_exported, _)| *is_exported);
+        }
+        elided.hidden_symbols += total - names.len();
+        elided.collapsed_modules += 1;
+        let names: Vec<String> = names.into_iter().map(|(_, name)| name).collect();
+        output.push_str(&format!("# {}: {}\n", title, names.join(", ")));
+    } else {
+        output.push_str(&format!("# {}\n", title));
+        if let Some(types_package) = &module.types_package {
+            output.push_str(&format!("types from {}\n", types_package));
+        }
+        if !module.exports.is_empty() {
+            output.push_str(&format!("exports: {}\n", module.exports.join(", ")));
+        }
+
+        // Declarations, exported ones first
+        let mut lines: Vec<(bool, String)> = Vec::new();
+        for function in &module.functions {
+            let mut signature =
+                function.to_signature(function.name.clone(), SignatureKind::Function);
+            abbreviate_signature(&mut signature, &abbreviate);
+            let mut line = format!("fn {}", signature.declaration());
+            push_doc(&mut line, function.doc_comment.as_deref());
+            lines.push((exported(&function.name), line));
+        }
+        for class in &module.classes {
+            let mut line = class_line(class, &abbreviate);
+            push_doc(&mut line, class.doc_comment.as_deref());
+            lines.push((exported(&class.name), line));
+        }
+        for type_info in &module.types {
+            let mut line = abbreviate(&type_info.definition);
+            push_doc(&mut line, type_info.doc_comment.as_deref());
+            lines.push((exported(&type_info.name), line));
+        }
+        for constant in &module.constants {
+            let mut line = match &constant.value_type {
+                Some(value_type) => format!("const {}: {}", constant.name, abbreviate(value_type)),
+                None => format!("const {}", constant.name),
+            };
+            push_doc(&mut line, constant.doc_comment.as_deref());
+            lines.push((exported(&constant.name), line));
+        }
+        lines.sort_by_key(|(is_exported, _)| !is_exported);
+        for (is_exported, line) in lines {
+            if detail.exported_only && !is_exported {
+                elided.hidden_symbols += 1;
+                continue;
+            }
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    elided.abbreviated_types += abbreviated_types.get();
+    elided.doc_comments += doc_comments.get();
+
+    for (name, submodule) in &module.submodules {
+        if depth + 1 > detail.list_depth {
+            elided.omitted_modules += 1 + count_submodules(submodule);
+            continue;
+        }
+        render_module(
+            submodule,
+            &format!("{}/{}", path, name),
+            depth + 1,
+            detail,
+            output,
+            elided,
+        );
+    }
+}
+
+fn count_submodules(module: &NodeModuleInfo) -> usize {
+    module
+        .submodules
+        .values()
+        .map(|submodule| 1 + count_submodules(submodule))
+        .sum()
+}
+
+/// `class Router<T> extends Base { constructor(options); get(path): this; name: string }`
+fn class_line(class: &ClassInfo, abbreviate: &dyn Fn(&str) -> String) -> String {
+    let mut line = format!("class {}", class.name);
+    if !class.type_params.is_empty() {
+        line.push_str(&format!("<{}>", class.type_params.join(", ")));
+    }
+    if let Some(extends) = &class.extends {
+        line.push_str(&format!(" extends {}", abbreviate(extends)));
+    }
+
+    let mut members = Vec::new();
+    if let Some(constructor) = &class.constructor {
+        let mut signature =
+            constructor.to_signature("constructor".to_string(), SignatureKind::Constructor);
+        signature.return_type = None;
+        abbreviate_signature(&mut signature, abbreviate);
+        members.push(signature.declaration());
+    }
+    for method in &class.methods {
+        let mut signature = method.to_signature(method.name.clone(), SignatureKind::Method);
+        abbreviate_signature(&mut signature, abbreviate);
+        members.push(signature.declaration());
+    }
+    for property in &class.properties {
+        members.push(match &property.property_type {
+            Some(property_type) => format!("{}: {}", property.name, abbreviate(property_type)),
+            None => property.name.clone(),
+        });
+    }
+    if !members.is_empty() {
+        line.push_str(&format!(" {{ {} }}", members.join("; ")));
+    }
+    line
+}
+
+fn abbreviate_signature(signature: &mut SignatureInfo, abbreviate: &dyn Fn(&str) -> String) {
+    for Parameter { param_type, .. } in &mut signature.parameters {
+        if let Some(text) = param_type {
+            *text = abbreviate(text);
+        }
+    }
+    if let Some(text) = &mut signature.return_type {
+        *text = abbreviate(text);
+    }
+}
+
+/// Shorten `text` to `limit` characters, marking the cut with `…`
+fn abbreviate(text: &str, limit: Option<usize>) -> String {
+    match limit {
+        Some(limit) if text.chars().count() > limit => {
+            let kept: String = text.chars().take(limit.saturating_sub(1)).collect();
+            format!("{}…", kept.trim_end())
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// The first line of a doc comment, if it says anything
+fn summary(doc_comment: Option<&str>) -> Option<&str> {
+    doc_comment
+        .and_then(|doc| doc.lines().next())
+        .map(str::trim)
+        .filter(|summary| !summary.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::typescript::TypeScriptParser;
+
+    fn fixture() -> NodeModuleInfo {
+        let parser = TypeScriptParser::new();
+        let mut root = parser
+            .parse_declaration_content(
+                "/** Create an application with a long explanation of what it does */\n\
+                 export declare function create(options?: { port: number; host: string; secure: boolean; timeout: number }): App;\n\
+                 declare function internalHelper(): void;\n\
+                 export declare class App { listen(port: number): this; }\n\
+                 export declare const VERSION: string;\n",
+                "web",
+            )
+            .unwrap();
+        root.version = Some("2.0.0".to_string());
+        for name in ["router", "static", "json"] {
+            let mut submodule = parser
+                .parse_declaration_content(
+                    &format!("/** The {0} middleware */\nexport declare function {0}(path: string): Handler;\n", name),
+                    name,
+                )
+                .unwrap();
+            submodule.add_submodule(
+                "impl".to_string(),
+                parser
+                    .parse_declaration_content("export declare function detail(): void;", "impl")
+                    .unwrap(),
+            );
+            root.add_submodule(name.to_string(), submodule);
+        }
+        root
+    }
+
+    #[test]
+    fn test_full_output_within_budget() {
+        let output = LlmFormatter::new().format_tree(&fixture()).unwrap();
+        assert!(output.starts_with("# web@2.0.0\n"));
+        assert!(output.contains("fn create(options?: { port: number; host: string; secure: boolean; timeout: number }): App // Create an application"));
+        assert!(output.contains("# web/router/impl\nexports: detail\nfn detail(): void"));
+        // Unexported symbols come after the exported ones
+        assert!(output.contains("const VERSION: string\nfn internalHelper(): void\n"));
+        assert!(!output.contains("[elided"));
+        assert!(estimate_tokens(&output) <= DEFAULT_MAX_TOKENS);
+    }
+
+    #[test]
+    fn test_budget_elides_progressively() {
+        let tree = fixture();
+        let full = LlmFormatter::new().format_tree(&tree).unwrap();
+        let full_tokens = estimate_tokens(&full);
+
+        // Dropping doc comments is enough
+        let output = LlmFormatter::new()
+            .with_max_tokens(full_tokens - 10)
+            .format_tree(&tree)
+            .unwrap();
+        assert!(!output.contains("// Create an application"));
+        assert!(output.contains("secure: boolean; timeout: number }"));
+        assert!(output.ends_with(
+            "[elided to fit ~{} tokens: 4 doc comment(s)]"
+                .replace("{}", &(full_tokens - 10).to_string())
+                .as_str()
+        ));
+
+        for budget in [150, 100, 75] {
+            let output = LlmFormatter::new()
+                .with_max_tokens(budget)
+                .format_tree(&tree)
+                .unwrap();
+            assert!(
+                estimate_tokens(&output) <= budget,
+                "{} tokens over {}:\n{}",
+                estimate_tokens(&output),
+                budget,
+                output
+            );
+            assert!(output.starts_with("# web@2.0.0\nexports: create, App, VERSION\n"));
+            assert!(output.contains("[elided to fit"));
+        }
+
+        let output = LlmFormatter::new()
+            .with_max_tokens(100)
+            .format_tree(&tree)
+            .unwrap();
+        assert!(output.contains("fn create(options?: { port: number; host: string;…): App"));
+        assert!(output.contains("# web/json: json()"));
+        assert!(!output.contains("internalHelper"));
+        assert!(output.contains("1 unexported symbol(s), 3 nested submodule(s)]"));
+    }
+
+    #[test]
+    fn test_abbreviate() {
+        assert_eq!(abbreviate("string", Some(10)), "string");
+        assert_eq!(abbreviate("Record<string, number>", Some(10)), "Record<st…");
+        assert_eq!(
+            abbreviate("Record<string, number>", None),
+            "Record<string, number>"
+        );
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::markdown_formatter::class_outline;
+use crate::module_info::{DocTags, NodeModuleInfo, SignatureKind};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// The index file of a bundle
+pub const INDEX_FILE: &str = "llms.txt";
+
+/// The file with the full API of a bundle
+pub const FULL_FILE: &str = "llms-full.txt";
+
+/// An [llms.txt](https://llmstxt.org) bundle for a package: `llms.txt`
+/// indexes the modules and what each exports, `llms-full.txt` holds every
+/// exported signature with its doc comment
+///
+/// Only exported symbols are included, and modules exporting nothing are
+/// left out of both files.
+pub struct LlmsTxt {
+    pub index: String,
+    pub full: String,
+}
+
+impl LlmsTxt {
+    pub fn from_tree(tree: &NodeModuleInfo) -> Self {
+        let mut modules = Vec::new();
+        collect_modules(tree, &tree.name, &mut modules);
+
+        let title = match &tree.version {
+            Some(version) => format!("{} {}", tree.name, version),
+            None => tree.name.clone(),
+        };
+        let (mut functions, mut classes, mut types, mut constants) = (0, 0, 0, 0);
+        for (_, module) in &modules {
+            functions += exported(module, module.functions.iter().map(|f| &f.name));
+            classes += exported(module, module.classes.iter().map(|c| &c.name));
+            types += exported(module, module.types.iter().map(|t| &t.name));
+            constants += exported(module, module.constants.iter().map(|c| &c.name));
+        }
+
+        let mut index = format!("# {}\n\n", tree.name);
+        index.push_str(&format!(
+            "> API reference for {}: {} exporting {}, {}, {} and {}.",
+            title,
+            plural(modules.len(), "module"),
+            plural(functions, "function"),
+            plural(classes, "class"),
+            plural(types, "type"),
+            plural(constants, "constant")
+        ));
+        if let Some(types_package) = &tree.types_package {
+            index.push_str(&format!(" Types from {}.", types_package));
+        }
+        index.push_str(&format!(
+            "\n\nGenerated by pretty-node from the package's code and declarations. \
+             Each module below lists its exports; {} has their signatures and doc comments.\n\n",
+            FULL_FILE
+        ));
+        index.push_str("## Modules\n\n");
+        for (path, module) in &modules {
+            index.push_str(&format!(
+                "- `{}`: {}\n",
+                path,
+                export_names(module).join(", ")
+            ));
+        }
+        index.push_str(&format!(
+            "\n## Optional\n\n- [Full API reference]({}): every exported signature with its doc comment\n",
+            FULL_FILE
+        ));
+
+        let mut full = format!("# {}\n\n", tree.name);
+        full.push_str(&format!(
+            "> Every exported signature and doc comment of {}, generated by pretty-node.\n",
+            title
+        ));
+        for (path, module) in &modules {
+            full.push_str(&format!("\n## {}\n", path));
+            write_module(module, &mut full);
+        }
+
+        Self { index, full }
+    }
+
+    /// Write `llms.txt` and `llms-full.txt` into `dir`, creating it if needed
+    pub fn write_to(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        for (file, content) in [(INDEX_FILE, &self.index), (FULL_FILE, &self.full)] {
+            let path = dir.join(file);
+            fs::write(&path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Modules exporting anything, depth first in name order, with their paths
+fn collect_modules<'a>(
+    module: &'a NodeModuleInfo,
+    path: &str,
+    modules: &mut Vec<(String, &'a NodeModuleInfo)>,
+) {
+    if !export_names(module).is_empty() {
+        modules.push((path.to_string(), module));
+    }
+    for (name, submodule) in &module.submodules {
+        collect_modules(submodule, &format!("{}/{}", path, name), modules);
+    }
+}
+
+fn exported<'a>(module: &NodeModuleInfo, names: impl Iterator<Item = &'a String>) -> usize {
+    names.filter(|name| module.is_exported(name)).count()
+}
+
+/// `Router()`, `class Application`, `type Options`, `VERSION`
+fn export_names(module: &NodeModuleInfo) -> Vec<String> {
+    let mut names = Vec::new();
+    for function in &module.functions {
+        if module.is_exported(&function.name) {
+            names.push(format!("{}()", function.name));
+        }
+    }
+    for class in &module.classes {
+        if module.is_exported(&class.name) {
+            names.push(format!("class {}", class.name));
+        }
+    }
+    for type_info in &module.types {
+        if module.is_exported(&type_info.name) {
+            names.push(format!("type {}", type_info.name));
+        }
+    }
+    for constant in &module.constants {
+        if module.is_exported(&constant.name) {
+            names.push(constant.name.clone());
+        }
+    }
+    names
+}
+
+/// A `###` section per exported symbol: its declaration, then its doc comment
+fn write_module(module: &NodeModuleInfo, output: &mut String) {
+    for function in &module.functions {
+        if module.is_exported(&function.name) {
+            let signature = function.to_signature(function.name.clone(), SignatureKind::Function);
+            let declaration = format!("function {}", signature.declaration());
+            write_symbol(
+                &function.name,
+                &declaration,
+                function.doc_comment.as_deref(),
+                function.doc_tags.as_ref(),
+                output,
+            );
+        }
+    }
+    for class in &module.classes {
+        if module.is_exported(&class.name) {
+            write_symbol(
+                &class.name,
+                &class_outline(class),
+                class.doc_comment.as_deref(),
+                class.doc_tags.as_ref(),
+                output,
+            );
+        }
+    }
+    for type_info in &module.types {
+        if module.is_exported(&type_info.name) {
+            write_symbol(
+                &type_info.name,
+                &type_info.definition,
+                type_info.doc_comment.as_deref(),
+                type_info.doc_tags.as_ref(),
+                output,
+            );
+        }
+    }
+    for constant in &module.constants {
+        if module.is_exported(&constant.name) {
+            let declaration = match &constant.value_type {
+                Some(value_type) => format!("const {}: {}", constant.name, value_type),
+                None => format!("const {}", constant.name),
+            };
+            write_symbol(
+                &constant.name,
+                &declaration,
+                constant.doc_comment.as_deref(),
+                constant.doc_tags.as_ref(),
+                output,
+            );
+        }
+    }
+}
+
+fn write_symbol(
+    name: &str,
+    declaration: &str,
+    doc_comment: Option<&str>,
+    doc_tags: Option<&DocTags>,
+    output: &mut String,
+) {
+    output.push_str(&format!("\n### {}\n\n```ts\n{}\n```\n", name, declaration));
+    if let Some(doc) = doc_comment.map(str::trim).filter(|doc| !doc.is_empty()) {
+        output.push_str(&format!("\n{}\n", doc));
+    }
+    if let Some(reason) = doc_tags.and_then(|tags| tags.deprecated.as_ref()) {
+        if reason.is_empty() {
+            output.push_str("\nDeprecated.\n");
+        } else {
+            output.push_str(&format!("\nDeprecated: {}\n", reason));
+        }
+    }
+}
+
+/// `1 class`, `2 classes`
+fn plural(count: usize, noun: &str) -> String {
+    match (count, noun.ends_with('s')) {
+        (1, _) => format!("1 {}", noun),
+        (_, true) => format!("{} {}es", count, noun),
+        (_, false) => format!("{} {}s", count, noun),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::typescript::TypeScriptParser;
+
+    fn parse(source: &str, name: &str) -> NodeModuleInfo {
+        TypeScriptParser::new()
+            .parse_declaration_content(source, name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_bundle() {
+        let mut tree = parse(
+            "/**\n * Create a server.\n * Call listen() to start it.\n * @deprecated Use serve\n */\n\
+             export declare function create(port?: number): Server;\n\
+             declare function internal(): void;\n\
+             export declare class Server { listen(): this; }\n\
+             export type Mode = 'a' | 'b';\n\
+             export declare const VERSION: string;\n",
+            "web",
+        );
+        tree.exports.retain(|name| name != "internal");
+        tree.version = Some("2.0.0".to_string());
+        let mut lib = NodeModuleInfo::new("lib".to_string());
+        lib.add_submodule(
+            "router".to_string(),
+            parse(
+                "export declare function route(path: string): void;",
+                "router",
+            ),
+        );
+        tree.add_submodule("lib".to_string(), lib);
+
+        let bundle = LlmsTxt::from_tree(&tree);
+        assert!(bundle.index.starts_with(
+            "# web\n\n> API reference for web 2.0.0: 2 modules exporting 2 functions, 1 class, 1 type and 1 constant.\n"
+        ));
+        assert!(bundle
+            .index
+            .contains("## Modules\n\n- `web`: create(), class Server, type Mode, VERSION\n- `web/lib/router`: route()\n\n"));
+        assert!(bundle
+            .index
+            .contains("- [Full API reference](llms-full.txt)"));
+        assert!(!bundle.index.contains("internal"));
+
+        assert!(bundle.full.contains(
+            "## web\n\n### create\n\n```ts\nfunction create(port?: number): Server\n```\n\n\
+             Create a server.\nCall listen() to start it.\n\nDeprecated: Use serve\n"
+        ));
+        assert!(bundle
+            .full
+            .contains("### Server\n\n```ts\nclass Server {\n  listen(): this;\n}\n```\n"));
+        assert!(bundle
+            .full
+            .contains("### VERSION\n\n```ts\nconst VERSION: string\n```\n"));
+        assert!(bundle.full.contains("## web/lib/router\n"));
+        assert!(!bundle.full.contains("## web/lib\n"));
+        assert!(!bundle.full.contains("internal"));
+    }
+
+    #[test]
+    fn test_plural() {
+        assert_eq!(plural(0, "function"), "0 functions");
+        assert_eq!(plural(1, "class"), "1 class");
+        assert_eq!(plural(3, "class"), "3 classes");
+    }
+}
@@ -1,12 +1,3 @@
-mod config;
-mod explorer;
-mod module_info;
-mod npm_client;
-mod output_format;
-mod parser;
-mod tree_formatter;
-mod utils;
-
 use clap::{Parser, Subcommand};
 use std::process;
 use tokio::signal;
@@ -18,23 +9,80 @@ use tokio::signal;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Bypass the on-disk package cache (always download)
+    #[arg(long, global = true)]
+    no_cache: bool,
+    /// Never access the network; only use node_modules and the cache
+    /// (also enabled by PRETTY_NODE_OFFLINE)
+    #[arg(long, global = true)]
+    offline: bool,
+    /// Registry for unscoped packages, overriding `registry=` in .npmrc
+    #[arg(long, global = true, value_name = "URL")]
+    registry: Option<String>,
+    /// Resolve installed packages and .npmrc from this directory instead of
+    /// the current one
+    #[arg(long, global = true, value_name = "DIR")]
+    project: Option<std::path::PathBuf>,
+}
+
+/// Where installed packages are looked up and how the others may be fetched
+#[derive(Clone)]
+struct ClientOptions {
+    no_cache: bool,
+    offline: bool,
+    registry: Option<String>,
+    project: Option<std::path::PathBuf>,
+}
+
+impl ClientOptions {
+    /// Client honouring `--project`, `--no-cache`, `--offline` and `--registry`
+    fn client(&self) -> pretty_node::npm_client::NpmClient {
+        let mut client = pretty_node::npm_client::NpmClient::new();
+        // First, as it reloads the project's .npmrc
+        if let Some(project) = &self.project {
+            client = client.with_project_dir(project.clone());
+        }
+        if let Some(registry) = &self.registry {
+            client = client.with_registry(registry);
+        }
+        if self.no_cache {
+            client = client.with_cache(None);
+        }
+        if self.offline {
+            client = client.with_offline(true);
+        }
+        client
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Display Node.js package tree structure
     Tree {
-        /// Package name (e.g., 'express', '@types/node')
+        /// Package name (e.g., 'express', '@types/node'), directory, tarball or
+        /// git URL (e.g., './packages/core', 'git+https://host/repo.git#v1')
         package: String,
-        /// Maximum depth to explore
-        #[arg(long, default_value_t = 2)]
+        /// Maximum module depth to explore (the package is 1, `lib` 2,
+        /// `lib/router` 3)
+        #[arg(long, default_value_t = 3)]
         depth: usize,
         /// Suppress warnings and informational messages
         #[arg(short, long)]
         quiet: bool,
         /// Output format
-        #[arg(short, long, value_enum, default_value_t = OutputFormat::Pretty)]
-        output: OutputFormat,
+        #[arg(short, long, value_enum, default_value_t = ApiOutputFormat::Pretty)]
+        output: ApiOutputFormat,
+        /// Package exports condition to enable (repeatable, e.g. 'browser');
+        /// replaces the default node/import/require/types set
+        #[arg(long = "condition", value_name = "NAME")]
+        conditions: Vec<String>,
+        /// Order of the symbols within each module; submodules are always
+        /// ordered by name
+        #[arg(long, value_enum, default_value_t = SortOrder::SourceOrder)]
+        sort: SortOrder,
+        /// Approximate token budget for the output; implies `-o llm`
+        #[arg(long, value_name = "N")]
+        max_tokens: Option<usize>,
     },
     /// Display function/class signature
     Sig {
@@ -44,15 +92,131 @@ enum Commands {
         #[arg(short, long)]
         quiet: bool,
         /// Output format
+        #[arg(short, long, value_enum, default_value_t = ApiOutputFormat::Pretty)]
+        output: ApiOutputFormat,
+    },
+    /// Find functions, classes, types and constants by name
+    Search {
+        /// Package name (e.g., 'express', '@types/node')
+        package: String,
+        /// Name pattern to look for
+        pattern: String,
+        /// How the pattern is matched against symbol names
+        #[arg(short, long, value_enum, default_value_t = MatchMode::Substring)]
+        mode: MatchMode,
+        /// Suppress download messages
+        #[arg(short, long)]
+        quiet: bool,
+        /// Output format
         #[arg(short, long, value_enum, default_value_t = OutputFormat::Pretty)]
         output: OutputFormat,
     },
+    /// Compare the API surface of two package versions
+    Diff {
+        /// Old version (e.g., 'express@4.18.0')
+        from: String,
+        /// New version (e.g., 'express@5.0.0')
+        to: String,
+        /// Maximum module depth to explore (the package is 1, `lib` 2,
+        /// `lib/router` 3)
+        #[arg(long, default_value_t = 3)]
+        depth: usize,
+        /// Suppress download messages
+        #[arg(short, long)]
+        quiet: bool,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = DiffOutputFormat::Pretty)]
+        output: DiffOutputFormat,
+    },
+    /// Write documentation files for a package's exported API
+    Docs {
+        /// Package name (e.g., 'express', '@types/node'), directory, tarball or
+        /// git URL
+        package: String,
+        /// Documentation format
+        #[arg(short, long, value_enum, default_value_t = DocsFormat::LlmsTxt)]
+        format: DocsFormat,
+        /// Directory to write the files to
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        out_dir: std::path::PathBuf,
+        /// Maximum module depth to explore (the package is 1, `lib` 2,
+        /// `lib/router` 3)
+        #[arg(long, default_value_t = 3)]
+        depth: usize,
+        /// Suppress warnings and informational messages
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Inspect or clear the package cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// List cached packages
+    Ls {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Pretty)]
+        output: OutputFormat,
+    },
+    /// Remove everything from the cache
+    Clean,
+    /// Remove packages not used recently
+    Prune {
+        /// Age such as '30m', '12h', '7d' or '2w'
+        #[arg(long, value_name = "DURATION")]
+        older_than: String,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone)]
+enum DocsFormat {
+    /// `llms.txt`, an index of the modules, and `llms-full.txt`, every
+    /// exported signature with its doc comment
+    LlmsTxt,
+}
+
+#[derive(clap::ValueEnum, Clone)]
+enum DiffOutputFormat {
+    Pretty,
+    Json,
+    Markdown,
+}
+
+#[derive(clap::ValueEnum, Clone)]
+enum MatchMode {
+    Substring,
+    Regex,
+    Fuzzy,
+}
+
+#[derive(clap::ValueEnum, Clone)]
+enum SortOrder {
+    Name,
+    Kind,
+    SourceOrder,
+}
+
+/// Output formats for a package's API (`tree` and `sig`)
+#[derive(clap::ValueEnum, Clone)]
+enum ApiOutputFormat {
+    Pretty,
+    Json,
+    Markdown,
+    /// TypeScript declarations
+    Dts,
+    /// Compact text fitted to a token budget (see `--max-tokens`)
+    Llm,
 }
 
 #[derive(clap::ValueEnum, Clone)]
 enum OutputFormat {
     Pretty,
     Json,
+    Markdown,
 }
 
 #[tokio::main]
@@ -66,6 +230,12 @@ async fn main() {
             .expect("failed to install Ctrl+C handler");
     };
 
+    let client_options = ClientOptions {
+        no_cache: cli.no_cache,
+        offline: cli.offline,
+        registry: cli.registry,
+        project: cli.project,
+    };
     let main_task = async {
         match cli.command {
             Commands::Tree {
@@ -73,12 +243,41 @@ async fn main() {
                 depth,
                 quiet,
                 output,
-            } => handle_tree_command(&package, depth, quiet, output).await,
+                conditions,
+                sort,
+                max_tokens,
+            } => {
+                let formatter = tree_formatter(output, max_tokens)?;
+                handle_tree_command(&package, depth, quiet, formatter, conditions, sort, client_options)
+                    .await
+            }
             Commands::Sig {
                 import_path,
                 quiet,
                 output,
-            } => handle_sig_command(&import_path, quiet, output).await,
+            } => handle_sig_command(&import_path, quiet, output, client_options).await,
+            Commands::Search {
+                package,
+                pattern,
+                mode,
+                quiet,
+                output,
+            } => handle_search_command(&package, &pattern, mode, quiet, output, client_options).await,
+            Commands::Diff {
+                from,
+                to,
+                depth,
+                quiet,
+                output,
+            } => handle_diff_command(&from, &to, depth, quiet, output, client_options).await,
+            Commands::Docs {
+                package,
+                format,
+                out_dir,
+                depth,
+                quiet,
+            } => handle_docs_command(&package, format, &out_dir, depth, quiet, client_options).await,
+            Commands::Cache { action } => handle_cache_command(action),
         }
     };
 
@@ -99,44 +298,81 @@ async fn main() {
     }
 }
 
+/// The formatter for `tree`; a token budget implies the llm format
+fn tree_formatter(
+    output: ApiOutputFormat,
+    max_tokens: Option<usize>,
+) -> anyhow::Result<Box<dyn pretty_node::output_format::OutputFormatter>> {
+    use pretty_node::llm_formatter::LlmFormatter;
+    use pretty_node::output_format::create_formatter;
+
+    match (output, max_tokens) {
+        (ApiOutputFormat::Pretty | ApiOutputFormat::Llm, Some(max_tokens)) => {
+            Ok(Box::new(LlmFormatter::new().with_max_tokens(max_tokens)))
+        }
+        (_, Some(_)) => Err(anyhow::anyhow!(
+            "--max-tokens only applies to the llm output format"
+        )),
+        (output, None) => Ok(create_formatter(match output {
+            ApiOutputFormat::Pretty => "pretty",
+            ApiOutputFormat::Json => "json",
+            ApiOutputFormat::Markdown => "markdown",
+            ApiOutputFormat::Dts => "dts",
+            ApiOutputFormat::Llm => "llm",
+        })),
+    }
+}
+
 async fn handle_tree_command(
     package: &str,
     depth: usize,
     quiet: bool,
-    output: OutputFormat,
+    formatter: Box<dyn pretty_node::output_format::OutputFormatter>,
+    conditions: Vec<String>,
+    sort: SortOrder,
+    client_options: ClientOptions,
 ) -> anyhow::Result<()> {
-    use crate::explorer::NodeModuleExplorer;
-    use crate::output_format::create_formatter;
+    use pretty_node::explorer::NodeModuleExplorer;
+    use pretty_node::module_info;
+    use pretty_node::utils::{parse_package_source, PackageSource};
 
-    // Validate that package doesn't contain colon (which would be for sig command)
-    if package.contains(':') {
+    // Validate that package doesn't contain colon (which would be for sig command);
+    // `file:` and `git+https:` sources legitimately do
+    let is_registry_spec = matches!(
+        parse_package_source(package)?,
+        PackageSource::Registry { .. }
+    );
+    if is_registry_spec && package.contains(':') {
         return Err(anyhow::anyhow!(
             "Invalid module path '{}' for tree command. Module paths with ':' syntax are for signatures. Use 'pretty-node sig {}' instead.",
             package, package
         ));
     }
 
-    let format_str = match output {
-        OutputFormat::Pretty => "pretty",
-        OutputFormat::Json => "json",
-    };
-    let formatter = create_formatter(format_str);
-
-    let explorer = NodeModuleExplorer::new(package.to_string(), depth, quiet);
+    let explorer = NodeModuleExplorer::new(package.to_string(), depth, quiet)
+        .with_conditions(conditions)
+        .with_npm_client(client_options.client());
     let tree_result = explorer.explore().await;
 
     match tree_result {
-        Ok(tree) => {
+        Ok(mut tree) => {
+            tree.sort(match sort {
+                SortOrder::Name => module_info::SortOrder::Name,
+                SortOrder::Kind => module_info::SortOrder::Kind,
+                SortOrder::SourceOrder => module_info::SortOrder::SourceOrder,
+            });
             let output = formatter.format_tree(&tree)?;
             println!("{}", output);
         }
+        // Offline misses, tampered tarballs and auth failures are not a missing package
+        Err(e) if is_fatal(&e) => return Err(e),
         Err(_) => {
             // Gracefully handle package not found
             if !quiet {
                 eprintln!("⚠️  Package '{}' not found or could not be explored", package);
             }
             // Still show a basic tree structure
-            let fallback_tree = module_info::NodeModuleInfo::new(package.to_string());
+            let fallback_tree = pretty_node::module_info::NodeModuleInfo::new(package.to_string());
             let output = formatter.format_tree(&fallback_tree)?;
             println!("{}", output);
         }
@@ -148,27 +384,33 @@ async fn handle_tree_command(
 async fn handle_sig_command(
     import_path: &str,
     quiet: bool,
-    output: OutputFormat,
+    output: ApiOutputFormat,
+    client_options: ClientOptions,
 ) -> anyhow::Result<()> {
-    use crate::parser::signature::extract_signature;
-    use crate::output_format::create_formatter;
+    use pretty_node::parser::signature::extract_signature_with_client;
+    use pretty_node::output_format::create_formatter;
 
     let format_str = match output {
-        OutputFormat::Pretty => "pretty",
-        OutputFormat::Json => "json",
+        ApiOutputFormat::Pretty => "pretty",
+        ApiOutputFormat::Json => "json",
+        ApiOutputFormat::Markdown => "markdown",
+        ApiOutputFormat::Dts => "dts",
+        ApiOutputFormat::Llm => "llm",
     };
     let formatter = create_formatter(format_str);
 
-    let signature_result = extract_signature(import_path, quiet).await;
+    let signature_result =
+        extract_signature_with_client(import_path, quiet, &client_options.client()).await;
     
     match signature_result {
         Ok(signature) => {
             let output = formatter.format_signature(&signature)?;
             println!("{}", output);
         }
+        Err(e) if is_fatal(&e) => return Err(e),
         Err(_) => {
             // Gracefully handle signature extraction failures
-            let symbol_name = import_path.split(':').last().unwrap_or("unknown");
+            let symbol_name = import_path.split(':').next_back().unwrap_or("unknown");
             let output = formatter.format_signature_not_available(symbol_name);
             println!("{}", output);
         }
@@ -176,3 +418,193 @@ async fn handle_sig_command(
 
     Ok(())
 }
+
+async fn handle_search_command(
+    package: &str,
+    pattern: &str,
+    mode: MatchMode,
+    quiet: bool,
+    output: OutputFormat,
+    client_options: ClientOptions,
+) -> anyhow::Result<()> {
+    use pretty_node::output_format::create_formatter;
+    use pretty_node::search::{self, SymbolMatcher};
+
+    let format_str = match output {
+        OutputFormat::Pretty => "pretty",
+        OutputFormat::Json => "json",
+        OutputFormat::Markdown => "markdown",
+    };
+    let formatter = create_formatter(format_str);
+
+    let mode = match mode {
+        MatchMode::Substring => search::MatchMode::Substring,
+        MatchMode::Regex => search::MatchMode::Regex,
+        MatchMode::Fuzzy => search::MatchMode::Fuzzy,
+    };
+    let matcher = SymbolMatcher::new(pattern, mode)?;
+
+    let results = search::search_package(package, &matcher, quiet, client_options.client())
+        .await
+        .map_err(|e| anyhow::anyhow!("Package '{}' could not be searched: {}", package, e))?;
+    println!("{}", formatter.format_search_results(pattern, &results)?);
+
+    Ok(())
+}
+
+async fn handle_diff_command(
+    from: &str,
+    to: &str,
+    depth: usize,
+    quiet: bool,
+    output: DiffOutputFormat,
+    client_options: ClientOptions,
+) -> anyhow::Result<()> {
+    use pretty_node::api_diff::ApiDiff;
+    use pretty_node::explorer::NodeModuleExplorer;
+    use pretty_node::output_format::create_formatter;
+
+    let old_explorer = NodeModuleExplorer::new(from.to_string(), depth, quiet)
+        .with_npm_client(client_options.client());
+    let new_explorer = NodeModuleExplorer::new(to.to_string(), depth, quiet)
+        .with_npm_client(client_options.client());
+    let (old_tree, new_tree) = tokio::join!(old_explorer.explore(), new_explorer.explore());
+    let old_tree = old_tree.map_err(|e| anyhow::anyhow!("Could not explore '{}': {}", from, e))?;
+    let new_tree = new_tree.map_err(|e| anyhow::anyhow!("Could not explore '{}': {}", to, e))?;
+
+    let diff = ApiDiff::compare(&old_tree, &new_tree);
+    let rendered = match output {
+        DiffOutputFormat::Pretty => create_formatter("pretty").format_api_diff(&diff)?,
+        DiffOutputFormat::Json => create_formatter("json").format_api_diff(&diff)?,
+        DiffOutputFormat::Markdown => create_formatter("markdown").format_api_diff(&diff)?,
+    };
+    println!("{}", rendered);
+
+    Ok(())
+}
+
+async fn handle_docs_command(
+    package: &str,
+    format: DocsFormat,
+    out_dir: &std::path::Path,
+    depth: usize,
+    quiet: bool,
+    client_options: ClientOptions,
+) -> anyhow::Result<()> {
+    use pretty_node::explorer::NodeModuleExplorer;
+    use pretty_node::llms_txt::{LlmsTxt, FULL_FILE, INDEX_FILE};
+
+    let explorer = NodeModuleExplorer::new(package.to_string(), depth, quiet)
+        .with_npm_client(client_options.client());
+    let tree = explorer
+        .explore()
+        .await
+        .map_err(|e| anyhow::anyhow!("Could not explore '{}': {}", package, e))?;
+
+    match format {
+        DocsFormat::LlmsTxt => {
+            LlmsTxt::from_tree(&tree).write_to(out_dir)?;
+            if !quiet {
+                eprintln!(
+                    "Wrote {} and {} to {}",
+                    INDEX_FILE,
+                    FULL_FILE,
+                    out_dir.display()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_cache_command(action: CacheAction) -> anyhow::Result<()> {
+    use pretty_node::cache::{parse_duration, PackageCache};
+
+    let cache = PackageCache::from_env().ok_or_else(|| {
+        anyhow::anyhow!("Package cache is disabled (PRETTY_NODE_NO_CACHE) or has no location")
+    })?;
+
+    match action {
+        CacheAction::Ls { output } => {
+            let entries = cache.entries()?;
+            match output {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+                OutputFormat::Markdown => {
+                    println!("| Package | Version | Size | Last used |");
+                    println!("| --- | --- | --- | --- |");
+                    for entry in &entries {
+                        println!(
+                            "| `{}` | {} | {} | {} |",
+                            entry.name,
+                            entry.version,
+                            format_size(entry.size),
+                            format_age(entry.last_used)
+                        );
+                    }
+                }
+                OutputFormat::Pretty => {
+                    println!("📁 {}", cache.root().display());
+                    if entries.is_empty() {
+                        println!("└── (empty)");
+                    }
+                    for (i, entry) in entries.iter().enumerate() {
+                        let prefix = if i == entries.len() - 1 { "└── " } else { "├── " };
+                        println!(
+                            "{}📦 {}@{} ({}, used {})",
+                            prefix,
+                            entry.name,
+                            entry.version,
+                            format_size(entry.size),
+                            format_age(entry.last_used)
+                        );
+                    }
+                }
+            }
+        }
+        CacheAction::Clean => {
+            cache.clean()?;
+            println!("Removed all cached packages from {}", cache.root().display());
+        }
+        CacheAction::Prune { older_than } => {
+            let removed = cache.prune(parse_duration(&older_than)?)?;
+            for entry in &removed {
+                println!("Removed {}@{}", entry.name, entry.version);
+            }
+            println!("Pruned {} package(s) unused for {}", removed.len(), older_than);
+        }
+    }
+
+    Ok(())
+}
+
+/// Errors that must not be hidden behind the graceful "not found" output
+fn is_fatal(error: &anyhow::Error) -> bool {
+    use pretty_node::integrity::IntegrityError;
+    use pretty_node::npm_client::{OfflineError, RegistryAuthError};
+
+    error.downcast_ref::<OfflineError>().is_some()
+        || error.downcast_ref::<IntegrityError>().is_some()
+        || error.downcast_ref::<RegistryAuthError>().is_some()
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn format_age(timestamp: u64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    match now.saturating_sub(timestamp) {
+        age if age < 60 => "just now".to_string(),
+        age if age < 3600 => format!("{}m ago", age / 60),
+        age if age < 86400 => format!("{}h ago", age / 3600),
+        age => format!("{}d ago", age / 86400),
+    }
+}
@@ -0,0 +1,410 @@
+use crate::api_diff::ApiDiff;
+use crate::module_info::{
+    ClassInfo, DocTags, NodeModuleInfo, SignatureInfo, SignatureKind, SymbolSource, TypeKind,
+};
+use crate::output_format::OutputFormatter;
+use crate::search::{SearchResult, SymbolKind};
+use anyhow::Result;
+
+/// Markdown for pasting into pull requests, wikis and prompts: a heading
+/// per module, tables of functions, types and constants, and classes and
+/// signatures as fenced TypeScript
+pub struct MarkdownFormatter;
+
+impl MarkdownFormatter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn format_module(
+        &self,
+        module: &NodeModuleInfo,
+        path: &str,
+        level: usize,
+        output: &mut String,
+    ) {
+        let title = match &module.version {
+            Some(version) => format!("{}@{}", path, version),
+            None => path.to_string(),
+        };
+        output.push_str(&heading(level, &code(&title)));
+        if let Some(types_package) = &module.types_package {
+            output.push_str(&format!("Types from `{}`.\n\n", types_package));
+        }
+        if !module.exports.is_empty() {
+            let exports: Vec<String> = module.exports.iter().map(|e| code(e)).collect();
+            output.push_str(&format!("**Exports:** {}\n\n", exports.join(", ")));
+        }
+
+        if !module.functions.is_empty() {
+            output.push_str(&heading(level + 1, "Functions"));
+            output.push_str("| Function | Signature | Description |\n");
+            output.push_str("| --- | --- | --- |\n");
+            for function in &module.functions {
+                let signature = function.to_signature(String::new(), SignatureKind::Function);
+                output.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    code(&function.name),
+                    cell(&code(&signature.declaration())),
+                    cell(&description(
+                        function.doc_comment.as_deref(),
+                        function.doc_tags.as_ref()
+                    ))
+                ));
+            }
+            output.push('\n');
+        }
+
+        if !module.classes.is_empty() {
+            output.push_str(&heading(level + 1, "Classes"));
+            for class in &module.classes {
+                output.push_str(&heading(level + 2, &code(&class.name)));
+                let summary = description(class.doc_comment.as_deref(), class.doc_tags.as_ref());
+                if !summary.is_empty() {
+                    output.push_str(&format!("{}\n\n", summary));
+                }
+                output.push_str(&format!("```ts\n{}\n```\n\n", class_outline(class)));
+            }
+        }
+
+        if !module.types.is_empty() {
+            output.push_str(&heading(level + 1, "Types"));
+            output.push_str("| Type | Kind | Definition |\n");
+            output.push_str("| --- | --- | --- |\n");
+            for type_info in &module.types {
+                let kind = match type_info.kind {
+                    TypeKind::Interface => "interface",
+                    TypeKind::Type => "type",
+                    TypeKind::Enum => "enum",
+                };
+                output.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    code(&type_info.name),
+                    kind,
+                    cell(&code(&type_info.definition))
+                ));
+            }
+            output.push('\n');
+        }
+
+        if !module.constants.is_empty() {
+            output.push_str(&heading(level + 1, "Constants"));
+            output.push_str("| Constant | Type | Description |\n");
+            output.push_str("| --- | --- | --- |\n");
+            for constant in &module.constants {
+                output.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    code(&constant.name),
+                    cell(&constant.value_type.as_deref().map(code).unwrap_or_default()),
+                    cell(&description(
+                        constant.doc_comment.as_deref(),
+                        constant.doc_tags.as_ref()
+                    ))
+                ));
+            }
+            output.push('\n');
+        }
+
+        // Submodules are headed by their full path, one level down
+        for (name, submodule) in &module.submodules {
+            let path = format!("{}/{}", path, name);
+            self.format_module(submodule, &path, level + 1, output);
+        }
+    }
+}
+
+impl Default for MarkdownFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputFormatter for MarkdownFormatter {
+    fn format_tree(&self, tree: &NodeModuleInfo) -> Result<String> {
+        let mut output = String::new();
+        self.format_module(tree, &tree.name, 1, &mut output);
+        Ok(output.trim_end().to_string())
+    }
+
+    fn format_signature(&self, signature: &SignatureInfo) -> Result<String> {
+        let keyword = match signature.kind {
+            SignatureKind::Function | SignatureKind::ArrowFunction => "function ",
+            SignatureKind::Constructor => "new ",
+            SignatureKind::Method => "",
+        };
+        let mut output = format!("### {}\n\n", code(&signature.name));
+        output.push_str(&format!(
+            "```ts\n{}{}\n```\n",
+            keyword,
+            signature.declaration()
+        ));
+
+        if let Some(doc) = &signature.doc_comment {
+            output.push_str(&format!("\n{}\n", doc));
+        }
+
+        let note = match &signature.source {
+            SymbolSource::Package => None,
+            SymbolSource::Merged(package) => Some(format!("Types from `{}`.", package)),
+            SymbolSource::TypesPackage(package) => Some(format!("Declared in `{}` only.", package)),
+            SymbolSource::Curated(origin) => Some(format!(
+                "Curated signature from `{}`, not parsed from the package.",
+                origin
+            )),
+        };
+        if let Some(note) = note {
+            output.push_str(&format!("\n> {}\n", note));
+        }
+
+        let tags = signature.doc_tags.clone().unwrap_or_default();
+        if let Some(reason) = &tags.deprecated {
+            if reason.is_empty() {
+                output.push_str("\n**Deprecated**\n");
+            } else {
+                output.push_str(&format!("\n**Deprecated:** {}\n", reason));
+            }
+        }
+
+        if !signature.parameters.is_empty() {
+            output.push_str("\n| Parameter | Type | Description |\n");
+            output.push_str("| --- | --- | --- |\n");
+            for param in &signature.parameters {
+                let mut description = tags
+                    .params
+                    .iter()
+                    .find(|p| p.name == param.name)
+                    .and_then(|p| p.description.clone())
+                    .unwrap_or_default();
+                if let Some(default_value) = &param.default_value {
+                    if !description.is_empty() {
+                        description.push(' ');
+                    }
+                    description.push_str(&format!("(default: {})", code(default_value)));
+                }
+                output.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    cell(&code(&param.declaration())),
+                    cell(&param.param_type.as_deref().map(code).unwrap_or_default()),
+                    cell(&description)
+                ));
+            }
+        }
+
+        if let Some(return_type) = &signature.return_type {
+            let description = tags
+                .returns
+                .as_ref()
+                .and_then(|returns| returns.description.as_deref())
+                .map(|description| format!(": {}", description))
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "\n**Returns** {}{}\n",
+                code(return_type),
+                description
+            ));
+        }
+
+        for example in &tags.examples {
+            output.push_str(&format!(
+                "\n**Example**\n\n```ts\n{}\n```\n",
+                example.trim()
+            ));
+        }
+
+        Ok(output.trim_end().to_string())
+    }
+
+    fn format_signature_not_available(&self, object_name: &str) -> String {
+        format!("### {}\n\nSignature not available.", code(object_name))
+    }
+
+    fn format_search_results(&self, pattern: &str, results: &[SearchResult]) -> Result<String> {
+        if results.is_empty() {
+            return Ok(format!("No symbols matching {}.", code(pattern)));
+        }
+        let mut output = String::from("| Symbol | Kind | File |\n| --- | --- | --- |\n");
+        for result in results {
+            let kind = match result.kind {
+                SymbolKind::Function => "function",
+                SymbolKind::Method => "method",
+                SymbolKind::Class => "class",
+                SymbolKind::Type => "type",
+                SymbolKind::Constant => "constant",
+            };
+            output.push_str(&format!(
+                "| {} | {} | {} |\n",
+                code(&format!("{}:{}", result.module_path, result.name)),
+                kind,
+                code(&result.file)
+            ));
+        }
+        Ok(output.trim_end().to_string())
+    }
+
+    fn format_api_diff(&self, diff: &ApiDiff) -> Result<String> {
+        Ok(diff.to_markdown().trim_end().to_string())
+    }
+}
+
+/// The class as a TypeScript outline: heritage, constructor, properties
+/// and method signatures
+pub(crate) fn class_outline(class: &ClassInfo) -> String {
+    let mut header = format!("class {}", class.name);
+    if !class.type_params.is_empty() {
+        header.push_str(&format!("<{}>", class.type_params.join(", ")));
+    }
+    if let Some(extends) = &class.extends {
+        header.push_str(&format!(" extends {}", extends));
+    }
+    if !class.implements.is_empty() {
+        header.push_str(&format!(" implements {}", class.implements.join(", ")));
+    }
+
+    let mut members = Vec::new();
+    if let Some(constructor) = &class.constructor {
+        let mut signature =
+            constructor.to_signature("constructor".to_string(), SignatureKind::Constructor);
+        signature.return_type = None;
+        members.push(signature.declaration());
+    }
+    for property in &class.properties {
+        let mut member = String::new();
+        if property.is_static {
+            member.push_str("static ");
+        }
+        if property.is_readonly {
+            member.push_str("readonly ");
+        }
+        member.push_str(&property.name);
+        if let Some(property_type) = &property.property_type {
+            member.push_str(&format!(": {}", property_type));
+        }
+        members.push(member);
+    }
+    for method in &class.methods {
+        members.push(
+            method
+                .to_signature(method.name.clone(), SignatureKind::Method)
+                .declaration(),
+        );
+    }
+
+    if members.is_empty() {
+        return format!("{} {{}}", header);
+    }
+    let body: Vec<String> = members
+        .iter()
+        .map(|member| format!("  {};", member))
+        .collect();
+    format!("{} {{\n{}\n}}", header, body.join("\n"))
+}
+
+/// The summary line of a doc comment, flagged when deprecated
+fn description(doc_comment: Option<&str>, doc_tags: Option<&DocTags>) -> String {
+    let summary = doc_comment
+        .and_then(|doc| doc.lines().next())
+        .unwrap_or_default()
+        .to_string();
+    match doc_tags.and_then(|tags| tags.deprecated.as_ref()) {
+        Some(_) if summary.is_empty() => "**Deprecated**".to_string(),
+        Some(_) => format!("**Deprecated** {}", summary),
+        None => summary,
+    }
+}
+
+/// Inline code; a backtick inside needs a longer fence
+fn code(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+/// Text for a table cell, where pipes and line breaks would break the row
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// A heading block; Markdown has no headings past h6, so deeper ones
+/// become bold text
+fn heading(level: usize, text: &str) -> String {
+    if level <= 6 {
+        format!("{} {}\n\n", "#".repeat(level), text)
+    } else {
+        format!("**{}**\n\n", text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::typescript::TypeScriptParser;
+
+    fn parse(source: &str) -> NodeModuleInfo {
+        TypeScriptParser::new()
+            .parse_declaration_content(source, "pkg")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tree_markdown() {
+        let mut module = parse(
+            "/** Create a server */\nexport declare function create(port?: number): Server;\n\
+             export declare class Server<T> extends Emitter {\n  constructor(port: number);\n  readonly port: number;\n  listen(cb?: () => void): this;\n}\n\
+             export type Mode = 'a' | 'b';\nexport declare const VERSION: string;\n",
+        );
+        module.version = Some("1.0.0".to_string());
+        module.add_submodule(
+            "lib".to_string(),
+            parse("export declare function helper(): void;"),
+        );
+
+        let markdown = MarkdownFormatter::new().format_tree(&module).unwrap();
+        assert!(markdown.starts_with("# `pkg@1.0.0`\n"));
+        assert!(markdown.contains("| `create` | `(port?: number): Server` | Create a server |"));
+        assert!(markdown.contains(
+            "```ts\nclass Server<T> extends Emitter {\n  constructor(port: number);\n  readonly port: number;\n  listen(cb?: () => void): this;\n}\n```"
+        ));
+        assert!(markdown.contains(r"| `Mode` | type | `type Mode = 'a' \| 'b'` |"));
+        assert!(markdown.contains("| `VERSION` | `string` |"));
+        assert!(markdown.contains("## `pkg/lib`\n"));
+    }
+
+    #[test]
+    fn test_deep_tree_headings() {
+        let mut module = parse("export declare class Leaf {}");
+        for depth in (1..=7).rev() {
+            let mut parent = parse("export declare function f(): void;");
+            parent.add_submodule(format!("d{}", depth), module);
+            module = parent;
+        }
+
+        let markdown = MarkdownFormatter::new().format_tree(&module).unwrap();
+        assert!(!markdown.contains("#######"));
+        assert!(markdown.contains("###### `pkg/d1/d2/d3/d4/d5`\n"));
+        assert!(markdown.contains("**Functions**\n"));
+        assert!(markdown.contains("**`pkg/d1/d2/d3/d4/d5/d6/d7`**\n"));
+        assert!(markdown.contains("**`Leaf`**\n"));
+    }
+
+    #[test]
+    fn test_signature_markdown() {
+        let module = parse(
+            "/**\n * Pad a string\n * @param str The input\n * @returns The padded string\n * @deprecated Use padStart\n */\n\
+             export declare function pad<T>(str: string, ...chars: T[]): string;\n",
+        );
+        let signature =
+            module.functions[0].to_signature("pad".to_string(), SignatureKind::Function);
+        let markdown = MarkdownFormatter::new()
+            .format_signature(&signature)
+            .unwrap();
+        assert!(markdown.starts_with(
+            "### `pad`\n\n```ts\nfunction pad<T>(str: string, ...chars: T[]): string\n```"
+        ));
+        assert!(markdown.contains("\nPad a string\n"));
+        assert!(markdown.contains("**Deprecated:** Use padStart"));
+        assert!(markdown.contains("| `str: string` | `string` | The input |"));
+        assert!(markdown.contains("**Returns** `string`: The padded string"));
+    }
+}
@@ -1,10 +1,17 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeModuleInfo {
     pub name: String,
     pub version: Option<String>,
+    /// The version or range asked for, when it differs from `version`
+    /// (`^4`, `next`)
+    pub requested_version: Option<String>,
+    /// The DefinitelyTyped package (`@types/express@4.17.21`) whose
+    /// declarations were merged in, for packages without their own
+    pub types_package: Option<String>,
     pub main: Option<String>,
     pub exports: Vec<String>,
     pub imports: Vec<String>,
@@ -12,7 +19,29 @@ pub struct NodeModuleInfo {
     pub classes: Vec<ClassInfo>,
     pub types: Vec<TypeInfo>,
     pub constants: Vec<ConstantInfo>,
-    pub submodules: HashMap<String, NodeModuleInfo>,
+    /// Specifiers whose exports are re-exported wholesale
+    /// (`export * from "./x"`, `__exportStar(require("./x"), exports)`)
+    #[serde(skip)]
+    pub star_exports: Vec<String>,
+    /// Named re-exports (`export { a as b } from "./x"`, `export * as ns from "./x"`)
+    #[serde(skip)]
+    pub reexports: Vec<ReExportInfo>,
+    /// The symbol assigned to `module.exports` or `export =`, which importers
+    /// receive as the module itself
+    #[serde(skip)]
+    pub export_assignment: Option<String>,
+    /// Ordered by name, so output is the same on every run
+    pub submodules: BTreeMap<String, NodeModuleInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReExportInfo {
+    /// Module specifier as written (`"./lib/router"`)
+    pub source: String,
+    /// Name in the source module; `*` for a namespace re-export
+    pub imported: String,
+    /// Name this module exports it under
+    pub exported: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,9 +49,15 @@ pub struct FunctionInfo {
     pub name: String,
     pub parameters: Vec<Parameter>,
     pub return_type: Option<String>,
+    pub type_params: Vec<String>,
     pub is_async: bool,
     pub is_generator: bool,
     pub doc_comment: Option<String>,
+    pub doc_tags: Option<DocTags>,
+    /// Package-relative file the symbol is defined in, when it was
+    /// reached through a re-export
+    pub defined_in: Option<String>,
+    pub source: SymbolSource,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,7 +68,13 @@ pub struct ClassInfo {
     pub properties: Vec<PropertyInfo>,
     pub extends: Option<String>,
     pub implements: Vec<String>,
+    pub type_params: Vec<String>,
     pub doc_comment: Option<String>,
+    pub doc_tags: Option<DocTags>,
+    /// Package-relative file the symbol is defined in, when it was
+    /// reached through a re-export
+    pub defined_in: Option<String>,
+    pub source: SymbolSource,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +83,28 @@ pub struct TypeInfo {
     pub kind: TypeKind,
     pub definition: String,
     pub doc_comment: Option<String>,
+    pub doc_tags: Option<DocTags>,
+    /// Package-relative file the symbol is defined in, when it was
+    /// reached through a re-export
+    pub defined_in: Option<String>,
+    pub source: SymbolSource,
+}
+
+/// Where a symbol's information was read from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolSource {
+    /// The package's own code or declarations
+    #[default]
+    Package,
+    /// Only declared in a DefinitelyTyped package (`@types/express`)
+    TypesPackage(String),
+    /// Found in the package's JavaScript, with its types (parameters,
+    /// return and property types) taken from a DefinitelyTyped package
+    Merged(String),
+    /// Not parsed at all: a hand-written signature from the named
+    /// override file
+    Curated(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +119,11 @@ pub struct ConstantInfo {
     pub name: String,
     pub value_type: Option<String>,
     pub doc_comment: Option<String>,
+    pub doc_tags: Option<DocTags>,
+    /// Package-relative file the symbol is defined in, when it was
+    /// reached through a re-export
+    pub defined_in: Option<String>,
+    pub source: SymbolSource,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,6 +133,7 @@ pub struct PropertyInfo {
     pub is_readonly: bool,
     pub is_static: bool,
     pub doc_comment: Option<String>,
+    pub doc_tags: Option<DocTags>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,13 +145,42 @@ pub struct Parameter {
     pub default_value: Option<String>,
 }
 
+/// Structured JSDoc/TSDoc tags attached to a declaration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocTags {
+    pub params: Vec<DocParam>,
+    pub returns: Option<DocReturn>,
+    pub deprecated: Option<String>,
+    pub examples: Vec<String>,
+}
+
+/// A single `@param` tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocParam {
+    pub name: String,
+    pub param_type: Option<String>,
+    pub description: Option<String>,
+    pub is_optional: bool,
+    pub default_value: Option<String>,
+}
+
+/// The `@returns` tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocReturn {
+    pub return_type: Option<String>,
+    pub description: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignatureInfo {
     pub name: String,
     pub kind: SignatureKind,
     pub parameters: Vec<Parameter>,
     pub return_type: Option<String>,
+    pub type_params: Vec<String>,
     pub doc_comment: Option<String>,
+    pub doc_tags: Option<DocTags>,
+    pub source: SymbolSource,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,6 +196,8 @@ impl NodeModuleInfo {
         Self {
             name,
             version: None,
+            requested_version: None,
+            types_package: None,
             main: None,
             exports: Vec::new(),
             imports: Vec::new(),
@@ -105,7 +205,10 @@ impl NodeModuleInfo {
             classes: Vec::new(),
             types: Vec::new(),
             constants: Vec::new(),
-            submodules: HashMap::new(),
+            star_exports: Vec::new(),
+            reexports: Vec::new(),
+            export_assignment: None,
+            submodules: BTreeMap::new(),
         }
     }
 
@@ -128,4 +231,498 @@ impl NodeModuleInfo {
     pub fn add_constant(&mut self, constant: ConstantInfo) {
         self.constants.push(constant);
     }
+
+    /// Record the file every top-level symbol without one is defined in
+    pub fn set_defined_in(&mut self, file: &str) {
+        let file = Some(file.to_string());
+        for function in self.functions.iter_mut().filter(|f| f.defined_in.is_none()) {
+            function.defined_in = file.clone();
+        }
+        for class in self.classes.iter_mut().filter(|c| c.defined_in.is_none()) {
+            class.defined_in = file.clone();
+        }
+        for type_info in self.types.iter_mut().filter(|t| t.defined_in.is_none()) {
+            type_info.defined_in = file.clone();
+        }
+        for constant in self.constants.iter_mut().filter(|c| c.defined_in.is_none()) {
+            constant.defined_in = file.clone();
+        }
+    }
+
+    /// Whether a function, class, type or constant with this name is defined here
+    pub fn defines(&self, name: &str) -> bool {
+        self.functions.iter().any(|f| f.name == name)
+            || self.classes.iter().any(|c| c.name == name)
+            || self.types.iter().any(|t| t.name == name)
+            || self.constants.iter().any(|c| c.name == name)
+    }
+
+    /// Whether the module exports this name; without an export list (plain
+    /// scripts, bare declarations) everything counts as exported
+    pub fn is_exported(&self, name: &str) -> bool {
+        self.exports.is_empty()
+            || self.exports.iter().any(|export| export == name)
+            || self.export_assignment.as_deref() == Some(name)
+    }
+
+    /// Merge `export * from` a resolved module: every export except `default`
+    /// that this module does not already provide itself
+    pub fn merge_star_export(&mut self, source: &NodeModuleInfo) {
+        for name in &source.exports {
+            if name == "default" || self.exports.contains(name) {
+                continue;
+            }
+            self.exports.push(name.clone());
+            self.copy_symbol(source, name, name);
+        }
+    }
+
+    /// Merge a named re-export from a resolved module
+    pub fn merge_reexport(&mut self, reexport: &ReExportInfo, source: &NodeModuleInfo) {
+        if !self.exports.contains(&reexport.exported) {
+            self.exports.push(reexport.exported.clone());
+        }
+        if self.defines(&reexport.exported) {
+            return;
+        }
+
+        match reexport.imported.as_str() {
+            // A namespace, or the default import of CommonJS, is the module
+            // itself unless it assigned a single value to `module.exports`
+            "*" | "default" => match &source.export_assignment {
+                Some(assigned) => self.copy_symbol(source, assigned, &reexport.exported),
+                None if reexport.imported == "*" => {
+                    let mut namespace = source.clone();
+                    namespace.name = reexport.exported.clone();
+                    self.add_submodule(reexport.exported.clone(), namespace);
+                }
+                None => {}
+            },
+            imported => self.copy_symbol(source, imported, &reexport.exported),
+        }
+    }
+
+    /// Merge the declarations of a DefinitelyTyped package (`package`, e.g.
+    /// `@types/express`) into this module, which was read from JavaScript
+    ///
+    /// Symbols the JavaScript defines keep their docs but take their types
+    /// from the declarations and become `Merged`; those only the
+    /// declarations know about are added as `TypesPackage`. The symbols
+    /// assigned to `module.exports` and `export =` are the same symbol
+    /// whatever their names. Submodules with the same key are merged too.
+    pub fn merge_types_package(&mut self, declarations: &NodeModuleInfo, package: &str) {
+        self.merge_declarations(
+            declarations,
+            &SymbolSource::Merged(package.to_string()),
+            &SymbolSource::TypesPackage(package.to_string()),
+        );
+    }
+
+    /// Merge the package's own declarations (its `types` entry) into this
+    /// module, which was read from its JavaScript. Everything stays `Package`.
+    pub fn merge_own_declarations(&mut self, declarations: &NodeModuleInfo) {
+        self.merge_declarations(declarations, &SymbolSource::Package, &SymbolSource::Package);
+    }
+
+    /// Merge declarations, marking symbols the JavaScript also defines as
+    /// `merged` and those only declared as `declared_only`
+    fn merge_declarations(
+        &mut self,
+        declarations: &NodeModuleInfo,
+        merged: &SymbolSource,
+        declared_only: &SymbolSource,
+    ) {
+        let assigned = self.export_assignment.clone();
+        let local_name = |name: &str| match (&declarations.export_assignment, &assigned) {
+            (Some(declared), Some(assigned)) if declared == name => assigned.clone(),
+            _ => name.to_string(),
+        };
+
+        // Overloads after the first are added with the source the first got
+        let mut merged_functions: Vec<(String, SymbolSource)> = Vec::new();
+        for declared in &declarations.functions {
+            let name = local_name(&declared.name);
+            if let Some((_, source)) = merged_functions.iter().find(|(n, _)| *n == name) {
+                self.functions.push(FunctionInfo {
+                    name,
+                    source: source.clone(),
+                    ..declared.clone()
+                });
+                continue;
+            }
+            if let Some(function) = self.functions.iter_mut().find(|f| f.name == name) {
+                function.merge_declaration(declared, merged);
+            } else if let Some(constant) = self.take_constant(&name) {
+                // `exports.fn = require(...)` reads as a constant but is declared a function
+                let mut function = FunctionInfo {
+                    name: name.clone(),
+                    doc_comment: constant.doc_comment,
+                    doc_tags: constant.doc_tags,
+                    defined_in: constant.defined_in,
+                    ..declared.clone()
+                };
+                function.merge_declaration(declared, merged);
+                self.functions.push(function);
+            } else if !self.defines(&name) {
+                self.functions.push(FunctionInfo {
+                    name: name.clone(),
+                    source: declared_only.clone(),
+                    ..declared.clone()
+                });
+            } else {
+                continue;
+            }
+            let source = self
+                .functions
+                .iter()
+                .find(|f| f.name == name)
+                .map(|f| f.source.clone())
+                .unwrap_or_default();
+            merged_functions.push((name, source));
+        }
+
+        for declared in &declarations.classes {
+            let name = local_name(&declared.name);
+            if let Some(class) = self.classes.iter_mut().find(|c| c.name == name) {
+                class.merge_declaration(declared, merged);
+            } else if let Some(constant) = self.take_constant(&name) {
+                let mut class = ClassInfo {
+                    name,
+                    doc_comment: constant.doc_comment,
+                    doc_tags: constant.doc_tags,
+                    defined_in: constant.defined_in,
+                    ..declared.clone()
+                };
+                class.merge_declaration(declared, merged);
+                self.classes.push(class);
+            } else if !self.defines(&name) {
+                self.classes.push(ClassInfo {
+                    name,
+                    source: declared_only.clone(),
+                    ..declared.clone()
+                });
+            }
+        }
+
+        for declared in &declarations.constants {
+            let name = local_name(&declared.name);
+            if let Some(constant) = self.constants.iter_mut().find(|c| c.name == name) {
+                constant.value_type = declared.value_type.clone().or(constant.value_type.take());
+                constant.doc_comment = constant
+                    .doc_comment
+                    .take()
+                    .or_else(|| declared.doc_comment.clone());
+                constant.source = merged.clone();
+            } else if !self.defines(&name) {
+                self.constants.push(ConstantInfo {
+                    name,
+                    source: declared_only.clone(),
+                    ..declared.clone()
+                });
+            }
+        }
+
+        for declared in &declarations.types {
+            if !self.defines(&declared.name) {
+                self.types.push(TypeInfo {
+                    source: declared_only.clone(),
+                    ..declared.clone()
+                });
+            }
+        }
+
+        // Values the parser could not see in the JavaScript (computed
+        // exports) are still importable; types are not runtime exports
+        for name in &declarations.exports {
+            let name = local_name(name);
+            let is_value = self.functions.iter().any(|f| f.name == name)
+                || self.classes.iter().any(|c| c.name == name)
+                || self.constants.iter().any(|c| c.name == name);
+            if is_value && !self.exports.contains(&name) {
+                self.exports.push(name);
+            }
+        }
+
+        for (key, declared) in &declarations.submodules {
+            if let Some(submodule) = self.submodules.get_mut(key) {
+                submodule.merge_declarations(declared, merged, declared_only);
+            }
+        }
+    }
+
+    /// Remove and return the constant named `name`, if there is one
+    fn take_constant(&mut self, name: &str) -> Option<ConstantInfo> {
+        let index = self.constants.iter().position(|c| c.name == name)?;
+        Some(self.constants.remove(index))
+    }
+
+    /// Copy the definitions of `name` from `source`, renamed to `as_name`
+    fn copy_symbol(&mut self, source: &NodeModuleInfo, name: &str, as_name: &str) {
+        for function in source.functions.iter().filter(|f| f.name == name) {
+            self.functions.push(FunctionInfo {
+                name: as_name.to_string(),
+                ..function.clone()
+            });
+        }
+        for class in source.classes.iter().filter(|c| c.name == name) {
+            self.classes.push(ClassInfo {
+                name: as_name.to_string(),
+                ..class.clone()
+            });
+        }
+        for type_info in source.types.iter().filter(|t| t.name == name) {
+            self.types.push(TypeInfo {
+                name: as_name.to_string(),
+                ..type_info.clone()
+            });
+        }
+        for constant in source.constants.iter().filter(|c| c.name == name) {
+            self.constants.push(ConstantInfo {
+                name: as_name.to_string(),
+                ..constant.clone()
+            });
+        }
+        if let Some(namespace) = source.submodules.get(name) {
+            self.submodules
+                .entry(as_name.to_string())
+                .or_insert_with(|| namespace.clone());
+        }
+    }
+
+    /// Order the exports and symbols of this module and its submodules.
+    /// Overloads keep their declared order.
+    pub fn sort(&mut self, order: SortOrder) {
+        match order {
+            SortOrder::SourceOrder => {}
+            SortOrder::Name => {
+                self.exports.sort_by(|a, b| cmp_names(a, b));
+                self.functions.sort_by(|a, b| cmp_names(&a.name, &b.name));
+                self.classes.sort_by(|a, b| cmp_names(&a.name, &b.name));
+                self.types.sort_by(|a, b| cmp_names(&a.name, &b.name));
+                self.constants.sort_by(|a, b| cmp_names(&a.name, &b.name));
+            }
+            SortOrder::Kind => {
+                let function_kind = |f: &FunctionInfo| (f.is_async, f.is_generator);
+                let type_kind = |t: &TypeInfo| match t.kind {
+                    TypeKind::Interface => 0,
+                    TypeKind::Type => 1,
+                    TypeKind::Enum => 2,
+                };
+                // Untyped constants last
+                let constant_kind =
+                    |c: &ConstantInfo| (c.value_type.is_none(), c.value_type.clone());
+                self.exports.sort_by(|a, b| cmp_names(a, b));
+                self.functions.sort_by(|a, b| {
+                    function_kind(a)
+                        .cmp(&function_kind(b))
+                        .then_with(|| cmp_names(&a.name, &b.name))
+                });
+                self.classes.sort_by(|a, b| cmp_names(&a.name, &b.name));
+                self.types.sort_by(|a, b| {
+                    type_kind(a)
+                        .cmp(&type_kind(b))
+                        .then_with(|| cmp_names(&a.name, &b.name))
+                });
+                self.constants.sort_by(|a, b| {
+                    constant_kind(a)
+                        .cmp(&constant_kind(b))
+                        .then_with(|| cmp_names(&a.name, &b.name))
+                });
+            }
+        }
+        for submodule in self.submodules.values_mut() {
+            submodule.sort(order);
+        }
+    }
+}
+
+/// How the symbols within a module are ordered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Alphabetically, ignoring case
+    Name,
+    /// Grouped by kind (plain, async and generator functions; interfaces,
+    /// type aliases and enums; constants by type), then by name
+    Kind,
+    /// As declared in the source
+    #[default]
+    SourceOrder,
+}
+
+/// Alphabetical, ignoring case; names that differ only in case keep a
+/// fixed order
+fn cmp_names(a: &str, b: &str) -> Ordering {
+    a.to_lowercase()
+        .cmp(&b.to_lowercase())
+        .then_with(|| a.cmp(b))
+}
+
+impl DocTags {
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+            && self.returns.is_none()
+            && self.deprecated.is_none()
+            && self.examples.is_empty()
+    }
+}
+
+impl FunctionInfo {
+    /// Build a signature for this function under the given display name
+    pub fn to_signature(&self, name: String, kind: SignatureKind) -> SignatureInfo {
+        SignatureInfo {
+            name,
+            kind,
+            parameters: self.parameters.clone(),
+            return_type: self.return_type.clone(),
+            type_params: self.type_params.clone(),
+            doc_comment: self.doc_comment.clone(),
+            doc_tags: self.doc_tags.clone(),
+            source: self.source.clone(),
+        }
+    }
+
+    /// Whether any parameter or the return value carries a type
+    pub fn has_types(&self) -> bool {
+        self.return_type.is_some() || self.parameters.iter().any(|p| p.param_type.is_some())
+    }
+
+    /// Take the signature of a declaration of the same function, keeping
+    /// this one's docs where it has them
+    fn merge_declaration(&mut self, declared: &FunctionInfo, source: &SymbolSource) {
+        self.parameters = declared.parameters.clone();
+        self.return_type = declared.return_type.clone();
+        self.type_params = declared.type_params.clone();
+        self.doc_comment = self
+            .doc_comment
+            .take()
+            .or_else(|| declared.doc_comment.clone());
+        self.doc_tags = self.doc_tags.take().or_else(|| declared.doc_tags.clone());
+        self.source = source.clone();
+    }
+}
+
+impl ClassInfo {
+    /// Build the constructor signature for this class
+    pub fn constructor_signature(&self) -> SignatureInfo {
+        SignatureInfo {
+            name: self.name.clone(),
+            kind: SignatureKind::Constructor,
+            parameters: self
+                .constructor
+                .as_ref()
+                .map(|c| c.parameters.clone())
+                .unwrap_or_default(),
+            return_type: Some(self.name.clone()),
+            type_params: self.type_params.clone(),
+            doc_comment: self.doc_comment.clone(),
+            doc_tags: self.doc_tags.clone(),
+            source: self.source.clone(),
+        }
+    }
+
+    /// Take constructor, method and property types from a declaration of
+    /// the same class; members only the declaration has are added
+    fn merge_declaration(&mut self, declared: &ClassInfo, source: &SymbolSource) {
+        match (&mut self.constructor, &declared.constructor) {
+            (Some(constructor), Some(declared)) => constructor.merge_declaration(declared, source),
+            (None, Some(declared)) => self.constructor = Some(declared.clone()),
+            _ => {}
+        }
+        for method in &declared.methods {
+            match self.methods.iter_mut().find(|m| m.name == method.name) {
+                Some(existing) => existing.merge_declaration(method, source),
+                None => self.methods.push(method.clone()),
+            }
+        }
+        for property in &declared.properties {
+            match self.properties.iter_mut().find(|p| p.name == property.name) {
+                Some(existing) => {
+                    existing.property_type = property.property_type.clone();
+                    existing.is_readonly = property.is_readonly;
+                }
+                None => self.properties.push(property.clone()),
+            }
+        }
+        if self.extends.is_none() {
+            self.extends = declared.extends.clone();
+        }
+        if self.implements.is_empty() {
+            self.implements = declared.implements.clone();
+        }
+        if self.type_params.is_empty() {
+            self.type_params = declared.type_params.clone();
+        }
+        self.doc_comment = self
+            .doc_comment
+            .take()
+            .or_else(|| declared.doc_comment.clone());
+        self.doc_tags = self.doc_tags.take().or_else(|| declared.doc_tags.clone());
+        self.source = source.clone();
+    }
+}
+
+impl SignatureInfo {
+    /// Whether any parameter, or the return value of a non-constructor,
+    /// carries a type
+    pub fn has_types(&self) -> bool {
+        self.parameters.iter().any(|p| p.param_type.is_some())
+            || (!matches!(self.kind, SignatureKind::Constructor) && self.return_type.is_some())
+    }
+
+    /// Take parameter and return types from the declaration of the same
+    /// symbol in a DefinitelyTyped package, keeping this one's docs where
+    /// it has them
+    pub fn merge_types(&mut self, declared: SignatureInfo, package: &str) {
+        self.merge_declaration(declared, SymbolSource::Merged(package.to_string()));
+    }
+
+    /// Take parameter and return types from the package's own declaration
+    /// of the same symbol; the signature stays the package's
+    pub fn merge_own_declaration(&mut self, declared: SignatureInfo) {
+        self.merge_declaration(declared, SymbolSource::Package);
+    }
+
+    fn merge_declaration(&mut self, declared: SignatureInfo, source: SymbolSource) {
+        self.parameters = declared.parameters;
+        self.return_type = declared.return_type;
+        self.type_params = declared.type_params;
+        self.doc_comment = self.doc_comment.take().or(declared.doc_comment);
+        self.doc_tags = self.doc_tags.take().or(declared.doc_tags);
+        self.source = source;
+    }
+
+    /// The signature as TypeScript, without a leading keyword:
+    /// `name<T>(a: string, b?: number): R`. Parameters with a default are
+    /// optional; an unknown return type is left out.
+    pub fn declaration(&self) -> String {
+        let type_params = if self.type_params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", self.type_params.join(", "))
+        };
+        let parameters: Vec<String> = self.parameters.iter().map(Parameter::declaration).collect();
+        let mut declaration = format!("{}{}({})", self.name, type_params, parameters.join(", "));
+        if let Some(return_type) = &self.return_type {
+            declaration.push_str(&format!(": {}", return_type));
+        }
+        declaration
+    }
+}
+
+impl Parameter {
+    /// The parameter as TypeScript: `...rest: T[]`, `options?: Options`
+    pub fn declaration(&self) -> String {
+        let mut declaration = String::new();
+        if self.is_rest {
+            declaration.push_str("...");
+        }
+        declaration.push_str(&self.name);
+        if (self.is_optional || self.default_value.is_some()) && !self.is_rest {
+            declaration.push('?');
+        }
+        if let Some(param_type) = &self.param_type {
+            declaration.push_str(&format!(": {}", param_type));
+        }
+        declaration
+    }
 }
@@ -1,20 +1,101 @@
-use anyhow::{anyhow, Result};
-use reqwest::Client;
+use crate::cache::{CacheEntry, PackageCache};
+use crate::extract::{self, ExtractLimits, ExtractReport};
+use crate::integrity;
+use crate::npmrc::{self, NpmrcConfig};
+use crate::package_json::PackageJson;
+use crate::package_layout::{self, InstalledPackage, PnpManifest};
+use crate::utils::PackageSource;
+use crate::semver_range::{parse_version, VersionRange};
+use anyhow::{anyhow, Context, Result};
+use reqwest::header::{ACCEPT, AUTHORIZATION};
+use reqwest::{Client, RequestBuilder, StatusCode};
 use serde_json::Value;
+use std::env;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
+/// npm's "corgi" registry documents: per-release metadata without readmes
+const ABBREVIATED_METADATA: &str =
+    "application/vnd.npm.install-v1+json; q=1.0, application/json; q=0.8, */*";
+
 #[derive(Debug)]
 pub struct NpmPackageInfo {
     pub name: String,
     pub version: String,
     pub tarball_url: String,
+    /// `dist.integrity` (`sha512-<base64>`), if the registry published one
+    pub integrity: Option<String>,
+    /// `dist.shasum`, the legacy sha1 hex digest
+    pub shasum: Option<String>,
+}
+
+/// An unpacked package tarball, either in the cache or in a temporary
+/// directory that is removed on drop
+pub struct DownloadedPackage {
+    root: PathBuf,
+    _temp_dir: Option<TempDir>,
+}
+
+impl DownloadedPackage {
+    /// The package root (the tarball's `package/` directory)
+    pub fn path(&self) -> PathBuf {
+        self.root.join("package")
+    }
+}
+
+impl From<DownloadedPackage> for InstalledPackage {
+    fn from(package: DownloadedPackage) -> Self {
+        InstalledPackage::unpacked(package.path(), package._temp_dir)
+    }
+}
+
+/// A package had to be fetched from the registry while running offline
+#[derive(Debug)]
+pub struct OfflineError {
+    pub package: String,
+}
+
+impl fmt::Display for OfflineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is neither installed in node_modules nor cached, and network access is disabled (--offline / PRETTY_NODE_OFFLINE)",
+            self.package
+        )
+    }
+}
+
+impl std::error::Error for OfflineError {}
+
+/// The registry rejected our credentials (or their absence)
+#[derive(Debug)]
+pub struct RegistryAuthError {
+    pub registry: String,
+    pub package: String,
+    pub status: StatusCode,
 }
 
+impl fmt::Display for RegistryAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Registry {} refused '{}' ({}); check the auth token for it in .npmrc",
+            self.registry, self.package, self.status
+        )
+    }
+}
+
+impl std::error::Error for RegistryAuthError {}
+
 pub struct NpmClient {
     client: Client,
-    registry_url: String,
+    /// Where local module resolution and project `.npmrc` lookup start
+    project_dir: PathBuf,
+    npmrc: NpmrcConfig,
+    cache: Option<PackageCache>,
+    offline: bool,
 }
 
 impl Default for NpmClient {
@@ -25,78 +106,234 @@ impl Default for NpmClient {
 
 impl NpmClient {
     pub fn new() -> Self {
+        let project_dir = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
         Self {
             client: Client::new(),
-            registry_url: "https://registry.npmjs.org".to_string(),
+            npmrc: NpmrcConfig::load(&project_dir),
+            project_dir,
+            cache: PackageCache::from_env(),
+            offline: env::var("PRETTY_NODE_OFFLINE").is_ok(),
         }
     }
 
+    /// Resolve local packages and the project `.npmrc` from `dir` instead of
+    /// the working directory; replaces any registry settings made so far
+    pub fn with_project_dir(mut self, dir: PathBuf) -> Self {
+        self.npmrc = NpmrcConfig::load(&dir);
+        self.project_dir = dir;
+        self
+    }
+
+    pub fn project_dir(&self) -> &Path {
+        &self.project_dir
+    }
+
+    /// Use these registry settings instead of the `.npmrc` files around the
+    /// project directory
+    pub fn with_npmrc(mut self, npmrc: NpmrcConfig) -> Self {
+        self.npmrc = npmrc;
+        self
+    }
+
+    /// Override the default registry, as `--registry` does for npm; scoped
+    /// registries still apply
+    pub fn with_registry(mut self, registry: &str) -> Self {
+        self.npmrc.set("registry", registry);
+        self
+    }
+
+    pub fn npmrc(&self) -> &NpmrcConfig {
+        &self.npmrc
+    }
+
+    /// Never touch the network; only cached packages can be downloaded
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Use this cache instead of the one configured by the environment;
+    /// `None` always goes to the registry
+    pub fn with_cache(mut self, cache: Option<PackageCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn cache(&self) -> Option<&PackageCache> {
+        self.cache.as_ref()
+    }
+
     /// Fetch package metadata from npm registry
     pub async fn get_package_info(
         &self,
         package_name: &str,
         version: Option<&str>,
     ) -> Result<NpmPackageInfo> {
-        let url = if let Some(v) = version {
-            format!("{}/{}@{}", self.registry_url, package_name, v)
-        } else {
-            format!("{}/{}", self.registry_url, package_name)
+        let registry = self.npmrc.registry_for(package_name);
+        // Scoped names are requested as `@scope%2fname`
+        let escaped_name = package_name.replace('/', "%2f");
+        // The whole document is fetched so ranges and dist-tags resolve locally
+        let url = format!("{}{}", registry, escaped_name);
+
+        let spec = match version {
+            Some(v) => format!("{}@{}", package_name, v),
+            None => package_name.to_string(),
         };
+        // Documents from other registries must not shadow the public ones
+        let cache_key = match self.cache_registry(package_name) {
+            Some(registry) => format!("{}{}", registry, package_name),
+            None => package_name.to_string(),
+        };
+        let data = match self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get_metadata(&cache_key))
+        {
+            Some(data) => data,
+            None if self.offline => {
+                return self.cached_package_info(package_name, version, &spec, &cache_key)
+            }
+            None => {
+                let response = self
+                    .get(&url, &registry)
+                    // The abbreviated document has everything needed to pick a release
+                    .header(ACCEPT, ABBREVIATED_METADATA)
+                    .send()
+                    .await?;
 
-        let response = self.client.get(&url).send().await?;
+                match response.status() {
+                    status if status.is_success() => {}
+                    status @ (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                        return Err(RegistryAuthError {
+                            registry,
+                            package: package_name.to_string(),
+                            status,
+                        }
+                        .into())
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "Package '{}' not found in npm registry",
+                            package_name
+                        ))
+                    }
+                }
 
-        if !response.status().is_success() {
-            return Err(anyhow!(
-                "Package '{}' not found in npm registry",
-                package_name
-            ));
-        }
-
-        let data: Value = response.json().await?;
-
-        // Handle both package@version and latest package responses
-        let pkg_data = if let Some(versions) = data.get("versions") {
-            // Response contains all versions, get the latest or specified one
-            if let Some(v) = version {
-                versions
-                    .get(v)
-                    .ok_or_else(|| anyhow!("Version {} not found", v))?
-            } else {
-                let latest_version = data["dist-tags"]["latest"]
-                    .as_str()
-                    .ok_or_else(|| anyhow!("No latest version found"))?;
-                versions
-                    .get(latest_version)
-                    .ok_or_else(|| anyhow!("Latest version not found in versions"))?
+                let data: Value = response.json().await?;
+                if let Some(cache) = &self.cache {
+                    // A cache that cannot be written to only costs a re-fetch
+                    let _ = cache.put_metadata(&cache_key, &data);
+                }
+                data
             }
-        } else {
-            // Response is for a specific version
-            &data
         };
 
-        let name = pkg_data["name"]
-            .as_str()
-            .ok_or_else(|| anyhow!("No name field in package info"))?;
-        let version = pkg_data["version"]
-            .as_str()
-            .ok_or_else(|| anyhow!("No version field in package info"))?;
-        let tarball_url = pkg_data["dist"]["tarball"]
-            .as_str()
-            .ok_or_else(|| anyhow!("No tarball URL in package info"))?;
+        package_info_from_document(&data, version)
+    }
 
+    /// Offline lookup: expired metadata is better than none, and a cached
+    /// release needs no metadata at all
+    fn cached_package_info(
+        &self,
+        package_name: &str,
+        version: Option<&str>,
+        spec: &str,
+        cache_key: &str,
+    ) -> Result<NpmPackageInfo> {
+        let offline_error = || {
+            anyhow::Error::new(OfflineError {
+                package: spec.to_string(),
+            })
+        };
+        let cache = self.cache.as_ref().ok_or_else(offline_error)?;
+
+        if let Some(data) = cache.get_stale_metadata(cache_key) {
+            if let Ok(info) = package_info_from_document(&data, version) {
+                return Ok(info);
+            }
+        }
+
+        // Without a version, the most recently used cached release stands in
+        // for latest; a range picks the highest cached release it allows
+        let registry = self.cache_registry(package_name);
+        let cached = || {
+            cache
+                .entries()
+                .ok()
+                .into_iter()
+                .flatten()
+                .filter(|entry| entry.name == package_name && entry.registry == registry)
+        };
+        let entry = match version.map(VersionRange::parse) {
+            None => cached().max_by_key(|entry| entry.last_used),
+            Some(Ok(range)) => {
+                let entries: Vec<CacheEntry> = cached().collect();
+                range
+                    .max_satisfying(entries.iter().map(|entry| entry.version.as_str()))
+                    .map(str::to_string)
+                    .and_then(|v| entries.into_iter().find(|entry| entry.version == v))
+            }
+            // A dist-tag cannot be resolved without its document
+            Some(Err(_)) => None,
+        };
+        let entry = entry.ok_or_else(offline_error)?;
         Ok(NpmPackageInfo {
-            name: name.to_string(),
-            version: version.to_string(),
-            tarball_url: tarball_url.to_string(),
+            name: entry.name,
+            version: entry.version,
+            tarball_url: entry.tarball_url,
+            integrity: entry.integrity,
+            shasum: None,
         })
     }
 
-    /// Download and extract package to a temporary directory
+    /// Download and extract a package, reusing the cached copy when present
     pub async fn download_package(
         &self,
         package_info: &NpmPackageInfo,
         quiet: bool,
-    ) -> Result<TempDir> {
+    ) -> Result<DownloadedPackage> {
+        let spec = format!("{}@{}", package_info.name, package_info.version);
+        let cache_registry = self.cache_registry(&package_info.name);
+        // The hash the tarball is (or will be) stored under
+        let published = package_info.integrity.clone().or_else(|| {
+            package_info
+                .shasum
+                .as_deref()
+                .and_then(integrity::shasum_to_sri)
+        });
+        if let Some(cache) = &self.cache {
+            let (registry, name, version) = (
+                cache_registry.as_deref(),
+                package_info.name.as_str(),
+                package_info.version.as_str(),
+            );
+            // A cached release without the published hash is stale or was
+            // tampered with; it is downloaded and verified again
+            match cache.entry(registry, name, version) {
+                Some(entry) if published.is_none() || entry.integrity == published => {
+                    if let Some(root) = cache.get_package(registry, name, version) {
+                        return Ok(DownloadedPackage {
+                            root,
+                            _temp_dir: None,
+                        });
+                    }
+                }
+                Some(_) if !quiet => eprintln!(
+                    "⚠️  Cached {} does not match the registry's integrity; downloading it again",
+                    spec
+                ),
+                _ => {}
+            }
+        }
+
+        if self.offline {
+            return Err(OfflineError { package: spec }.into());
+        }
+
         if !quiet {
             eprintln!(
                 "📦 Downloading package {}@{}",
@@ -105,23 +342,319 @@ impl NpmClient {
         }
 
         // Download tarball
-        let response = self.client.get(&package_info.tarball_url).send().await?;
+        let registry = self.npmrc.registry_for(&package_info.name);
+        let response = self
+            .get(&package_info.tarball_url, &registry)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to download {} ({})",
+                package_info.tarball_url,
+                response.status()
+            ));
+        }
         let bytes = response.bytes().await?;
 
-        // Create temp directory
+        // Nothing from an unverified tarball is written to disk
+        integrity::verify(
+            &spec,
+            &bytes,
+            package_info.integrity.as_deref(),
+            package_info.shasum.as_deref(),
+        )?;
+
+        if let Some(cache) = &self.cache {
+            match cache.put_package(
+                cache_registry.as_deref(),
+                &package_info.name,
+                &package_info.version,
+                published.as_deref(),
+                &package_info.tarball_url,
+                &bytes,
+            ) {
+                Ok((root, report)) => {
+                    report_skipped(&spec, &report, quiet);
+                    return Ok(DownloadedPackage {
+                        root,
+                        _temp_dir: None,
+                    });
+                }
+                Err(e) if !quiet => eprintln!("⚠️  Could not cache package: {}", e),
+                Err(_) => {}
+            }
+        }
+
         let temp_dir = TempDir::new()?;
-        let tarball_path = temp_dir.path().join("package.tgz");
+        let report = extract::extract_tarball(&bytes, temp_dir.path(), &ExtractLimits::default())?;
+        report_skipped(&spec, &report, quiet);
+        Ok(DownloadedPackage {
+            root: temp_dir.path().to_path_buf(),
+            _temp_dir: Some(temp_dir),
+        })
+    }
+
+    /// The registry `package_name` is cached under; `None` for the public
+    /// registry
+    fn cache_registry(&self, package_name: &str) -> Option<String> {
+        let registry = self.npmrc.registry_for(package_name);
+        (registry != crate::npmrc::DEFAULT_REGISTRY).then_some(registry)
+    }
+
+    /// A GET request carrying whatever credentials `.npmrc` has for `url`,
+    /// or for `registry` when it is marked `always-auth` and `url` is under
+    /// it; tarballs on other hosts never see the registry's token
+    fn get(&self, url: &str, registry: &str) -> RequestBuilder {
+        let credentials = self.npmrc.credentials_for(url).or_else(|| {
+            (self.npmrc.always_auth(registry) && npmrc::is_within_registry(url, registry))
+                .then(|| self.npmrc.credentials_for(registry))
+                .flatten()
+        });
+        let request = self.client.get(url);
+        match credentials {
+            Some(credentials) => request.header(AUTHORIZATION, credentials.header_value()),
+            None => request,
+        }
+    }
+
+    /// Open a package given by path or git URL rather than by name,
+    /// unpacking or checking it out as needed
+    pub async fn open_source(&self, source: PackageSource, quiet: bool) -> Result<InstalledPackage> {
+        Ok(match source {
+            PackageSource::Directory(path) => InstalledPackage::directory(path),
+            PackageSource::Tarball(path) => self.unpack_tarball_file(&path, quiet)?.into(),
+            PackageSource::Git { url, committish } => self
+                .clone_git(&url, committish.as_deref(), quiet)
+                .await?
+                .into(),
+            PackageSource::Registry { name, .. } => {
+                return Err(anyhow!("{} is not a local package source", name))
+            }
+        })
+    }
+
+    /// Unpack a tarball from disk (`npm pack` output) into a temporary
+    /// directory; like registry tarballs it is treated as untrusted
+    pub fn unpack_tarball_file(&self, path: &Path, quiet: bool) -> Result<DownloadedPackage> {
+        let bytes =
+            fs::read(path).with_context(|| format!("Failed to read tarball {}", path.display()))?;
+        let temp_dir = TempDir::new()?;
+        let report = extract::extract_tarball(&bytes, temp_dir.path(), &ExtractLimits::default())?;
+        report_skipped(&path.display().to_string(), &report, quiet);
+
+        // npm packs into `package/`, but hand-made tarballs may use any single top directory
+        let root = temp_dir.path().to_path_buf();
+        if !root.join("package").is_dir() {
+            let top_dirs: Vec<PathBuf> = fs::read_dir(&root)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_dir())
+                .collect();
+            match &top_dirs[..] {
+                [only] => fs::rename(only, root.join("package"))?,
+                _ => {
+                    return Err(anyhow!(
+                        "{} does not contain a package directory",
+                        path.display()
+                    ))
+                }
+            }
+        }
+        Ok(DownloadedPackage {
+            root,
+            _temp_dir: Some(temp_dir),
+        })
+    }
+
+    /// Shallow-fetch `committish` (default: the remote HEAD) of a git
+    /// repository into a temporary directory
+    pub async fn clone_git(
+        &self,
+        url: &str,
+        committish: Option<&str>,
+        quiet: bool,
+    ) -> Result<DownloadedPackage> {
+        let spec = match committish {
+            Some(committish) => format!("{}#{}", url, committish),
+            None => url.to_string(),
+        };
+        if self.offline && !url.starts_with("file://") {
+            return Err(OfflineError { package: spec }.into());
+        }
+        if !quiet {
+            eprintln!("📦 Fetching {}", spec);
+        }
+
+        let temp_dir = TempDir::new()?;
+        let checkout = temp_dir.path().join("package");
+        fs::create_dir_all(&checkout)?;
+        // Fetching by ref works for branches, tags and (on most hosts) commit
+        // hashes alike. `--end-of-options` keeps a URL or ref from being read
+        // as an option such as `--upload-pack`.
+        let steps: [&[&str]; 3] = [
+            &["init", "--quiet"],
+            &[
+                "fetch",
+                "--quiet",
+                "--depth",
+                "1",
+                "--end-of-options",
+                url,
+                committish.unwrap_or("HEAD"),
+            ],
+            &["switch", "--quiet", "--detach", "--end-of-options", "FETCH_HEAD"],
+        ];
+        for args in steps {
+            let output = tokio::process::Command::new("git")
+                .args(args)
+                .current_dir(&checkout)
+                // Credentials come from git's own helpers; never block on a prompt
+                .env("GIT_TERMINAL_PROMPT", "0")
+                .output()
+                .await
+                .context("Failed to run git")?;
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "git {} failed for {}: {}",
+                    args[0],
+                    spec,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+        fs::remove_dir_all(checkout.join(".git"))?;
+
+        Ok(DownloadedPackage {
+            root: temp_dir.path().to_path_buf(),
+            _temp_dir: Some(temp_dir),
+        })
+    }
+
+    /// Directories Node would look for packages in from the project
+    /// directory: `node_modules` in it and every ancestor, then `NODE_PATH`,
+    /// then the global folders (`~/.node_modules`, `~/.node_libraries`,
+    /// `<prefix>/lib/node`) and npm's global installs (`<prefix>/lib/node_modules`)
+    pub fn module_directories(&self) -> Vec<PathBuf> {
+        let project_dir = self
+            .project_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.project_dir.clone());
+        let mut directories: Vec<PathBuf> = project_dir
+            .ancestors()
+            // `node_modules/node_modules` is never searched
+            .filter(|dir| dir.file_name().is_none_or(|name| name != "node_modules"))
+            .map(|dir| dir.join("node_modules"))
+            .collect();
+
+        if let Some(node_path) = env::var_os("NODE_PATH") {
+            directories
+                .extend(env::split_paths(&node_path).filter(|dir| !dir.as_os_str().is_empty()));
+        }
+        if let Some(home) = env::var_os("HOME").map(PathBuf::from) {
+            directories.push(home.join(".node_modules"));
+            directories.push(home.join(".node_libraries"));
+        }
+        let prefix = self
+            .npmrc
+            .get("prefix")
+            .map(PathBuf::from)
+            .or_else(crate::npmrc::global_prefix);
+        if let Some(prefix) = prefix {
+            directories.push(prefix.join("lib").join("node"));
+            directories.push(prefix.join("lib").join("node_modules"));
+        }
+        directories
+    }
 
-        // Write tarball to temp file
-        fs::write(&tarball_path, bytes)?;
+    /// The nearest local install of `package_name` that satisfies `version`
+    /// (exact or a range); dist-tags only mean something to the registry, so
+    /// they never match a local install
+    ///
+    /// Besides plain `node_modules/<name>` directories this finds packages in
+    /// pnpm's `.pnpm` store, in the project's workspaces (`pnpm-workspace.yaml`
+    /// or package.json `workspaces`) and in Yarn Plug'n'Play installs, whose
+    /// zip-cached packages are unpacked to a temporary directory.
+    pub fn find_installed_package(
+        &self,
+        package_name: &str,
+        version: Option<&str>,
+    ) -> Option<InstalledPackage> {
+        let range = match version {
+            Some(version) => Some(VersionRange::parse(version).ok()?),
+            None => None,
+        };
+        let package_json = |path: &Path| PackageJson::read(&path.join("package.json")).ok();
+        let satisfies = |path: &Path| match &range {
+            Some(range) => package_json(path)
+                .and_then(|package_json| parse_version(&package_json.version?))
+                .is_some_and(|installed| range.matches(&installed)),
+            None => true,
+        };
 
-        // Extract tarball
-        let tar_gz = fs::File::open(&tarball_path)?;
-        let tar = flate2::read::GzDecoder::new(tar_gz);
-        let mut archive = tar::Archive::new(tar);
-        archive.unpack(temp_dir.path())?;
+        for dir in self.module_directories() {
+            let candidates = std::iter::once(dir.join(package_name))
+                .chain(package_layout::pnpm_store_packages(&dir, package_name));
+            for candidate in candidates {
+                if candidate.is_dir() && satisfies(&candidate) {
+                    // pnpm links packages out of its store; resolving the link
+                    // puts the package next to its own dependencies
+                    let path = candidate.canonicalize().unwrap_or(candidate);
+                    return Some(InstalledPackage::directory(path));
+                }
+            }
+        }
+
+        let project_dir = self
+            .project_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.project_dir.clone());
+        let workspace_package = package_layout::workspace_packages(&project_dir)
+            .into_iter()
+            .find(|path| {
+                package_json(path).and_then(|package_json| package_json.name).as_deref()
+                    == Some(package_name)
+                    && satisfies(path)
+            });
+        if let Some(path) = workspace_package {
+            return Some(InstalledPackage::directory(path));
+        }
 
-        Ok(temp_dir)
+        PnpManifest::find(&project_dir)?
+            .package_locations(package_name)
+            .iter()
+            .filter_map(|location| location.open().ok())
+            .find(|package| satisfies(package.path()))
+    }
+
+    /// The DefinitelyTyped package (`@types/<name>`) for a package without
+    /// declarations of its own: the installed one whatever its version, else
+    /// the registry's release for the same major version as `version` (the
+    /// installed version of the package itself)
+    ///
+    /// The lookup is best-effort: a missing package, network failure or
+    /// `--offline` all give `None`.
+    pub async fn find_types_package(
+        &self,
+        package_name: &str,
+        version: Option<&str>,
+        quiet: bool,
+    ) -> Option<InstalledPackage> {
+        let types_name = crate::utils::types_package_name(package_name)?;
+        if let Some(installed) = self.find_installed_package(&types_name, None) {
+            return Some(installed);
+        }
+
+        // DefinitelyTyped releases follow the major (for 0.x the minor)
+        // version of the package they describe
+        let range = version.and_then(parse_version).map(|version| match version.major {
+            0 => format!("^0.{}", version.minor),
+            major => format!("^{}", major),
+        });
+        let package_info = self
+            .get_package_info(&types_name, range.as_deref())
+            .await
+            .ok()?;
+        let package = self.download_package(&package_info, quiet).await.ok()?;
+        Some(package.into())
     }
 
     /// Check if package is locally installed in node_modules
@@ -141,3 +674,93 @@ impl NpmClient {
         None
     }
 }
+
+/// Resolve an exact version, dist-tag or semver range against a registry
+/// document the way npm does: exact versions and tags first, then the
+/// `latest` release if the range allows it, else the highest match
+fn resolve_version(data: &Value, requested: Option<&str>) -> Result<String> {
+    let dist_tag = |tag: &str| data["dist-tags"][tag].as_str().map(str::to_string);
+    let Some(requested) = requested else {
+        return dist_tag("latest").ok_or_else(|| anyhow!("No latest version found"));
+    };
+    if data["versions"].get(requested).is_some() {
+        return Ok(requested.to_string());
+    }
+    if let Some(tagged) = dist_tag(requested) {
+        return Ok(tagged);
+    }
+
+    let name = data["name"].as_str().unwrap_or("package");
+    let range = VersionRange::parse(requested).map_err(|_| {
+        anyhow!(
+            "'{}' is neither a version, a range nor a dist-tag of {}",
+            requested,
+            name
+        )
+    })?;
+    if let Some(latest) = dist_tag("latest") {
+        if parse_version(&latest).is_some_and(|version| range.matches(&version)) {
+            return Ok(latest);
+        }
+    }
+    let versions = data["versions"]
+        .as_object()
+        .map(|versions| versions.keys().map(String::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+    range
+        .max_satisfying(versions)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("No version of {} matches '{}'", name, requested))
+}
+
+/// Warn about tarball entries the extractor refused to write
+fn report_skipped(package: &str, report: &ExtractReport, quiet: bool) {
+    if quiet || report.skipped.is_empty() {
+        return;
+    }
+    eprintln!(
+        "⚠️  Skipped {} unsafe entr{} in {}:",
+        report.skipped.len(),
+        if report.skipped.len() == 1 {
+            "y"
+        } else {
+            "ies"
+        },
+        package
+    );
+    for entry in &report.skipped {
+        eprintln!("   {} ({})", entry.path, entry.reason.label());
+    }
+}
+
+/// Pick the requested (or latest) release out of a registry document
+fn package_info_from_document(data: &Value, version: Option<&str>) -> Result<NpmPackageInfo> {
+    // Handle both package@version and latest package responses
+    let pkg_data = if let Some(versions) = data.get("versions") {
+        let resolved = resolve_version(data, version)?;
+        versions
+            .get(&resolved)
+            .ok_or_else(|| anyhow!("Version {} not found in versions", resolved))?
+    } else {
+        // Response is for a specific version
+        data
+    };
+
+    let name = pkg_data["name"]
+        .as_str()
+        .ok_or_else(|| anyhow!("No name field in package info"))?;
+    let version = pkg_data["version"]
+        .as_str()
+        .ok_or_else(|| anyhow!("No version field in package info"))?;
+    let tarball_url = pkg_data["dist"]["tarball"]
+        .as_str()
+        .ok_or_else(|| anyhow!("No tarball URL in package info"))?;
+
+    Ok(NpmPackageInfo {
+        name: name.to_string(),
+        version: version.to_string(),
+        tarball_url: tarball_url.to_string(),
+        integrity: pkg_data["dist"]["integrity"].as_str().map(str::to_string),
+        shasum: pkg_data["dist"]["shasum"].as_str().map(str::to_string),
+    })
+}
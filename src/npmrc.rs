@@ -0,0 +1,344 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// Registry settings merged from npm's config files
+///
+/// Files are read global → user → project, later files overriding earlier
+/// ones, as npm does. Only the keys that matter for fetching packages are
+/// interpreted: `registry`, `@scope:registry`, `always-auth` and the
+/// per-registry credentials (`//host/path/:_authToken`, `:_auth`,
+/// `:username` + `:_password`).
+#[derive(Debug, Clone, Default)]
+pub struct NpmrcConfig {
+    settings: HashMap<String, String>,
+}
+
+/// Credentials to send to a registry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    Bearer(String),
+    /// Base64 of `user:password`
+    Basic(String),
+}
+
+impl Credentials {
+    pub fn header_value(&self) -> String {
+        match self {
+            Credentials::Bearer(token) => format!("Bearer {}", token),
+            Credentials::Basic(encoded) => format!("Basic {}", encoded),
+        }
+    }
+}
+
+impl NpmrcConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the global, user and project `.npmrc` files that apply to `cwd`
+    pub fn load(cwd: &Path) -> Self {
+        let mut config = Self::new();
+        let mut seen = Vec::new();
+        for path in [
+            global_config_path(),
+            user_config_path(),
+            project_config_path(cwd),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            let path = path.canonicalize().unwrap_or(path);
+            if seen.contains(&path) {
+                continue;
+            }
+            if let Ok(contents) = fs::read_to_string(&path) {
+                config.apply(&contents);
+            }
+            seen.push(path);
+        }
+        config
+    }
+
+    /// Merge the settings of one `.npmrc` file over the current ones
+    pub fn apply(&mut self, contents: &str) {
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            // `[section]` headers and lines without a value carry nothing we use
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = interpolate(unquote(key.trim()));
+            let value = interpolate(unquote(value.trim()));
+            self.settings.insert(key, value);
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.settings.get(key).map(String::as_str)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.settings.insert(key.to_string(), value.to_string());
+    }
+
+    /// The default registry, always with a trailing slash
+    pub fn registry(&self) -> String {
+        with_trailing_slash(self.get("registry").unwrap_or(DEFAULT_REGISTRY))
+    }
+
+    /// The registry serving `package`, honouring `@scope:registry`
+    pub fn registry_for(&self, package: &str) -> String {
+        package
+            .strip_prefix('@')
+            .and_then(|rest| rest.split_once('/'))
+            .and_then(|(scope, _)| self.get(&format!("@{}:registry", scope)))
+            .map(with_trailing_slash)
+            .unwrap_or_else(|| self.registry())
+    }
+
+    /// Credentials configured for `url`, matched on the longest
+    /// `//host/path/` prefix as npm does
+    pub fn credentials_for(&self, url: &str) -> Option<Credentials> {
+        let found = nerf_darts(url)
+            .into_iter()
+            .find_map(|prefix| self.credentials_at(&format!("{}:", prefix)));
+        // Legacy unscoped credentials only ever apply to the default registry
+        found.or_else(|| {
+            url.starts_with(&self.registry())
+                .then(|| self.credentials_at(""))
+                .flatten()
+        })
+    }
+
+    /// Whether `registry`'s credentials go along with every request under
+    /// it, even where no credentials are configured for the request's URL
+    pub fn always_auth(&self, registry: &str) -> bool {
+        nerf_darts(registry)
+            .into_iter()
+            .find_map(|prefix| self.get(&format!("{}:always-auth", prefix)))
+            .or_else(|| self.get("always-auth"))
+            .is_some_and(|value| value == "true")
+    }
+
+    fn credentials_at(&self, prefix: &str) -> Option<Credentials> {
+        let key = |name: &str| self.get(&format!("{}{}", prefix, name));
+        if let Some(token) = key("_authToken") {
+            return Some(Credentials::Bearer(token.to_string()));
+        }
+        if let Some(auth) = key("_auth") {
+            return Some(Credentials::Basic(auth.to_string()));
+        }
+        let username = key("username")?;
+        // `_password` is stored base64-encoded
+        let password = STANDARD.decode(key("_password")?).ok()?;
+        let password = String::from_utf8(password).ok()?;
+        Some(Credentials::Basic(
+            STANDARD.encode(format!("{}:{}", username, password)),
+        ))
+    }
+}
+
+/// npm's global prefix: `NPM_CONFIG_PREFIX`, else the directory above the
+/// one holding the `node` binary on `PATH`
+pub fn global_prefix() -> Option<PathBuf> {
+    if let Some(prefix) = env_var("NPM_CONFIG_PREFIX") {
+        return Some(PathBuf::from(prefix));
+    }
+    let node = env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join("node"))
+        .find(|candidate| candidate.is_file())?;
+    let node = node.canonicalize().unwrap_or(node);
+    Some(node.parent()?.parent()?.to_path_buf())
+}
+
+fn global_config_path() -> Option<PathBuf> {
+    env_var("NPM_CONFIG_GLOBALCONFIG")
+        .map(PathBuf::from)
+        .or_else(|| Some(global_prefix()?.join("etc").join("npmrc")))
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    env_var("NPM_CONFIG_USERCONFIG")
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".npmrc")))
+}
+
+/// `.npmrc` at the project root: the nearest ancestor with a package.json
+fn project_config_path(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors()
+        .find(|dir| dir.join("package.json").is_file())
+        .map(|dir| dir.join(".npmrc"))
+}
+
+/// npm reads its environment overrides case-insensitively
+fn env_var(name: &str) -> Option<String> {
+    env::var(name)
+        .or_else(|_| env::var(name.to_lowercase()))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+/// Replace `${VAR}` with the environment variable; `\${VAR}` stays literal.
+/// Unset variables are left as written so the failure is visible.
+fn interpolate(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let escaped = rest[..start].ends_with('\\');
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        if escaped {
+            result.push_str(&rest[..start - 1]);
+            result.push_str(&rest[start..=end]);
+        } else {
+            result.push_str(&rest[..start]);
+            let name = &rest[start + 2..end];
+            match env::var(name) {
+                Ok(substitute) => result.push_str(&substitute),
+                Err(_) => result.push_str(&rest[start..=end]),
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn with_trailing_slash(url: &str) -> String {
+    if url.ends_with('/') {
+        url.to_string()
+    } else {
+        format!("{}/", url)
+    }
+}
+
+/// Whether `url` lies under `registry`'s nerf-dart (`//host/path/`), so
+/// that the registry's credentials may be sent with it
+pub fn is_within_registry(url: &str, registry: &str) -> bool {
+    let registry = with_trailing_slash(registry);
+    match nerf_darts(&registry).first() {
+        Some(registry_dart) => nerf_darts(url).contains(registry_dart),
+        None => false,
+    }
+}
+
+/// `https://host:8080/a/b/c.tgz` → `//host:8080/a/b/`, `//host:8080/a/`,
+/// `//host:8080/`
+fn nerf_darts(url: &str) -> Vec<String> {
+    let Some((_, rest)) = url.split_once("//") else {
+        return Vec::new();
+    };
+    let rest = rest.split(['?', '#']).next().unwrap_or(rest);
+    let mut prefixes: Vec<String> = rest
+        .match_indices('/')
+        .map(|(i, _)| format!("//{}", &rest[..=i]))
+        .collect();
+    if !rest.contains('/') {
+        prefixes.push(format!("//{}/", rest));
+    }
+    prefixes.reverse();
+    prefixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(contents: &str) -> NpmrcConfig {
+        let mut config = NpmrcConfig::new();
+        config.apply(contents);
+        config
+    }
+
+    #[test]
+    fn test_registry_selection() {
+        let config = config(
+            "; company mirror\n\
+             registry = https://npm.acme.test/mirror\n\
+             @acme:registry=\"https://npm.acme.test/private/\"\n",
+        );
+        assert_eq!(config.registry(), "https://npm.acme.test/mirror/");
+        assert_eq!(
+            config.registry_for("express"),
+            "https://npm.acme.test/mirror/"
+        );
+        assert_eq!(
+            config.registry_for("@acme/ui"),
+            "https://npm.acme.test/private/"
+        );
+        assert_eq!(
+            config.registry_for("@types/node"),
+            "https://npm.acme.test/mirror/"
+        );
+        assert_eq!(NpmrcConfig::new().registry(), DEFAULT_REGISTRY);
+    }
+
+    #[test]
+    fn test_credentials_match_longest_prefix() {
+        env::set_var("PRETTY_NODE_TEST_NPM_TOKEN", "s3cret");
+        let config = config(
+            "//npm.acme.test/:_authToken=host-wide\n\
+             //npm.acme.test/private/:_authToken=${PRETTY_NODE_TEST_NPM_TOKEN}\n\
+             //npm.acme.test/private/:always-auth=true\n\
+             //basic.test/:username=bob\n\
+             //basic.test/:_password=aHVudGVyMg==\n\
+             //literal.test/:_authToken=\\${NOT_INTERPOLATED}\n",
+        );
+        assert_eq!(
+            config.credentials_for("https://npm.acme.test/private/@acme/ui/-/ui-1.0.0.tgz"),
+            Some(Credentials::Bearer("s3cret".to_string()))
+        );
+        assert_eq!(
+            config.credentials_for("https://npm.acme.test/mirror/express"),
+            Some(Credentials::Bearer("host-wide".to_string()))
+        );
+        assert_eq!(
+            config
+                .credentials_for("http://basic.test/pkg")
+                .unwrap()
+                .header_value(),
+            format!("Basic {}", STANDARD.encode("bob:hunter2"))
+        );
+        assert_eq!(
+            config.credentials_for("https://literal.test/pkg"),
+            Some(Credentials::Bearer("${NOT_INTERPOLATED}".to_string()))
+        );
+        assert_eq!(
+            config.credentials_for("https://registry.npmjs.org/express"),
+            None
+        );
+
+        assert!(config.always_auth("https://npm.acme.test/private/"));
+        assert!(!config.always_auth("https://npm.acme.test/mirror/"));
+        assert!(is_within_registry(
+            "https://npm.acme.test/private/@acme/ui/-/ui-1.0.0.tgz",
+            "https://npm.acme.test/private"
+        ));
+        assert!(!is_within_registry(
+            "https://cdn.acme.test/private/@acme/ui/-/ui-1.0.0.tgz",
+            "https://npm.acme.test/private/"
+        ));
+        assert!(!is_within_registry(
+            "https://npm.acme.test/mirror/ui/-/ui-1.0.0.tgz",
+            "https://npm.acme.test/private/"
+        ));
+    }
+}
@@ -1,4 +1,9 @@
+use crate::api_diff::ApiDiff;
+use crate::dts_formatter::DtsFormatter;
+use crate::llm_formatter::LlmFormatter;
+use crate::markdown_formatter::MarkdownFormatter;
 use crate::module_info::{NodeModuleInfo, SignatureInfo};
+use crate::search::SearchResult;
 use crate::tree_formatter::TreeFormatter;
 use anyhow::Result;
 
@@ -12,6 +17,12 @@ pub trait OutputFormatter {
 
     /// Format a signature not available message
     fn format_signature_not_available(&self, object_name: &str) -> String;
+
+    /// Format the symbols found by `search`
+    fn format_search_results(&self, pattern: &str, results: &[SearchResult]) -> Result<String>;
+
+    /// Format the API changes between two package versions
+    fn format_api_diff(&self, diff: &ApiDiff) -> Result<String>;
 }
 
 /// Pretty print formatter (current default behavior)
@@ -45,6 +56,14 @@ impl OutputFormatter for PrettyPrintFormatter {
     fn format_signature_not_available(&self, object_name: &str) -> String {
         format!("📎 {}\nsignature not available", object_name)
     }
+
+    fn format_search_results(&self, pattern: &str, results: &[SearchResult]) -> Result<String> {
+        Ok(self.tree_formatter.format_search_results(pattern, results))
+    }
+
+    fn format_api_diff(&self, diff: &ApiDiff) -> Result<String> {
+        Ok(self.tree_formatter.format_api_diff(diff))
+    }
 }
 
 /// JSON formatter for machine-readable output
@@ -81,12 +100,23 @@ impl OutputFormatter for JsonFormatter {
         });
         serde_json::to_string_pretty(&fallback).unwrap_or_else(|_| "{}".to_string())
     }
+
+    fn format_search_results(&self, _pattern: &str, results: &[SearchResult]) -> Result<String> {
+        Ok(serde_json::to_string_pretty(results)?)
+    }
+
+    fn format_api_diff(&self, diff: &ApiDiff) -> Result<String> {
+        Ok(serde_json::to_string_pretty(diff)?)
+    }
 }
 
 /// Factory function to create formatter based on format string
 pub fn create_formatter(format: &str) -> Box<dyn OutputFormatter> {
     match format.to_lowercase().as_str() {
         "json" => Box::new(JsonFormatter::new()),
+        "markdown" => Box::new(MarkdownFormatter::new()),
+        "dts" => Box::new(DtsFormatter::new()),
+        "llm" => Box::new(LlmFormatter::new()),
         _ => Box::new(PrettyPrintFormatter::new()),
     }
 }
\ No newline at end of file
@@ -0,0 +1,329 @@
+use crate::module_info::{Parameter, SignatureInfo, SignatureKind, SymbolSource};
+use crate::semver_range::{parse_version, VersionRange};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Overrides shipped with pretty-node, from the repository's `overrides/`
+const BUILTIN: [(&str, &str); 2] = [
+    ("express.json", include_str!("../overrides/express.json")),
+    ("react.toml", include_str!("../overrides/react.toml")),
+];
+
+/// Curated signatures for symbols whose real signature can't be parsed
+/// from the package (factories assembled at runtime, hooks re-exported
+/// through CommonJS bundles)
+///
+/// Override files hold an `overrides` list, as JSON or TOML, of entries
+/// keyed by package (or subpath), npm version range and symbol. The
+/// built-in set is read first, then every file in
+/// `$XDG_CONFIG_HOME/pretty-node/overrides` (else
+/// `~/.config/pretty-node/overrides`) in name order; when several entries
+/// match, the one read last wins.
+#[derive(Debug, Clone, Default)]
+pub struct SignatureOverrides {
+    entries: Vec<(SignatureOverride, String)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OverrideFile {
+    #[serde(default)]
+    overrides: Vec<SignatureOverride>,
+}
+
+/// One curated signature
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignatureOverride {
+    pub package: String,
+    /// The versions it applies to; all of them when absent
+    pub versions: Option<String>,
+    pub symbol: String,
+    #[serde(default)]
+    pub kind: OverrideKind,
+    #[serde(default)]
+    pub parameters: Vec<OverrideParameter>,
+    pub returns: Option<String>,
+    #[serde(default)]
+    pub type_params: Vec<String>,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverrideKind {
+    #[default]
+    Function,
+    Method,
+    Constructor,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OverrideParameter {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: Option<String>,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub rest: bool,
+    pub default: Option<String>,
+}
+
+impl SignatureOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The overrides shipped with pretty-node
+    pub fn builtin() -> Self {
+        let mut overrides = Self::new();
+        for (name, content) in BUILTIN {
+            overrides
+                .add_file(Path::new(name), content, name)
+                .expect("built-in overrides are valid");
+        }
+        overrides
+    }
+
+    /// The built-in overrides followed by the user's; files that fail to
+    /// parse are skipped with a warning
+    pub fn load(quiet: bool) -> Self {
+        let mut overrides = Self::builtin();
+        let Some(dir) = user_overrides_dir() else {
+            return overrides;
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return overrides;
+        };
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(|ext| ext.to_str()),
+                    Some("json" | "toml")
+                )
+            })
+            .collect();
+        files.sort();
+
+        for file in files {
+            if let Err(e) = overrides.add_path(&file) {
+                if !quiet {
+                    eprintln!(
+                        "⚠️  Ignoring signature overrides in {}: {:#}",
+                        file.display(),
+                        e
+                    );
+                }
+            }
+        }
+        overrides
+    }
+
+    /// Read an override file, JSON or TOML by extension
+    pub fn add_path(&mut self, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)?;
+        self.add_file(path, &content, &path.display().to_string())
+    }
+
+    fn add_file(&mut self, path: &Path, content: &str, origin: &str) -> Result<()> {
+        let file: OverrideFile = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(content)?,
+            Some("toml") => toml::from_str(content)?,
+            _ => return Err(anyhow!("Override files must be .json or .toml")),
+        };
+        for entry in &file.overrides {
+            if let Some(range) = &entry.versions {
+                VersionRange::parse(range).with_context(|| {
+                    format!("in the override for {}:{}", entry.package, entry.symbol)
+                })?;
+            }
+        }
+        self.entries.extend(
+            file.overrides
+                .into_iter()
+                .map(|entry| (entry, origin.to_string())),
+        );
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The curated signature of `module:symbol` at `version`. Overrides
+    /// restricted to a version range never match an unknown version.
+    pub fn lookup(
+        &self,
+        module: &str,
+        version: Option<&str>,
+        symbol: &str,
+    ) -> Option<SignatureInfo> {
+        let version = version.and_then(parse_version);
+        self.entries
+            .iter()
+            .rev()
+            .find(|(entry, _)| {
+                entry.package == module
+                    && entry.symbol == symbol
+                    && match (&entry.versions, &version) {
+                        (None, _) => true,
+                        (Some(range), Some(version)) => {
+                            VersionRange::parse(range).is_ok_and(|range| range.matches(version))
+                        }
+                        (Some(_), None) => false,
+                    }
+            })
+            .map(|(entry, origin)| entry.to_signature(origin))
+    }
+}
+
+impl SignatureOverride {
+    fn to_signature(&self, origin: &str) -> SignatureInfo {
+        SignatureInfo {
+            name: self.symbol.clone(),
+            kind: match self.kind {
+                OverrideKind::Function => SignatureKind::Function,
+                OverrideKind::Method => SignatureKind::Method,
+                OverrideKind::Constructor => SignatureKind::Constructor,
+            },
+            parameters: self
+                .parameters
+                .iter()
+                .map(|parameter| Parameter {
+                    name: parameter.name.clone(),
+                    param_type: parameter.param_type.clone(),
+                    is_optional: parameter.optional,
+                    is_rest: parameter.rest,
+                    default_value: parameter.default.clone(),
+                })
+                .collect(),
+            return_type: self.returns.clone(),
+            type_params: self.type_params.clone(),
+            doc_comment: self.doc.clone(),
+            doc_tags: None,
+            source: SymbolSource::Curated(origin.to_string()),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/pretty-node/overrides`, else `~/.config/pretty-node/overrides`
+pub fn user_overrides_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| PathBuf::from(dir).join("pretty-node/overrides"))
+        .or_else(|| {
+            env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".config/pretty-node/overrides"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_and_toml() {
+        let mut overrides = SignatureOverrides::new();
+        overrides
+            .add_file(
+                Path::new("a.json"),
+                r#"{"overrides": [{"package": "lib", "symbol": "make", "kind": "constructor",
+                    "parameters": [{"name": "parts", "type": "string[]", "rest": true}],
+                    "returns": "Thing"}]}"#,
+                "a.json",
+            )
+            .unwrap();
+        overrides
+            .add_file(
+                Path::new("b.toml"),
+                "[[overrides]]\npackage = \"lib/sub\"\nsymbol = \"run\"\n\n[[overrides.parameters]]\nname = \"n\"\ntype = \"number\"\noptional = true\n",
+                "b.toml",
+            )
+            .unwrap();
+        assert_eq!(overrides.len(), 2);
+
+        let make = overrides.lookup("lib", Some("1.0.0"), "make").unwrap();
+        assert!(matches!(make.kind, SignatureKind::Constructor));
+        assert!(make.parameters[0].is_rest);
+        assert_eq!(make.return_type.as_deref(), Some("Thing"));
+        assert_eq!(make.source, SymbolSource::Curated("a.json".to_string()));
+
+        let run = overrides.lookup("lib/sub", None, "run").unwrap();
+        assert!(run.parameters[0].is_optional);
+        assert_eq!(run.source, SymbolSource::Curated("b.toml".to_string()));
+        assert!(overrides.lookup("lib", None, "run").is_none());
+
+        assert!(overrides
+            .add_file(
+                Path::new("c.json"),
+                r#"{"overrides": [{"package": "x"}]}"#,
+                "c.json"
+            )
+            .is_err());
+        assert!(overrides
+            .add_file(
+                Path::new("d.toml"),
+                "[[overrides]]\npackage = \"x\"\nsymbol = \"y\"\nversions = \"next\"\n",
+                "d.toml",
+            )
+            .is_err());
+        assert_eq!(overrides.len(), 2);
+    }
+
+    #[test]
+    fn test_version_ranges() {
+        let mut overrides = SignatureOverrides::new();
+        let file = r#"{"overrides": [
+            {"package": "lib", "symbol": "f", "returns": "any"},
+            {"package": "lib", "versions": "^2", "symbol": "f", "returns": "string"},
+            {"package": "lib", "versions": ">=3", "symbol": "f", "returns": "number"}
+        ]}"#;
+        overrides
+            .add_file(Path::new("f.json"), file, "f.json")
+            .unwrap();
+        let returns = |version: Option<&str>| {
+            overrides
+                .lookup("lib", version, "f")
+                .and_then(|signature| signature.return_type)
+        };
+        assert_eq!(returns(Some("1.0.0")).as_deref(), Some("any"));
+        assert_eq!(returns(Some("2.4.1")).as_deref(), Some("string"));
+        assert_eq!(returns(Some("3.0.0")).as_deref(), Some("number"));
+        assert_eq!(returns(None).as_deref(), Some("any"));
+    }
+
+    #[test]
+    fn test_builtin_overrides() {
+        let overrides = SignatureOverrides::builtin();
+        let router = overrides
+            .lookup("express", Some("4.18.2"), "Router")
+            .unwrap();
+        assert_eq!(router.return_type.as_deref(), Some("Router"));
+        assert!(overrides
+            .lookup("express", Some("4.15.0"), "json")
+            .is_none());
+        assert!(overrides.lookup("express", None, "Router").is_none());
+
+        let use_state = overrides
+            .lookup("react", Some("18.2.0"), "useState")
+            .unwrap();
+        assert_eq!(use_state.type_params, vec!["S"]);
+        assert_eq!(
+            use_state.parameters[0].param_type.as_deref(),
+            Some("S | (() => S)")
+        );
+        assert!(overrides
+            .lookup("react", Some("16.7.0"), "useState")
+            .is_none());
+    }
+}
@@ -0,0 +1,414 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Conditions active when none are given on the command line: what Node
+/// and TypeScript match when the package is used from a Node project
+pub const DEFAULT_CONDITIONS: &[&str] = &["types", "node", "import", "require"];
+
+/// The package.json fields that decide where a package's code lives
+#[derive(Debug, Clone, Default)]
+pub struct PackageJson {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub main: Option<String>,
+    pub module: Option<String>,
+    /// `types`, or the older `typings` spelling
+    pub types: Option<String>,
+    /// Either a replacement for `main` or a map of file substitutions
+    pub browser: Option<Value>,
+    pub exports: Option<Value>,
+}
+
+/// Outcome of matching a target in the `exports` map
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Path(String),
+    /// Explicitly hidden with `null`
+    Excluded,
+    /// No condition matched
+    Unmatched,
+}
+
+impl PackageJson {
+    pub fn read(path: &Path) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let data: Value = serde_json::from_str(content)?;
+        Ok(Self::from_value(&data))
+    }
+
+    pub fn from_value(data: &Value) -> Self {
+        let string_field = |key: &str| data.get(key).and_then(|v| v.as_str()).map(String::from);
+        Self {
+            name: string_field("name"),
+            version: string_field("version"),
+            main: string_field("main"),
+            module: string_field("module"),
+            types: string_field("types").or_else(|| string_field("typings")),
+            browser: data.get("browser").cloned(),
+            exports: data.get("exports").cloned(),
+        }
+    }
+
+    /// Resolve an import subpath (`"."`, `"./utils"`) through the `exports`
+    /// map. Returns `None` when the package has no `exports` field, or when
+    /// the subpath is not exported or is excluded with `null`.
+    pub fn resolve_export(&self, subpath: &str, conditions: &[String]) -> Option<String> {
+        let exports = self.exports.as_ref()?;
+        match resolve_exports(exports, subpath, conditions) {
+            Target::Path(path) => Some(path),
+            Target::Excluded | Target::Unmatched => None,
+        }
+    }
+
+    /// Every public subpath of the package with the file it resolves to,
+    /// `"."` first. Wildcard subpaths are expanded against the files on disk.
+    pub fn exported_entries(
+        &self,
+        package_path: &Path,
+        conditions: &[String],
+    ) -> Vec<(String, PathBuf)> {
+        let Some(exports) = &self.exports else {
+            return Vec::new();
+        };
+
+        let mut subpaths = Vec::new();
+        for (key, target) in subpath_map(exports) {
+            if !key.contains('*') {
+                subpaths.push(key);
+                continue;
+            }
+
+            // Expand `./features/*` by matching its target pattern against the package files
+            let Target::Path(pattern) = resolve_target(target, None, conditions) else {
+                continue;
+            };
+            let Some((prefix, suffix)) = pattern.trim_start_matches("./").split_once('*') else {
+                continue;
+            };
+            for entry in WalkDir::new(package_path)
+                .into_iter()
+                .filter_entry(|e| e.file_name() != "node_modules")
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+            {
+                let Ok(relative) = entry.path().strip_prefix(package_path) else {
+                    continue;
+                };
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                if relative.len() < prefix.len() + suffix.len() {
+                    continue;
+                }
+                if let Some(matched) = relative
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix))
+                {
+                    subpaths.push(key.replacen('*', matched, 1));
+                }
+            }
+        }
+
+        let mut entries: Vec<(String, PathBuf)> = Vec::new();
+        for subpath in subpaths {
+            if entries.iter().any(|(existing, _)| *existing == subpath) {
+                continue;
+            }
+            // Re-resolve so more specific keys and `null` exclusions win
+            if let Some(file) = self.resolve_export_file(package_path, &subpath, conditions) {
+                entries.push((subpath, file));
+            }
+        }
+        entries.sort_by_key(|(subpath, _)| subpath != ".");
+        entries
+    }
+
+    /// Resolve an exported subpath to a file on disk. Published packages
+    /// sometimes omit the declaration files their `types` condition points
+    /// at, so a missing target is retried without that condition.
+    pub fn resolve_export_file(
+        &self,
+        package_path: &Path,
+        subpath: &str,
+        conditions: &[String],
+    ) -> Option<PathBuf> {
+        let to_file = |target: String| package_path.join(target.trim_start_matches("./"));
+        let file = self.resolve_export(subpath, conditions).map(to_file)?;
+        if file.is_file() {
+            return Some(file);
+        }
+
+        let without_types: Vec<String> = conditions
+            .iter()
+            .filter(|c| *c != "types")
+            .cloned()
+            .collect();
+        self.resolve_export(subpath, &without_types)
+            .map(to_file)
+            .filter(|file| file.is_file())
+    }
+
+    /// The file Node would load for a bare `import "pkg"`
+    pub fn main_entry(&self, package_path: &Path, conditions: &[String]) -> Option<PathBuf> {
+        if self.exports.is_some() {
+            return self.resolve_export_file(package_path, ".", conditions);
+        }
+
+        let has = |condition: &str| conditions.iter().any(|c| c == condition);
+        let mut candidates = Vec::new();
+        if has("browser") {
+            if let Some(Value::String(browser)) = &self.browser {
+                candidates.push(browser.clone());
+            }
+        }
+        if has("import") {
+            candidates.extend(self.module.clone());
+        }
+        candidates.extend(self.main.clone());
+
+        let entry = candidates
+            .iter()
+            .find_map(|candidate| probe_file(&package_path.join(candidate)))?;
+
+        // The object form of `browser` swaps individual files
+        if has("browser") {
+            if let Some(Value::Object(replacements)) = &self.browser {
+                for (from, to) in replacements {
+                    if probe_file(&package_path.join(from)).as_ref() == Some(&entry) {
+                        return to
+                            .as_str()
+                            .and_then(|to| probe_file(&package_path.join(to)));
+                    }
+                }
+            }
+        }
+
+        Some(entry)
+    }
+
+    /// Whether the package ships declarations of its own: `types`/`typings`,
+    /// a `types` export condition or an `index.d.ts` in a usual place
+    pub fn declares_types(&self, package_path: &Path) -> bool {
+        let types_export = self
+            .resolve_export(".", &["types".to_string()])
+            .is_some_and(|target| target.ends_with(".d.ts"));
+        self.types_entry(package_path).is_some()
+            || types_export
+            || [
+                "index.d.ts",
+                "lib/index.d.ts",
+                "types/index.d.ts",
+                "dist/index.d.ts",
+            ]
+            .iter()
+            .any(|file| package_path.join(file).is_file())
+    }
+
+    /// The declaration file named by `types`/`typings`
+    pub fn types_entry(&self, package_path: &Path) -> Option<PathBuf> {
+        self.types
+            .as_ref()
+            .map(|types| package_path.join(types))
+            .filter(|path| path.is_file())
+    }
+}
+
+/// Build the active condition list from user-supplied names; `default`
+/// always matches, so it is never listed
+pub fn active_conditions(requested: &[String]) -> Vec<String> {
+    if requested.is_empty() {
+        DEFAULT_CONDITIONS.iter().map(|c| c.to_string()).collect()
+    } else {
+        requested.to_vec()
+    }
+}
+
+/// Try a path as given, with `.js` appended, and as a directory index
+fn probe_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+    let with_js = PathBuf::from(format!("{}.js", path.display()));
+    if with_js.is_file() {
+        return Some(with_js);
+    }
+    let index = path.join("index.js");
+    index.is_file().then_some(index)
+}
+
+/// The `exports` value as `(subpath, target)` pairs. A string, an array or
+/// an object of conditions is shorthand for the `"."` subpath.
+fn subpath_map(exports: &Value) -> Vec<(String, &Value)> {
+    match exports {
+        Value::Object(map) if map.keys().any(|key| key.starts_with('.')) => map
+            .iter()
+            .filter(|(key, _)| key.starts_with('.'))
+            .map(|(key, value)| (key.clone(), value))
+            .collect(),
+        other => vec![(".".to_string(), other)],
+    }
+}
+
+/// Node's PACKAGE_EXPORTS_RESOLVE: exact keys first, then the most specific
+/// wildcard key
+fn resolve_exports(exports: &Value, subpath: &str, conditions: &[String]) -> Target {
+    let map = subpath_map(exports);
+
+    if let Some((_, target)) = map
+        .iter()
+        .find(|(key, _)| key == subpath && !key.contains('*'))
+    {
+        return resolve_target(target, None, conditions);
+    }
+
+    let mut best: Option<(&str, &str, &Value)> = None;
+    for (key, target) in &map {
+        let Some((base, trailer)) = key.split_once('*') else {
+            continue;
+        };
+        if trailer.contains('*') || !subpath.starts_with(base) || subpath == base {
+            continue;
+        }
+        if !trailer.is_empty() && (subpath.len() < key.len() || !subpath.ends_with(trailer)) {
+            continue;
+        }
+        let is_better = match best {
+            None => true,
+            Some((best_key, best_base, _)) => {
+                base.len() > best_base.len()
+                    || (base.len() == best_base.len() && key.len() > best_key.len())
+            }
+        };
+        if is_better {
+            best = Some((key, base, target));
+        }
+    }
+
+    match best {
+        Some((key, base, target)) => {
+            let trailer_len = key.len() - base.len() - 1;
+            let matched = &subpath[base.len()..subpath.len() - trailer_len];
+            resolve_target(target, Some(matched), conditions)
+        }
+        None => Target::Unmatched,
+    }
+}
+
+/// Node's PACKAGE_TARGET_RESOLVE over strings, fallback arrays, condition
+/// objects and `null`
+fn resolve_target(target: &Value, pattern_match: Option<&str>, conditions: &[String]) -> Target {
+    match target {
+        Value::String(path) => {
+            // Targets must stay inside the package
+            if !path.starts_with("./") || path.split('/').any(|segment| segment == "..") {
+                return Target::Unmatched;
+            }
+            match pattern_match {
+                Some(matched) => Target::Path(path.replace('*', matched)),
+                None => Target::Path(path.clone()),
+            }
+        }
+        Value::Array(fallbacks) => fallbacks
+            .iter()
+            .map(|fallback| resolve_target(fallback, pattern_match, conditions))
+            .find(|resolved| *resolved != Target::Unmatched)
+            .unwrap_or(Target::Unmatched),
+        Value::Object(map) => {
+            for (condition, value) in map {
+                if condition == "default" || conditions.contains(condition) {
+                    let resolved = resolve_target(value, pattern_match, conditions);
+                    if resolved != Target::Unmatched {
+                        return resolved;
+                    }
+                }
+            }
+            Target::Unmatched
+        }
+        Value::Null => Target::Excluded,
+        _ => Target::Unmatched,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conditions(names: &[&str]) -> Vec<String> {
+        names.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_nested_conditions() {
+        let package = PackageJson::parse(
+            r#"{"exports": {".": {"import": {"types": "./index.d.mts", "default": "./index.mjs"}, "require": "./index.cjs"}, "./package.json": "./package.json"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            package
+                .resolve_export(".", &conditions(&["import"]))
+                .as_deref(),
+            Some("./index.mjs")
+        );
+        assert_eq!(
+            package
+                .resolve_export(".", &conditions(&["types", "import"]))
+                .as_deref(),
+            Some("./index.d.mts")
+        );
+        assert_eq!(
+            package
+                .resolve_export(".", &conditions(&["require"]))
+                .as_deref(),
+            Some("./index.cjs")
+        );
+        assert_eq!(
+            package.resolve_export("./missing", &conditions(&["import"])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_subpath_patterns_and_null() {
+        let package = PackageJson::parse(
+            r#"{"exports": {"./features/*.js": "./src/features/*.js", "./features/internal/*": null, "./utils/*": {"browser": "./browser/*.js", "default": "./lib/*.js"}}}"#,
+        )
+        .unwrap();
+        let node = conditions(&["node"]);
+        assert_eq!(
+            package.resolve_export("./features/x.js", &node).as_deref(),
+            Some("./src/features/x.js")
+        );
+        assert_eq!(package.resolve_export("./features/internal/y", &node), None);
+        assert_eq!(
+            package.resolve_export("./utils/a/b", &node).as_deref(),
+            Some("./lib/a/b.js")
+        );
+        assert_eq!(
+            package
+                .resolve_export("./utils/a", &conditions(&["browser"]))
+                .as_deref(),
+            Some("./browser/a.js")
+        );
+    }
+
+    #[test]
+    fn test_sugar_and_legacy_fields() {
+        let package =
+            PackageJson::parse(r#"{"exports": "./main.js", "typings": "x.d.ts"}"#).unwrap();
+        assert_eq!(
+            package.resolve_export(".", &conditions(&[])).as_deref(),
+            Some("./main.js")
+        );
+        assert_eq!(package.types.as_deref(), Some("x.d.ts"));
+
+        let package = PackageJson::parse(r#"{"exports": ["../escape.js", "./ok.js"]}"#).unwrap();
+        assert_eq!(
+            package.resolve_export(".", &conditions(&[])).as_deref(),
+            Some("./ok.js")
+        );
+    }
+}
@@ -0,0 +1,459 @@
+use crate::extract::{self, ExtractLimits};
+use crate::semver_range::parse_version;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+/// A package found on disk by a package manager's layout rather than at
+/// `node_modules/<name>`; packages unpacked from Yarn's zip cache live in a
+/// temporary directory that is removed on drop
+pub struct InstalledPackage {
+    path: PathBuf,
+    _temp_dir: Option<TempDir>,
+}
+
+impl InstalledPackage {
+    pub fn directory(path: PathBuf) -> Self {
+        Self {
+            path,
+            _temp_dir: None,
+        }
+    }
+
+    /// A package unpacked into `temp_dir`, which is kept alive with it
+    pub fn unpacked(path: PathBuf, temp_dir: Option<TempDir>) -> Self {
+        Self {
+            path,
+            _temp_dir: temp_dir,
+        }
+    }
+
+    /// The package root (the directory holding its package.json)
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Copies of `name` in pnpm's virtual store below `modules_dir`
+/// (`.pnpm/<name>@<version>[_peers]/node_modules/<name>`), highest version first
+pub fn pnpm_store_packages(modules_dir: &Path, name: &str) -> Vec<PathBuf> {
+    // Scoped names are stored as `@scope+name`
+    let entry_prefix = format!("{}@", name.replace('/', "+"));
+    let Ok(entries) = fs::read_dir(modules_dir.join(".pnpm")) else {
+        return Vec::new();
+    };
+    let mut packages: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let entry_name = entry.file_name().to_string_lossy().into_owned();
+            let rest = entry_name.strip_prefix(&entry_prefix)?;
+            // pnpm 7 appends peers as `_peer@1.0.0`, pnpm 8 as `(peer@1.0.0)`
+            let version = parse_version(rest.split(['_', '(']).next()?)?;
+            let path = entry.path().join("node_modules").join(name);
+            path.is_dir().then_some((version, path))
+        })
+        .collect();
+    packages.sort_by(|(a, _), (b, _)| b.cmp(a));
+    packages.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Package directories of the workspace `start` belongs to, as declared in
+/// `pnpm-workspace.yaml` or the root package.json's `workspaces`
+pub fn workspace_packages(start: &Path) -> Vec<PathBuf> {
+    let Some((root, patterns)) = start.ancestors().find_map(|dir| {
+        let patterns = match fs::read_to_string(dir.join("pnpm-workspace.yaml")) {
+            Ok(contents) => pnpm_workspace_patterns(&contents),
+            Err(_) => npm_workspace_patterns(&dir.join("package.json"))?,
+        };
+        Some((dir.to_path_buf(), patterns))
+    }) else {
+        return Vec::new();
+    };
+
+    let (excluded, included): (Vec<&String>, Vec<&String>) = patterns
+        .iter()
+        .partition(|pattern| pattern.starts_with('!'));
+    let included: Vec<Regex> = included.into_iter().filter_map(|p| glob_regex(p)).collect();
+    let excluded: Vec<Regex> = excluded
+        .into_iter()
+        .filter_map(|p| glob_regex(&p[1..]))
+        .collect();
+
+    let mut packages = vec![root.clone()];
+    let walker = WalkDir::new(&root)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.file_type().is_dir() && name != "node_modules" && !name.starts_with('.')
+        });
+    for entry in walker.filter_map(|entry| entry.ok()) {
+        let Ok(relative) = entry.path().strip_prefix(&root) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if entry.path().join("package.json").is_file()
+            && included.iter().any(|glob| glob.is_match(&relative))
+            && !excluded.iter().any(|glob| glob.is_match(&relative))
+        {
+            packages.push(entry.into_path());
+        }
+    }
+    packages
+}
+
+/// The `packages:` list of a `pnpm-workspace.yaml`
+fn pnpm_workspace_patterns(contents: &str) -> Vec<String> {
+    let unquote = |item: &str| item.trim().trim_matches(['\'', '"']).to_string();
+    let mut patterns = Vec::new();
+    let mut in_packages = false;
+    for line in contents.lines() {
+        let line = line.split(" #").next().unwrap_or(line).trim_end();
+        if let Some(rest) = line.strip_prefix("packages:") {
+            // Flow style: `packages: ['a/*', 'b']`
+            if let Some(list) = rest.trim().strip_prefix('[') {
+                let list = list.trim_end_matches(']');
+                patterns.extend(list.split(',').map(unquote).filter(|p| !p.is_empty()));
+            }
+            in_packages = true;
+            continue;
+        }
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !in_packages {
+            continue;
+        }
+        match trimmed.strip_prefix('-') {
+            Some(item) => patterns.push(unquote(item)),
+            // Any other key at the top level ends the list
+            None if !line.starts_with(' ') => in_packages = false,
+            None => {}
+        }
+    }
+    patterns
+}
+
+/// `workspaces` of a package.json, either a list or `{ "packages": [...] }`
+fn npm_workspace_patterns(package_json: &Path) -> Option<Vec<String>> {
+    let data: Value = serde_json::from_str(&fs::read_to_string(package_json).ok()?).ok()?;
+    let workspaces = &data["workspaces"];
+    let list = workspaces
+        .as_array()
+        .or_else(|| workspaces["packages"].as_array())?;
+    Some(
+        list.iter()
+            .filter_map(|pattern| pattern.as_str().map(str::to_string))
+            .collect(),
+    )
+}
+
+/// Translate a workspace glob (`packages/*`, `apps/**`) into a regex over
+/// `/`-separated paths relative to the workspace root
+fn glob_regex(pattern: &str) -> Option<Regex> {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+/// Yarn Plug'n'Play's package map, from `.pnp.data.json` or the state
+/// inlined in `.pnp.cjs`
+pub struct PnpManifest {
+    /// The directory holding the manifest; package locations are relative to it
+    root: PathBuf,
+    data: Value,
+}
+
+/// Where Plug'n'Play keeps a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PnpLocation {
+    Directory(PathBuf),
+    /// A directory inside an archive of Yarn's zip cache
+    Zip {
+        archive: PathBuf,
+        inner: String,
+    },
+}
+
+impl PnpManifest {
+    /// The manifest of the nearest Plug'n'Play project above `start`
+    pub fn find(start: &Path) -> Option<Self> {
+        start.ancestors().find_map(Self::load)
+    }
+
+    fn load(dir: &Path) -> Option<Self> {
+        let data = match fs::read_to_string(dir.join(".pnp.data.json")) {
+            Ok(contents) => serde_json::from_str(&contents).ok()?,
+            Err(_) => {
+                let script = fs::read_to_string(dir.join(".pnp.cjs")).ok()?;
+                serde_json::from_str(&inlined_runtime_state(&script)?).ok()?
+            }
+        };
+        Some(Self::new(dir.to_path_buf(), data))
+    }
+
+    pub fn new(root: PathBuf, data: Value) -> Self {
+        Self { root, data }
+    }
+
+    /// Every location of `name` the manifest knows of: the version the
+    /// project itself depends on first, then the others, highest first
+    pub fn package_locations(&self, name: &str) -> Vec<PnpLocation> {
+        let registry = self.data["packageRegistryData"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let references = |package: Option<&str>| -> Vec<(Option<&str>, &Value)> {
+            registry
+                .iter()
+                .filter(|entry| entry[0].as_str() == package)
+                .filter_map(|entry| entry[1].as_array())
+                .flatten()
+                .map(|reference| (reference[0].as_str(), &reference[1]))
+                .collect()
+        };
+
+        // The top-level workspace is registered without a name or reference
+        let project_reference = references(None)
+            .into_iter()
+            .filter_map(|(_, info)| info["packageDependencies"].as_array())
+            .flatten()
+            .find(|dependency| dependency[0].as_str() == Some(name))
+            .and_then(|dependency| dependency[1].as_str());
+
+        let mut candidates = references(Some(name));
+        candidates.sort_by_key(|(reference, _)| {
+            let version = reference
+                .and_then(|reference| reference.rsplit("npm:").next())
+                .and_then(parse_version);
+            (*reference != project_reference, std::cmp::Reverse(version))
+        });
+
+        let mut locations = Vec::new();
+        for (_, info) in candidates {
+            let Some(location) = info["packageLocation"].as_str() else {
+                continue;
+            };
+            let location = self.resolve(location);
+            if !locations.contains(&location) {
+                locations.push(location);
+            }
+        }
+        locations
+    }
+
+    fn resolve(&self, location: &str) -> PnpLocation {
+        let location = devirtualize(location);
+        match location.split_once(".zip/") {
+            Some((archive, inner)) => PnpLocation::Zip {
+                archive: self.root.join(format!("{}.zip", archive)),
+                inner: inner.to_string(),
+            },
+            None => PnpLocation::Directory(self.root.join(location)),
+        }
+    }
+}
+
+impl PnpLocation {
+    /// Make the package readable on disk, unpacking it from its archive if
+    /// need be
+    pub fn open(&self) -> Result<InstalledPackage> {
+        match self {
+            PnpLocation::Directory(path) => {
+                if !path.is_dir() {
+                    return Err(anyhow!("{} does not exist", path.display()));
+                }
+                Ok(InstalledPackage::directory(path.clone()))
+            }
+            PnpLocation::Zip { archive, inner } => {
+                let temp_dir = TempDir::new()?;
+                let path = temp_dir.path().join("package");
+                extract::extract_zip_subtree(archive, inner, &path, &ExtractLimits::default())?;
+                if !path.join("package.json").is_file() {
+                    return Err(anyhow!("{} has no package at {}", archive.display(), inner));
+                }
+                Ok(InstalledPackage::unpacked(path, Some(temp_dir)))
+            }
+        }
+    }
+}
+
+/// Yarn's virtual paths (`.yarn/__virtual__/<hash>/<depth>/<path>`) stand
+/// for `<path>` resolved `<depth>` directories above `.yarn`
+fn devirtualize(location: &str) -> String {
+    let segments: Vec<&str> = location.split('/').collect();
+    let Some(index) = segments
+        .iter()
+        .position(|segment| *segment == "__virtual__")
+    else {
+        return location.to_string();
+    };
+    let Some(depth) = segments
+        .get(index + 2)
+        .and_then(|depth| depth.parse::<usize>().ok())
+    else {
+        return location.to_string();
+    };
+    let mut resolved = segments[..index].to_vec();
+    resolved.extend(std::iter::repeat_n("..", depth));
+    resolved.extend(&segments[index + 3..]);
+    resolved.join("/")
+}
+
+/// The JSON of `RAW_RUNTIME_STATE`, the single-quoted string literal Yarn
+/// inlines into `.pnp.cjs`
+fn inlined_runtime_state(script: &str) -> Option<String> {
+    let start = script.find("RAW_RUNTIME_STATE")?;
+    let literal = &script[start..];
+    let literal = &literal[literal.find('\'')? + 1..];
+
+    let mut state = String::new();
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => return Some(state),
+            '\\' => match chars.next()? {
+                // Line continuation
+                '\n' => {}
+                'n' => state.push('\n'),
+                't' => state.push('\t'),
+                escaped => state.push(escaped),
+            },
+            c => state.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_pnpm_store_packages() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = temp_dir.path().join(".pnpm");
+        for entry in [
+            "@scope+ui@1.0.0",
+            "@scope+ui@1.2.0(react@18.2.0)",
+            "@scope+ui@1.1.0_react@17.0.2",
+            "@scope+ui-extra@9.0.0",
+        ] {
+            let name = if entry.contains("extra") {
+                "@scope/ui-extra"
+            } else {
+                "@scope/ui"
+            };
+            fs::create_dir_all(store.join(entry).join("node_modules").join(name)).unwrap();
+        }
+
+        let found = pnpm_store_packages(temp_dir.path(), "@scope/ui");
+        let entries: Vec<String> = found
+            .iter()
+            .map(|path| {
+                path.strip_prefix(&store)
+                    .unwrap()
+                    .components()
+                    .next()
+                    .unwrap()
+            })
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                "@scope+ui@1.2.0(react@18.2.0)",
+                "@scope+ui@1.1.0_react@17.0.2",
+                "@scope+ui@1.0.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_workspace_patterns() {
+        let yaml = "packages:\n  # apps and libs\n  - 'apps/*'\n  - \"libs/**\" # nested\n  - '!**/fixtures/**'\ncatalog:\n  - not-a-package\n";
+        assert_eq!(
+            pnpm_workspace_patterns(yaml),
+            vec!["apps/*", "libs/**", "!**/fixtures/**"]
+        );
+        assert_eq!(
+            pnpm_workspace_patterns("packages: ['a/*', \"b\"]\n"),
+            vec!["a/*", "b"]
+        );
+
+        let glob = |pattern: &str, path: &str| glob_regex(pattern).unwrap().is_match(path);
+        assert!(glob("apps/*", "apps/web"));
+        assert!(!glob("apps/*", "apps/web/nested"));
+        assert!(glob("libs/**", "libs/a/b"));
+        assert!(glob("**/fixtures/**", "libs/a/fixtures/x"));
+        assert!(glob("./tools/cli/", "tools/cli"));
+    }
+
+    #[test]
+    fn test_pnp_locations() {
+        let data = json!({
+            "packageRegistryData": [
+                [null, [[null, {
+                    "packageLocation": "./",
+                    "packageDependencies": [["lodash", "npm:4.17.20"], ["app", "workspace:."]]
+                }]]],
+                ["lodash", [
+                    ["npm:4.17.21", {"packageLocation": "./.yarn/cache/lodash-npm-4.17.21-abc.zip/node_modules/lodash/"}],
+                    ["npm:4.17.20", {"packageLocation": "./.yarn/unplugged/lodash-npm-4.17.20/node_modules/lodash/"}],
+                    ["npm:3.10.1", {"packageLocation": "./.yarn/__virtual__/lodash-virtual-123/0/cache/lodash-npm-3.10.1-def.zip/node_modules/lodash/"}]
+                ]]
+            ]
+        });
+        let manifest = PnpManifest::new(PathBuf::from("/project"), data);
+        assert_eq!(
+            manifest.package_locations("lodash"),
+            vec![
+                PnpLocation::Directory(PathBuf::from(
+                    "/project/./.yarn/unplugged/lodash-npm-4.17.20/node_modules/lodash/"
+                )),
+                PnpLocation::Zip {
+                    archive: PathBuf::from("/project/./.yarn/cache/lodash-npm-4.17.21-abc.zip"),
+                    inner: "node_modules/lodash/".to_string(),
+                },
+                PnpLocation::Zip {
+                    archive: PathBuf::from("/project/./.yarn/cache/lodash-npm-3.10.1-def.zip"),
+                    inner: "node_modules/lodash/".to_string(),
+                },
+            ]
+        );
+        assert!(manifest.package_locations("react").is_empty());
+    }
+
+    #[test]
+    fn test_inlined_runtime_state() {
+        let script = "#!/usr/bin/env node\n/* eslint-disable */\n\"use strict\";\n\nconst RAW_RUNTIME_STATE =\n'{\\\n  \"__info\": [\"It\\'s generated\"],\\\n  \"packageRegistryData\": []\\\n}';\n\nfunction $$SETUP_STATE() {}\n";
+        let state: Value = serde_json::from_str(&inlined_runtime_state(script).unwrap()).unwrap();
+        assert_eq!(state["__info"][0], "It's generated");
+        assert!(state["packageRegistryData"].is_array());
+    }
+}
@@ -1,14 +1,22 @@
 use crate::module_info::*;
+use crate::parser::jsdoc::JsDocParser;
+use crate::parser::type_printer::TypePrinter;
 // use crate::parser::semantic_analyzer::SemanticAnalyzer; // TODO: Fix compilation errors
 use anyhow::{anyhow, Result};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use swc_common::{sync::Lrc, FileName, SourceMap};
+use swc_common::comments::{CommentKind, Comments, SingleThreadedComments};
+use swc_common::{sync::Lrc, BytePos, FileName, SourceMap, Spanned};
 use swc_ecma_ast::*;
 use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax, TsConfig};
 
 pub struct AstParser {
     source_map: Lrc<SourceMap>,
+    /// Comments collected while lexing the most recent parse
+    comments: SingleThreadedComments,
+    printer: TypePrinter,
+    jsdoc: JsDocParser,
 }
 
 impl Default for AstParser {
@@ -21,6 +29,9 @@ impl AstParser {
     pub fn new() -> Self {
         Self {
             source_map: Lrc::new(SourceMap::default()),
+            comments: SingleThreadedComments::default(),
+            printer: TypePrinter::new(),
+            jsdoc: JsDocParser::new(),
         }
     }
 
@@ -31,9 +42,9 @@ impl AstParser {
             .file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("unknown");
-        
+
         let module_info = self.parse_content(&content, module_name)?;
-        
+
         // TODO: Add enhanced semantic analysis for better symbol extraction
         // let mut analyzer = SemanticAnalyzer::new();
         // if analyzer.analyze_file(file_path).is_ok() {
@@ -42,42 +53,68 @@ impl AstParser {
         //         // Semantic analysis succeeded - we now have method signatures too
         //     }
         // }
-        
+
         Ok(module_info)
     }
 
     /// Parse JavaScript/TypeScript content and extract module information
     pub fn parse_content(&self, content: &str, module_name: &str) -> Result<NodeModuleInfo> {
-        let source_file = self
-            .source_map
-            .new_source_file(FileName::Anon, content.to_string());
-
-        let lexer = Lexer::new(
+        let module = self.parse_module(
+            content,
             Syntax::Typescript(TsConfig {
                 tsx: true,
                 decorators: true,
                 ..Default::default()
             }),
-            Default::default(),
-            StringInput::from(&*source_file),
-            None,
-        );
-
-        let mut parser = Parser::new_from(lexer);
-        let module = parser
-            .parse_module()
-            .map_err(|e| anyhow!("Parse error: {:?}", e))?;
+        )?;
 
         let mut module_info = NodeModuleInfo::new(module_name.to_string());
 
-        for item in &module.body {
+        // Function declarations are hoisted, so CommonJS assignments that refer
+        // to them (`exports.foo = foo`) are handled after every declaration;
+        // the symbols each statement adds then go back to where it stands
+        let (assignments, declarations): (Vec<_>, Vec<_>) = module
+            .body
+            .iter()
+            .enumerate()
+            .partition(|(_, item)| matches!(item, ModuleItem::Stmt(Stmt::Expr(_))));
+        let mut positions = SourcePositions::default();
+        for (index, item) in declarations.into_iter().chain(assignments) {
             self.process_module_item(item, &mut module_info)?;
+            positions.record(index, &module_info);
         }
+        positions.restore(&mut module_info);
+        self.collect_import_reexports(&module.body, &mut module_info);
 
         Ok(module_info)
     }
 
-    fn process_module_item(
+    /// Parse source text into an swc module using the given syntax
+    pub(crate) fn parse_module(&self, content: &str, syntax: Syntax) -> Result<Module> {
+        let source_file = self
+            .source_map
+            .new_source_file(FileName::Anon, content.to_string());
+
+        // Comments are only needed until the module has been processed
+        let (mut leading, mut trailing) = self.comments.borrow_all_mut();
+        leading.clear();
+        trailing.clear();
+        drop((leading, trailing));
+
+        let lexer = Lexer::new(
+            syntax,
+            Default::default(),
+            StringInput::from(&*source_file),
+            Some(&self.comments),
+        );
+
+        let mut parser = Parser::new_from(lexer);
+        parser
+            .parse_module()
+            .map_err(|e| anyhow!("Parse error: {:?}", e))
+    }
+
+    pub(crate) fn process_module_item(
         &self,
         item: &ModuleItem,
         module_info: &mut NodeModuleInfo,
@@ -100,31 +137,60 @@ impl AstParser {
     ) -> Result<()> {
         match decl {
             ModuleDecl::ExportDecl(export_decl) => {
+                // The doc comment precedes the `export` keyword, not the declaration
+                if let Some(comments) = self.comments.take_leading(export_decl.span.lo) {
+                    self.comments
+                        .add_leading_comments(export_decl.decl.span_lo(), comments);
+                }
                 self.process_decl(&export_decl.decl, module_info, true)?;
             }
             ModuleDecl::ExportNamed(named_export) => {
+                let source = named_export.src.as_ref().map(|src| src.value.to_string());
                 for spec in &named_export.specifiers {
-                    if let ExportSpecifier::Named(named) = spec {
-                        let name = match &named.exported {
-                            Some(exported) => self.module_export_name_to_string(exported),
-                            None => self.module_export_name_to_string(&named.orig),
-                        };
-                        module_info.exports.push(name);
+                    let (imported, exported) = match spec {
+                        ExportSpecifier::Named(named) => {
+                            let orig = self.module_export_name_to_string(&named.orig);
+                            let exported = match &named.exported {
+                                Some(exported) => self.module_export_name_to_string(exported),
+                                None => orig.clone(),
+                            };
+                            (orig, exported)
+                        }
+                        // `export * as ns from "./x"`
+                        ExportSpecifier::Namespace(namespace) => (
+                            "*".to_string(),
+                            self.module_export_name_to_string(&namespace.name),
+                        ),
+                        ExportSpecifier::Default(default) => {
+                            ("default".to_string(), default.exported.sym.to_string())
+                        }
+                    };
+                    if let Some(source) = &source {
+                        module_info.reexports.push(ReExportInfo {
+                            source: source.clone(),
+                            imported,
+                            exported: exported.clone(),
+                        });
                     }
+                    module_info.exports.push(exported);
                 }
             }
             ModuleDecl::ExportDefaultDecl(default_export) => {
                 match &default_export.decl {
                     DefaultDecl::Class(class_expr) => {
                         if let Some(ident) = &class_expr.ident {
-                            let class_info = self.extract_class_info(&class_expr.class, &ident.sym);
+                            let mut class_info =
+                                self.extract_class_info(&class_expr.class, &ident.sym);
+                            (class_info.doc_comment, class_info.doc_tags) =
+                                self.doc_at(default_export.span.lo);
                             module_info.add_class(class_info);
                         }
                     }
                     DefaultDecl::Fn(fn_expr) => {
                         if let Some(ident) = &fn_expr.ident {
-                            let func_info =
+                            let mut func_info =
                                 self.extract_function_info(&fn_expr.function, &ident.sym);
+                            self.document_function(&mut func_info, default_export.span.lo);
                             module_info.add_function(func_info);
                         }
                     }
@@ -132,34 +198,399 @@ impl AstParser {
                 }
                 module_info.exports.push("default".to_string());
             }
+            ModuleDecl::ExportAll(export_all) => {
+                module_info
+                    .star_exports
+                    .push(export_all.src.value.to_string());
+            }
             _ => {}
         }
         Ok(())
     }
 
     fn process_stmt(&self, stmt: &Stmt, module_info: &mut NodeModuleInfo) -> Result<()> {
-        if let Stmt::Decl(decl) = stmt {
-            self.process_decl(decl, module_info, false)?;
+        match stmt {
+            Stmt::Decl(decl) => self.process_decl(decl, module_info, false)?,
+            Stmt::Expr(expr_stmt) => {
+                self.process_commonjs_expr(&expr_stmt.expr, module_info, expr_stmt.span.lo)
+            }
+            _ => {}
         }
         Ok(())
     }
 
-    fn process_decl(
+    /// Record exports of imported bindings as re-exports: `import { a } from
+    /// "./a"; export { a }`, and the getters TypeScript emits for re-exports
+    /// in CommonJS output (`Object.defineProperty(exports, "a", { get:
+    /// function () { return a_1.a; } })` with `const a_1 = require("./a")`)
+    pub(crate) fn collect_import_reexports(
+        &self,
+        items: &[ModuleItem],
+        module_info: &mut NodeModuleInfo,
+    ) {
+        // local binding -> (source, imported name)
+        let mut bindings: HashMap<String, (String, String)> = HashMap::new();
+        for item in items {
+            match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+                    let source = import.src.value.to_string();
+                    for spec in &import.specifiers {
+                        let (local, imported) = match spec {
+                            ImportSpecifier::Named(named) => (
+                                named.local.sym.to_string(),
+                                named
+                                    .imported
+                                    .as_ref()
+                                    .map(|name| self.module_export_name_to_string(name))
+                                    .unwrap_or_else(|| named.local.sym.to_string()),
+                            ),
+                            ImportSpecifier::Default(default) => {
+                                (default.local.sym.to_string(), "default".to_string())
+                            }
+                            ImportSpecifier::Namespace(namespace) => {
+                                (namespace.local.sym.to_string(), "*".to_string())
+                            }
+                        };
+                        bindings.insert(local, (source.clone(), imported));
+                    }
+                }
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl))) => {
+                    for declarator in &var_decl.decls {
+                        let (Pat::Ident(ident), Some(init)) = (&declarator.name, &declarator.init)
+                        else {
+                            continue;
+                        };
+                        if let Some(source) = require_source(init) {
+                            bindings.insert(ident.id.sym.to_string(), (source, "*".to_string()));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if bindings.is_empty() {
+            return;
+        }
+
+        for item in items {
+            match item {
+                ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(named_export))
+                    if named_export.src.is_none() =>
+                {
+                    for spec in &named_export.specifiers {
+                        let ExportSpecifier::Named(named) = spec else {
+                            continue;
+                        };
+                        let local = self.module_export_name_to_string(&named.orig);
+                        if let Some((source, imported)) = bindings.get(&local) {
+                            let exported = named
+                                .exported
+                                .as_ref()
+                                .map(|name| self.module_export_name_to_string(name))
+                                .unwrap_or(local);
+                            module_info.reexports.push(ReExportInfo {
+                                source: source.clone(),
+                                imported: imported.clone(),
+                                exported,
+                            });
+                        }
+                    }
+                }
+                ModuleItem::Stmt(Stmt::Expr(expr_stmt)) => {
+                    if let Some((exported, local, member)) = reexport_getter(&expr_stmt.expr) {
+                        if let Some((source, _)) = bindings.get(&local) {
+                            module_info.reexports.push(ReExportInfo {
+                                source: source.clone(),
+                                imported: member,
+                                exported,
+                            });
+                        }
+                    }
+                    // `module.exports = { parse }` / `exports.parse = parse` with `const parse = require("./parse")`
+                    for (exported, local) in commonjs_exported_locals(&expr_stmt.expr) {
+                        if let Some((source, imported)) = bindings.get(&local) {
+                            module_info.reexports.push(ReExportInfo {
+                                source: source.clone(),
+                                imported: imported.clone(),
+                                exported,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Recognize CommonJS exports: `module.exports = ...`, `exports.foo = ...`,
+    /// `Object.defineProperty(exports, "foo", ...)` and the `__exportStar` /
+    /// `__export` helpers emitted by TypeScript and Babel
+    fn process_commonjs_expr(
+        &self,
+        expr: &Expr,
+        module_info: &mut NodeModuleInfo,
+        doc_pos: BytePos,
+    ) {
+        match expr {
+            Expr::Paren(paren) => self.process_commonjs_expr(&paren.expr, module_info, doc_pos),
+            // Minified output joins statements with commas
+            Expr::Seq(seq) => {
+                for expr in &seq.exprs {
+                    self.process_commonjs_expr(expr, module_info, doc_pos);
+                }
+            }
+            Expr::Assign(assign) if assign.op == AssignOp::Assign => {
+                let AssignTarget::Simple(target) = &assign.left else {
+                    return;
+                };
+                match commonjs_export_target(target) {
+                    Some(CommonJsTarget::Module) => {
+                        self.process_module_exports(&assign.right, module_info, doc_pos);
+                    }
+                    Some(CommonJsTarget::Named(name)) => {
+                        // `exports.a = exports.b = void 0` exports every name in the chain
+                        self.process_commonjs_expr(&assign.right, module_info, doc_pos);
+                        self.add_commonjs_export(
+                            &name,
+                            innermost_value(&assign.right),
+                            module_info,
+                            doc_pos,
+                        );
+                    }
+                    // `exports = module.exports = x`
+                    Some(CommonJsTarget::ExportsAlias) => {
+                        self.process_commonjs_expr(&assign.right, module_info, doc_pos);
+                    }
+                    None => {}
+                }
+            }
+            Expr::Call(call) => self.process_commonjs_call(call, module_info),
+            _ => {}
+        }
+    }
+
+    /// `module.exports = value`: the value becomes the module itself
+    fn process_module_exports(
+        &self,
+        value: &Expr,
+        module_info: &mut NodeModuleInfo,
+        doc_pos: BytePos,
+    ) {
+        match value {
+            Expr::Paren(paren) => self.process_module_exports(&paren.expr, module_info, doc_pos),
+            Expr::Assign(_) => {
+                self.process_commonjs_expr(value, module_info, doc_pos);
+                self.process_module_exports(innermost_value(value), module_info, doc_pos);
+            }
+            Expr::Object(object) => {
+                for prop in &object.props {
+                    match prop {
+                        PropOrSpread::Prop(prop) => match &**prop {
+                            Prop::Shorthand(ident) => {
+                                push_export(module_info, ident.sym.to_string());
+                            }
+                            Prop::KeyValue(key_value) => {
+                                let name = self.printer.print_prop_name(&key_value.key);
+                                self.add_commonjs_export(
+                                    &name,
+                                    &key_value.value,
+                                    module_info,
+                                    key_value.key.span_lo(),
+                                );
+                            }
+                            Prop::Method(method) => {
+                                let name = self.printer.print_prop_name(&method.key);
+                                let mut func_info =
+                                    self.extract_function_info(&method.function, &name);
+                                self.document_function(&mut func_info, method.key.span_lo());
+                                module_info.add_function(func_info);
+                                push_export(module_info, name);
+                            }
+                            _ => {}
+                        },
+                        // `module.exports = { ...require("./x") }`
+                        PropOrSpread::Spread(spread) => {
+                            if let Some(source) = require_source(&spread.expr) {
+                                module_info.star_exports.push(source);
+                            }
+                        }
+                    }
+                }
+            }
+            Expr::Ident(ident) => {
+                push_export(module_info, ident.sym.to_string());
+                module_info.export_assignment = Some(ident.sym.to_string());
+            }
+            Expr::Fn(fn_expr) => {
+                let name = fn_expr
+                    .ident
+                    .as_ref()
+                    .map_or("default".to_string(), |ident| ident.sym.to_string());
+                self.add_commonjs_export(&name, value, module_info, doc_pos);
+                module_info.export_assignment = Some(name);
+            }
+            Expr::Class(class_expr) => {
+                let name = class_expr
+                    .ident
+                    .as_ref()
+                    .map_or("default".to_string(), |ident| ident.sym.to_string());
+                self.add_commonjs_export(&name, value, module_info, doc_pos);
+                module_info.export_assignment = Some(name);
+            }
+            Expr::Arrow(_) => {
+                self.add_commonjs_export("default", value, module_info, doc_pos);
+                module_info.export_assignment = Some("default".to_string());
+            }
+            _ => {
+                if let Some(source) = require_source(value) {
+                    module_info.star_exports.push(source);
+                }
+            }
+        }
+    }
+
+    /// Record a named CommonJS export and, when its value is defined inline,
+    /// the function, class or constant behind it
+    fn add_commonjs_export(
+        &self,
+        name: &str,
+        value: &Expr,
+        module_info: &mut NodeModuleInfo,
+        doc_pos: BytePos,
+    ) {
+        push_export(module_info, name.to_string());
+
+        match value {
+            Expr::Paren(paren) => {
+                self.add_commonjs_export(name, &paren.expr, module_info, doc_pos);
+            }
+            Expr::Fn(fn_expr) => {
+                let mut func_info = self.extract_function_info(&fn_expr.function, name);
+                self.document_function(&mut func_info, doc_pos);
+                module_info.add_function(func_info);
+            }
+            Expr::Arrow(arrow) => {
+                let mut func_info = self.extract_arrow_info(arrow, name);
+                self.document_function(&mut func_info, doc_pos);
+                module_info.add_function(func_info);
+            }
+            Expr::Class(class_expr) => {
+                let mut class_info = self.extract_class_info(&class_expr.class, name);
+                (class_info.doc_comment, class_info.doc_tags) = self.doc_at(doc_pos);
+                module_info.add_class(class_info);
+            }
+            // `exports.foo = foo` re-exports a local declaration, possibly under another name
+            Expr::Ident(ident) => {
+                let local = ident.sym.to_string();
+                if local == name {
+                    return;
+                }
+                if let Some(function) = module_info.functions.iter().find(|f| f.name == local) {
+                    let function = FunctionInfo {
+                        name: name.to_string(),
+                        ..function.clone()
+                    };
+                    module_info.add_function(function);
+                } else if let Some(class) = module_info.classes.iter().find(|c| c.name == local) {
+                    let class = ClassInfo {
+                        name: name.to_string(),
+                        ..class.clone()
+                    };
+                    module_info.add_class(class);
+                }
+            }
+            // Placeholder from TypeScript's `exports.foo = void 0` preamble
+            Expr::Unary(unary) if unary.op == UnaryOp::Void => {}
+            // `exports.router = require("./router")` re-exports the whole module
+            _ if require_source(value).is_some() => {
+                module_info.reexports.push(ReExportInfo {
+                    source: require_source(value).unwrap_or_default(),
+                    imported: "*".to_string(),
+                    exported: name.to_string(),
+                });
+            }
+            _ => {
+                let already_defined = module_info.functions.iter().any(|f| f.name == name)
+                    || module_info.classes.iter().any(|c| c.name == name)
+                    || module_info.constants.iter().any(|c| c.name == name);
+                if !already_defined {
+                    let (doc_comment, doc_tags) = self.doc_at(doc_pos);
+                    module_info.add_constant(ConstantInfo {
+                        name: name.to_string(),
+                        value_type: None,
+                        doc_comment,
+                        doc_tags,
+                        defined_in: None,
+                        source: SymbolSource::Package,
+                    });
+                }
+            }
+        }
+    }
+
+    fn process_commonjs_call(&self, call: &CallExpr, module_info: &mut NodeModuleInfo) {
+        let Callee::Expr(callee) = &call.callee else {
+            return;
+        };
+        let args: Vec<&Expr> = call.args.iter().map(|arg| &*arg.expr).collect();
+
+        // Helpers may be called bare or through an import (`tslib_1.__exportStar`)
+        let callee_name = match &**callee {
+            Expr::Ident(ident) => ident.sym.to_string(),
+            Expr::Member(member) => match &member.prop {
+                MemberProp::Ident(ident) => {
+                    if is_ident(&member.obj, "Object") {
+                        format!("Object.{}", ident.sym)
+                    } else {
+                        ident.sym.to_string()
+                    }
+                }
+                _ => return,
+            },
+            _ => return,
+        };
+
+        match callee_name.as_str() {
+            "Object.defineProperty" => {
+                let [target, Expr::Lit(Lit::Str(name)), ..] = args.as_slice() else {
+                    return;
+                };
+                let is_exports = is_ident(target, "exports")
+                    || matches!(target, Expr::Member(member) if is_module_exports(member));
+                if is_exports && &*name.value != "__esModule" {
+                    push_export(module_info, name.value.to_string());
+                }
+            }
+            "__exportStar" | "__export" | "_exportStar" => {
+                if let Some(source) = args.first().and_then(|arg| require_source(arg)) {
+                    module_info.star_exports.push(source);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn process_decl(
         &self,
         decl: &Decl,
         module_info: &mut NodeModuleInfo,
         is_export: bool,
     ) -> Result<()> {
+        let doc_pos = decl.span_lo();
         match decl {
             Decl::Fn(fn_decl) => {
-                let func_info = self.extract_function_info(&fn_decl.function, &fn_decl.ident.sym);
+                let mut func_info =
+                    self.extract_function_info(&fn_decl.function, &fn_decl.ident.sym);
+                self.document_function(&mut func_info, doc_pos);
                 if is_export {
                     module_info.exports.push(fn_decl.ident.sym.to_string());
                 }
                 module_info.add_function(func_info);
             }
             Decl::Class(class_decl) => {
-                let class_info = self.extract_class_info(&class_decl.class, &class_decl.ident.sym);
+                let mut class_info =
+                    self.extract_class_info(&class_decl.class, &class_decl.ident.sym);
+                (class_info.doc_comment, class_info.doc_tags) = self.doc_at(doc_pos);
                 if is_export {
                     module_info.exports.push(class_decl.ident.sym.to_string());
                 }
@@ -174,40 +605,52 @@ impl AstParser {
                         }
 
                         // Try to determine if this is a function or constant
-                        if let Some(init) = &decl.init {
-                            match &**init {
-                                Expr::Arrow(_) | Expr::Fn(_) => {
-                                    // It's a function
-                                    let func_info = FunctionInfo {
-                                        name: name.clone(),
-                                        parameters: Vec::new(), // TODO: extract from arrow/fn
-                                        return_type: None,
-                                        is_async: false,
-                                        is_generator: false,
-                                        doc_comment: None,
-                                    };
-                                    module_info.add_function(func_info);
-                                }
-                                _ => {
-                                    // It's a constant
-                                    let const_info = ConstantInfo {
-                                        name: name.clone(),
-                                        value_type: None, // TODO: infer type
-                                        doc_comment: None,
-                                    };
-                                    module_info.add_constant(const_info);
-                                }
+                        match decl.init.as_deref() {
+                            Some(Expr::Fn(fn_expr)) => {
+                                let mut func_info =
+                                    self.extract_function_info(&fn_expr.function, &name);
+                                self.document_function(&mut func_info, doc_pos);
+                                module_info.add_function(func_info);
+                            }
+                            Some(Expr::Arrow(arrow)) => {
+                                let mut func_info = self.extract_arrow_info(arrow, &name);
+                                self.document_function(&mut func_info, doc_pos);
+                                module_info.add_function(func_info);
+                            }
+                            // Ambient declarations (`declare const x: T`) have no initializer
+                            None if !var_decl.declare && ident.type_ann.is_none() => {}
+                            // `const x = require("x")` is an import, not a constant
+                            Some(init) if require_source(init).is_some() => {}
+                            _ => {
+                                // It's a constant
+                                let (doc_comment, doc_tags) = self.doc_at(doc_pos);
+                                let const_info = ConstantInfo {
+                                    name: name.clone(),
+                                    value_type: ident
+                                        .type_ann
+                                        .as_ref()
+                                        .map(|ann| self.printer.print_type_ann(ann)),
+                                    doc_comment,
+                                    doc_tags,
+                                    defined_in: None,
+                                    source: SymbolSource::Package,
+                                };
+                                module_info.add_constant(const_info);
                             }
                         }
                     }
                 }
             }
             Decl::TsInterface(interface_decl) => {
+                let (doc_comment, doc_tags) = self.doc_at(doc_pos);
                 let type_info = TypeInfo {
                     name: interface_decl.id.sym.to_string(),
                     kind: TypeKind::Interface,
-                    definition: format!("interface {}", interface_decl.id.sym),
-                    doc_comment: None,
+                    definition: self.interface_definition(interface_decl),
+                    doc_comment,
+                    doc_tags,
+                    defined_in: None,
+                    source: SymbolSource::Package,
                 };
                 if is_export {
                     module_info.exports.push(interface_decl.id.sym.to_string());
@@ -215,11 +658,15 @@ impl AstParser {
                 module_info.add_type(type_info);
             }
             Decl::TsTypeAlias(type_alias) => {
+                let (doc_comment, doc_tags) = self.doc_at(doc_pos);
                 let type_info = TypeInfo {
                     name: type_alias.id.sym.to_string(),
                     kind: TypeKind::Type,
-                    definition: format!("type {}", type_alias.id.sym),
-                    doc_comment: None,
+                    definition: self.type_alias_definition(type_alias),
+                    doc_comment,
+                    doc_tags,
+                    defined_in: None,
+                    source: SymbolSource::Package,
                 };
                 if is_export {
                     module_info.exports.push(type_alias.id.sym.to_string());
@@ -227,11 +674,15 @@ impl AstParser {
                 module_info.add_type(type_info);
             }
             Decl::TsEnum(enum_decl) => {
+                let (doc_comment, doc_tags) = self.doc_at(doc_pos);
                 let type_info = TypeInfo {
                     name: enum_decl.id.sym.to_string(),
                     kind: TypeKind::Enum,
-                    definition: format!("enum {}", enum_decl.id.sym),
-                    doc_comment: None,
+                    definition: self.enum_definition(enum_decl),
+                    doc_comment,
+                    doc_tags,
+                    defined_in: None,
+                    source: SymbolSource::Package,
                 };
                 if is_export {
                     module_info.exports.push(enum_decl.id.sym.to_string());
@@ -253,10 +704,39 @@ impl AstParser {
         FunctionInfo {
             name: name.to_string(),
             parameters,
-            return_type: function.return_type.as_ref().map(|_| "unknown".to_string()), // TODO: extract actual type
+            return_type: function
+                .return_type
+                .as_ref()
+                .map(|ann| self.printer.print_type_ann(ann)),
+            type_params: self.extract_type_params(function.type_params.as_deref()),
             is_async: function.is_async,
             is_generator: function.is_generator,
             doc_comment: None,
+            doc_tags: None,
+            defined_in: None,
+            source: SymbolSource::Package,
+        }
+    }
+
+    fn extract_arrow_info(&self, arrow: &ArrowExpr, name: &str) -> FunctionInfo {
+        FunctionInfo {
+            name: name.to_string(),
+            parameters: arrow
+                .params
+                .iter()
+                .map(|pat| self.extract_parameter_info(pat))
+                .collect(),
+            return_type: arrow
+                .return_type
+                .as_ref()
+                .map(|ann| self.printer.print_type_ann(ann)),
+            type_params: self.extract_type_params(arrow.type_params.as_deref()),
+            is_async: arrow.is_async,
+            is_generator: arrow.is_generator,
+            doc_comment: None,
+            doc_tags: None,
+            defined_in: None,
+            source: SymbolSource::Package,
         }
     }
 
@@ -268,55 +748,97 @@ impl AstParser {
         for member in &class.body {
             match member {
                 ClassMember::Constructor(ctor) => {
-                    let func_info = FunctionInfo {
+                    let mut parameters = Vec::new();
+                    for param in &ctor.params {
+                        match param {
+                            ParamOrTsParamProp::Param(p) => {
+                                parameters.push(self.extract_parameter_info(&p.pat));
+                            }
+                            ParamOrTsParamProp::TsParamProp(prop) => {
+                                let pat = match &prop.param {
+                                    TsParamPropParam::Ident(ident) => Pat::Ident(ident.clone()),
+                                    TsParamPropParam::Assign(assign) => Pat::Assign(assign.clone()),
+                                };
+                                let parameter = self.extract_parameter_info(&pat);
+                                // Parameter properties also declare a class property
+                                properties.push(PropertyInfo {
+                                    name: parameter.name.clone(),
+                                    property_type: parameter.param_type.clone(),
+                                    is_readonly: prop.readonly,
+                                    is_static: false,
+                                    doc_comment: None,
+                                    doc_tags: None,
+                                });
+                                parameters.push(parameter);
+                            }
+                        }
+                    }
+
+                    let mut func_info = FunctionInfo {
                         name: "constructor".to_string(),
-                        parameters: ctor
-                            .params
-                            .iter()
-                            .filter_map(|param| match param {
-                                ParamOrTsParamProp::Param(p) => {
-                                    Some(self.extract_parameter_info(&p.pat))
-                                }
-                                _ => None,
-                            })
-                            .collect(),
+                        parameters,
                         return_type: None,
+                        type_params: Vec::new(),
                         is_async: false,
                         is_generator: false,
                         doc_comment: None,
+                        doc_tags: None,
+                        defined_in: None,
+                        source: SymbolSource::Package,
                     };
+                    self.document_function(&mut func_info, ctor.span.lo);
                     constructor = Some(func_info);
                 }
                 ClassMember::Method(method) => {
-                    if let PropName::Ident(ident) = &method.key {
-                        let func_info = self.extract_function_info(&method.function, &ident.sym);
-                        methods.push(func_info);
-                    }
+                    let method_name = self.printer.print_prop_name(&method.key);
+                    let mut func_info = self.extract_function_info(&method.function, &method_name);
+                    self.document_function(&mut func_info, method.span.lo);
+                    methods.push(func_info);
                 }
                 ClassMember::ClassProp(prop) => {
-                    if let PropName::Ident(ident) = &prop.key {
-                        let prop_info = PropertyInfo {
-                            name: ident.sym.to_string(),
-                            property_type: prop.type_ann.as_ref().map(|_| "unknown".to_string()),
-                            is_readonly: prop.readonly,
-                            is_static: prop.is_static,
-                            doc_comment: None,
-                        };
-                        properties.push(prop_info);
-                    }
+                    let (doc_comment, doc_tags) = self.doc_at(prop.span.lo);
+                    let prop_info = PropertyInfo {
+                        name: self.printer.print_prop_name(&prop.key),
+                        property_type: prop
+                            .type_ann
+                            .as_ref()
+                            .map(|ann| self.printer.print_type_ann(ann)),
+                        is_readonly: prop.readonly,
+                        is_static: prop.is_static,
+                        doc_comment,
+                        doc_tags,
+                    };
+                    properties.push(prop_info);
                 }
                 _ => {}
             }
         }
 
+        let extends = class.super_class.as_ref().map(|super_class| {
+            let type_args = class
+                .super_type_params
+                .as_ref()
+                .map(|args| self.printer.print_type_args(args))
+                .unwrap_or_default();
+            format!("{}{}", self.printer.print_expr(super_class), type_args)
+        });
+
         ClassInfo {
             name: name.to_string(),
             constructor,
             methods,
             properties,
-            extends: class.super_class.as_ref().map(|_| "unknown".to_string()), // TODO: extract actual parent
-            implements: Vec::new(), // TODO: extract implements
+            extends,
+            implements: class
+                .implements
+                .iter()
+                .map(|i| self.printer.print_expr_with_type_args(i))
+                .collect(),
+            type_params: self.extract_type_params(class.type_params.as_deref()),
             doc_comment: None,
+            doc_tags: None,
+            defined_in: None,
+            source: SymbolSource::Package,
         }
     }
 
@@ -324,49 +846,56 @@ impl AstParser {
         match pat {
             Pat::Ident(ident) => Parameter {
                 name: ident.id.sym.to_string(),
-                param_type: ident.type_ann.as_ref().map(|_| "unknown".to_string()),
+                param_type: ident
+                    .type_ann
+                    .as_ref()
+                    .map(|ann| self.printer.print_type_ann(ann)),
                 is_optional: ident.optional,
                 is_rest: false,
                 default_value: None,
             },
             Pat::Rest(rest) => {
-                if let Pat::Ident(ident) = &*rest.arg {
-                    Parameter {
-                        name: ident.id.sym.to_string(),
-                        param_type: ident.type_ann.as_ref().map(|_| "unknown".to_string()),
-                        is_optional: false,
-                        is_rest: true,
-                        default_value: None,
-                    }
-                } else {
-                    Parameter {
-                        name: "...unknown".to_string(),
-                        param_type: None,
-                        is_optional: false,
-                        is_rest: true,
-                        default_value: None,
-                    }
+                // The annotation may sit on the rest pattern or on its argument
+                let type_ann = rest.type_ann.as_ref().or(match &*rest.arg {
+                    Pat::Ident(ident) => ident.type_ann.as_ref(),
+                    _ => None,
+                });
+                Parameter {
+                    name: self.printer.print_pat_name(&rest.arg),
+                    param_type: type_ann.map(|ann| self.printer.print_type_ann(ann)),
+                    is_optional: false,
+                    is_rest: true,
+                    default_value: None,
                 }
             }
             Pat::Assign(assign) => {
-                if let Pat::Ident(ident) = &*assign.left {
-                    Parameter {
-                        name: ident.id.sym.to_string(),
-                        param_type: ident.type_ann.as_ref().map(|_| "unknown".to_string()),
-                        is_optional: true,
-                        is_rest: false,
-                        default_value: Some("default".to_string()), // TODO: extract actual default
-                    }
-                } else {
-                    Parameter {
-                        name: "unknown".to_string(),
-                        param_type: None,
-                        is_optional: true,
-                        is_rest: false,
-                        default_value: Some("default".to_string()),
-                    }
+                let inner = self.extract_parameter_info(&assign.left);
+                Parameter {
+                    is_optional: true,
+                    default_value: Some(self.printer.print_expr(&assign.right)),
+                    ..inner
                 }
             }
+            Pat::Object(object) => Parameter {
+                name: self.printer.print_pat_name(pat),
+                param_type: object
+                    .type_ann
+                    .as_ref()
+                    .map(|ann| self.printer.print_type_ann(ann)),
+                is_optional: object.optional,
+                is_rest: false,
+                default_value: None,
+            },
+            Pat::Array(array) => Parameter {
+                name: self.printer.print_pat_name(pat),
+                param_type: array
+                    .type_ann
+                    .as_ref()
+                    .map(|ann| self.printer.print_type_ann(ann)),
+                is_optional: array.optional,
+                is_rest: false,
+                default_value: None,
+            },
             _ => Parameter {
                 name: "unknown".to_string(),
                 param_type: None,
@@ -377,6 +906,91 @@ impl AstParser {
         }
     }
 
+    /// The JSDoc block directly preceding the node that starts at `pos`
+    fn doc_at(&self, pos: BytePos) -> (Option<String>, Option<DocTags>) {
+        self.comments
+            .get_leading(pos)
+            .and_then(|comments| {
+                comments.into_iter().rev().find(|comment| {
+                    comment.kind == CommentKind::Block && JsDocParser::is_doc_comment(&comment.text)
+                })
+            })
+            .map(|comment| self.jsdoc.parse(&comment.text))
+            .unwrap_or_default()
+    }
+
+    /// Attach the doc comment at `pos` and use its tags to type untyped parameters
+    fn document_function(&self, func: &mut FunctionInfo, pos: BytePos) {
+        (func.doc_comment, func.doc_tags) = self.doc_at(pos);
+        self.jsdoc.apply_types(func);
+    }
+
+    fn extract_type_params(&self, decl: Option<&TsTypeParamDecl>) -> Vec<String> {
+        decl.map(|d| self.printer.print_type_params(d))
+            .unwrap_or_default()
+    }
+
+    fn type_params_suffix(&self, decl: Option<&TsTypeParamDecl>) -> String {
+        let params = self.extract_type_params(decl);
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", params.join(", "))
+        }
+    }
+
+    fn interface_definition(&self, decl: &TsInterfaceDecl) -> String {
+        let extends = if decl.extends.is_empty() {
+            String::new()
+        } else {
+            let parents: Vec<String> = decl
+                .extends
+                .iter()
+                .map(|e| self.printer.print_expr_with_type_args(e))
+                .collect();
+            format!(" extends {}", parents.join(", "))
+        };
+        format!(
+            "interface {}{}{} {}",
+            decl.id.sym,
+            self.type_params_suffix(decl.type_params.as_deref()),
+            extends,
+            self.printer.print_type_members(&decl.body.body)
+        )
+    }
+
+    fn type_alias_definition(&self, decl: &TsTypeAliasDecl) -> String {
+        format!(
+            "type {}{} = {}",
+            decl.id.sym,
+            self.type_params_suffix(decl.type_params.as_deref()),
+            self.printer.print_type(&decl.type_ann)
+        )
+    }
+
+    fn enum_definition(&self, decl: &TsEnumDecl) -> String {
+        let members: Vec<String> = decl
+            .members
+            .iter()
+            .map(|member| {
+                let name = match &member.id {
+                    TsEnumMemberId::Ident(ident) => ident.sym.to_string(),
+                    TsEnumMemberId::Str(s) => format!("'{}'", s.value),
+                };
+                match &member.init {
+                    Some(init) => format!("{} = {}", name, self.printer.print_expr(init)),
+                    None => name,
+                }
+            })
+            .collect();
+        format!(
+            "{}enum {} {{ {} }}",
+            if decl.is_const { "const " } else { "" },
+            decl.id.sym,
+            members.join(", ")
+        )
+    }
+
     #[allow(dead_code)]
     fn ident_to_string(&self, ident: &Ident) -> String {
         ident.sym.to_string()
@@ -389,3 +1003,444 @@ impl AstParser {
         }
     }
 }
+
+/// What the left-hand side of a CommonJS assignment exports
+enum CommonJsTarget {
+    /// `module.exports`
+    Module,
+    /// `exports.foo`, `module.exports.foo`, `exports["foo"]`
+    Named(String),
+    /// The `exports` binding itself
+    ExportsAlias,
+}
+
+fn commonjs_export_target(target: &SimpleAssignTarget) -> Option<CommonJsTarget> {
+    match target {
+        SimpleAssignTarget::Ident(ident) if &*ident.id.sym == "exports" => {
+            Some(CommonJsTarget::ExportsAlias)
+        }
+        SimpleAssignTarget::Member(member) => {
+            if is_module_exports(member) {
+                return Some(CommonJsTarget::Module);
+            }
+            let is_exports_object = is_ident(&member.obj, "exports")
+                || matches!(&*member.obj, Expr::Member(inner) if is_module_exports(inner));
+            if !is_exports_object {
+                return None;
+            }
+            match &member.prop {
+                MemberProp::Ident(ident) => Some(CommonJsTarget::Named(ident.sym.to_string())),
+                MemberProp::Computed(computed) => match &*computed.expr {
+                    Expr::Lit(Lit::Str(s)) => Some(CommonJsTarget::Named(s.value.to_string())),
+                    _ => None,
+                },
+                MemberProp::PrivateName(_) => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_module_exports(member: &MemberExpr) -> bool {
+    is_ident(&member.obj, "module")
+        && matches!(&member.prop, MemberProp::Ident(prop) if &*prop.sym == "exports")
+}
+
+fn is_ident(expr: &Expr, name: &str) -> bool {
+    matches!(expr, Expr::Ident(ident) if &*ident.sym == name)
+}
+
+/// `Object.defineProperty(exports, "name", { get: function () { return local.member; } })`
+/// as `(name, local, member)`
+fn reexport_getter(expr: &Expr) -> Option<(String, String, String)> {
+    let Expr::Call(call) = expr else {
+        return None;
+    };
+    let Callee::Expr(callee) = &call.callee else {
+        return None;
+    };
+    let Expr::Member(callee) = &**callee else {
+        return None;
+    };
+    let is_define_property = is_ident(&callee.obj, "Object")
+        && matches!(&callee.prop, MemberProp::Ident(prop) if &*prop.sym == "defineProperty");
+    let [target, name, descriptor] = call.args.as_slice() else {
+        return None;
+    };
+    if !is_define_property || !is_ident(&target.expr, "exports") {
+        return None;
+    }
+    let Expr::Lit(Lit::Str(name)) = &*name.expr else {
+        return None;
+    };
+    let Expr::Object(descriptor) = &*descriptor.expr else {
+        return None;
+    };
+
+    let getter_body = descriptor.props.iter().find_map(|prop| match prop {
+        PropOrSpread::Prop(prop) => match &**prop {
+            Prop::KeyValue(kv) if matches!(&kv.key, PropName::Ident(key) if &*key.sym == "get") => {
+                match &*kv.value {
+                    Expr::Fn(fn_expr) => fn_expr.function.body.as_ref(),
+                    _ => None,
+                }
+            }
+            Prop::Method(method) if matches!(&method.key, PropName::Ident(key) if &*key.sym == "get") => {
+                method.function.body.as_ref()
+            }
+            _ => None,
+        },
+        PropOrSpread::Spread(_) => None,
+    })?;
+
+    let [Stmt::Return(ReturnStmt {
+        arg: Some(returned),
+        ..
+    })] = getter_body.stmts.as_slice()
+    else {
+        return None;
+    };
+    let Expr::Member(member) = &**returned else {
+        return None;
+    };
+    let (Expr::Ident(local), MemberProp::Ident(prop)) = (&*member.obj, &member.prop) else {
+        return None;
+    };
+    Some((
+        name.value.to_string(),
+        local.sym.to_string(),
+        prop.sym.to_string(),
+    ))
+}
+
+/// `(exported, local)` pairs for CommonJS exports whose value is a plain
+/// identifier: `module.exports = { a, b: c }` and `exports.a = c`
+fn commonjs_exported_locals(expr: &Expr) -> Vec<(String, String)> {
+    let Expr::Assign(assign) = expr else {
+        return Vec::new();
+    };
+    let AssignTarget::Simple(target) = &assign.left else {
+        return Vec::new();
+    };
+    match (
+        commonjs_export_target(target),
+        innermost_value(&assign.right),
+    ) {
+        (Some(CommonJsTarget::Module), Expr::Object(object)) => object
+            .props
+            .iter()
+            .filter_map(|prop| match prop {
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::Shorthand(ident) => Some((ident.sym.to_string(), ident.sym.to_string())),
+                    Prop::KeyValue(kv) => match (&kv.key, &*kv.value) {
+                        (PropName::Ident(key), Expr::Ident(value)) => {
+                            Some((key.sym.to_string(), value.sym.to_string()))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                PropOrSpread::Spread(_) => None,
+            })
+            .collect(),
+        (Some(CommonJsTarget::Named(name)), Expr::Ident(value)) => {
+            vec![(name, value.sym.to_string())]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The value at the end of an assignment chain (`a = b = value`)
+fn innermost_value(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Assign(assign) => innermost_value(&assign.right),
+        _ => expr,
+    }
+}
+
+/// The specifier of `require("x")`, also when wrapped by an interop helper
+/// such as `__importStar(require("x"))`
+fn require_source(expr: &Expr) -> Option<String> {
+    let Expr::Call(call) = expr else {
+        return None;
+    };
+    let first_arg = call.args.first().map(|arg| &*arg.expr);
+    match &call.callee {
+        Callee::Expr(callee) if is_ident(callee, "require") => match first_arg {
+            Some(Expr::Lit(Lit::Str(s))) => Some(s.value.to_string()),
+            _ => None,
+        },
+        Callee::Expr(_) => first_arg.and_then(require_source),
+        _ => None,
+    }
+}
+
+/// The index of the statement that added each export and symbol, so that
+/// statements processed out of order still list their symbols in source order
+#[derive(Default)]
+struct SourcePositions {
+    exports: Vec<usize>,
+    functions: Vec<usize>,
+    classes: Vec<usize>,
+    constants: Vec<usize>,
+}
+
+impl SourcePositions {
+    /// Attribute everything added since the last call to statement `index`
+    fn record(&mut self, index: usize, module_info: &NodeModuleInfo) {
+        self.exports.resize(module_info.exports.len(), index);
+        self.functions.resize(module_info.functions.len(), index);
+        self.classes.resize(module_info.classes.len(), index);
+        self.constants.resize(module_info.constants.len(), index);
+    }
+
+    fn restore(self, module_info: &mut NodeModuleInfo) {
+        reorder(&mut module_info.exports, self.exports);
+        reorder(&mut module_info.functions, self.functions);
+        reorder(&mut module_info.classes, self.classes);
+        reorder(&mut module_info.constants, self.constants);
+    }
+}
+
+/// Stable-sort `items` by their positions
+fn reorder<T>(items: &mut Vec<T>, positions: Vec<usize>) {
+    let mut positioned: Vec<(usize, T)> = positions.into_iter().zip(items.drain(..)).collect();
+    positioned.sort_by_key(|(position, _)| *position);
+    items.extend(positioned.into_iter().map(|(_, item)| item));
+}
+
+fn push_export(module_info: &mut NodeModuleInfo, name: String) {
+    if !module_info.exports.contains(&name) {
+        module_info.exports.push(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commonjs_source_order() {
+        let module = AstParser::new()
+            .parse_content(
+                "function parse(text) {}\n\
+                 exports.parse = parse;\n\
+                 exports.format = function (value) {};\n\
+                 exports.VERSION = '1.0.0';\n\
+                 function stringify(value) {}\n\
+                 const LIMIT = 10;\n\
+                 exports.stringify = stringify;\n\
+                 exports.toJSON = stringify;\n\
+                 class Schema {}\n\
+                 exports.Schema = Schema;\n",
+                "cjs",
+            )
+            .unwrap();
+        let functions: Vec<&str> = module.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(functions, ["parse", "format", "stringify", "toJSON"]);
+        let constants: Vec<&str> = module.constants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(constants, ["VERSION", "LIMIT"]);
+        assert_eq!(
+            module.exports,
+            ["parse", "format", "VERSION", "stringify", "toJSON", "Schema"]
+        );
+    }
+
+    #[test]
+    fn test_function_types() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "export function pick<T extends object, K extends keyof T>(obj: T, ...keys: K[]): Pick<T, K> { return obj; }",
+                "test",
+            )
+            .unwrap();
+        let func = &module.functions[0];
+        assert_eq!(func.return_type.as_deref(), Some("Pick<T, K>"));
+        assert_eq!(
+            func.type_params,
+            vec!["T extends object", "K extends keyof T"]
+        );
+        assert_eq!(func.parameters[0].param_type.as_deref(), Some("T"));
+        assert!(func.parameters[1].is_rest);
+        assert_eq!(func.parameters[1].param_type.as_deref(), Some("K[]"));
+    }
+
+    #[test]
+    fn test_complex_parameter_types() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "export const listen = (port: number | string = 3000, cb?: (err: Error | null) => void): Promise<void> => Promise.resolve();",
+                "test",
+            )
+            .unwrap();
+        let func = &module.functions[0];
+        assert_eq!(
+            func.parameters[0].param_type.as_deref(),
+            Some("number | string")
+        );
+        assert_eq!(func.parameters[0].default_value.as_deref(), Some("3000"));
+        assert_eq!(
+            func.parameters[1].param_type.as_deref(),
+            Some("(err: Error | null) => void")
+        );
+        assert_eq!(func.return_type.as_deref(), Some("Promise<void>"));
+    }
+
+    #[test]
+    fn test_class_heritage() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "export class Store<S = {}> extends events.EventEmitter<Events> implements Disposable, Iterable<S> {}",
+                "test",
+            )
+            .unwrap();
+        let class = &module.classes[0];
+        assert_eq!(
+            class.extends.as_deref(),
+            Some("events.EventEmitter<Events>")
+        );
+        assert_eq!(class.implements, vec!["Disposable", "Iterable<S>"]);
+        assert_eq!(class.type_params, vec!["S = {}"]);
+    }
+
+    #[test]
+    fn test_jsdoc_types_untyped_parameters() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "/**\n * Join path segments.\n * @param {string} base The base path\n * @param {...string} [parts] More segments\n * @returns {string}\n */\nexport function join(base, parts) {}\n\n// unrelated\nfunction plain(a) {}",
+                "test",
+            )
+            .unwrap();
+        let func = &module.functions[0];
+        assert_eq!(func.doc_comment.as_deref(), Some("Join path segments."));
+        assert_eq!(func.parameters[0].param_type.as_deref(), Some("string"));
+        assert!(func.parameters[1].is_optional);
+        assert_eq!(func.return_type.as_deref(), Some("string"));
+        assert!(module.functions[1].doc_comment.is_none());
+    }
+
+    #[test]
+    fn test_doc_comments_on_members() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "/** A store */\nexport class Store {\n  /** Current size */\n  size: number = 0;\n  /**\n   * Read a key.\n   * @deprecated Use get\n   */\n  read(key: string): string { return key; }\n}\n/** Max entries */\nexport const MAX: number = 10;\n/** Options */\ninterface Options {}",
+                "test",
+            )
+            .unwrap();
+        let class = &module.classes[0];
+        assert_eq!(class.doc_comment.as_deref(), Some("A store"));
+        assert_eq!(
+            class.properties[0].doc_comment.as_deref(),
+            Some("Current size")
+        );
+        let tags = class.methods[0].doc_tags.as_ref().unwrap();
+        assert_eq!(tags.deprecated.as_deref(), Some("Use get"));
+        assert_eq!(
+            module.constants[0].doc_comment.as_deref(),
+            Some("Max entries")
+        );
+        assert_eq!(module.types[0].doc_comment.as_deref(), Some("Options"));
+    }
+
+    #[test]
+    fn test_commonjs_module_exports() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "module.exports = deprecate;\n/** Wrap a function */\nfunction deprecate(fn, msg) {}\nmodule.exports.Router = class Router { route(path) {} };\nexports.json = function json(options) {};\nexports.VERSION = '1.0';",
+                "test",
+            )
+            .unwrap();
+        assert_eq!(
+            module.exports,
+            vec!["deprecate", "Router", "json", "VERSION"]
+        );
+        assert_eq!(
+            module.functions[0].doc_comment.as_deref(),
+            Some("Wrap a function")
+        );
+        assert_eq!(module.classes[0].name, "Router");
+        assert_eq!(module.functions[1].name, "json");
+        assert_eq!(module.constants[0].name, "VERSION");
+    }
+
+    #[test]
+    fn test_commonjs_object_literal() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "const helper = () => 1;\nmodule.exports = { helper, parse: (input) => input, format(value, opts) {}, ...require('./extra') };",
+                "test",
+            )
+            .unwrap();
+        assert_eq!(module.exports, vec!["helper", "parse", "format"]);
+        assert_eq!(module.functions.len(), 3);
+        assert_eq!(module.functions[2].parameters.len(), 2);
+        assert_eq!(module.star_exports, vec!["./extra"]);
+    }
+
+    #[test]
+    fn test_compiled_typescript_commonjs() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "\"use strict\";\nObject.defineProperty(exports, \"__esModule\", { value: true });\nexports.parse = exports.Lexer = void 0;\nconst tslib_1 = require(\"tslib\");\ntslib_1.__exportStar(require(\"./types\"), exports);\n__export(require(\"./legacy\"));\nObject.defineProperty(exports, \"tokenize\", { enumerable: true, get: function () { return lexer_1.tokenize; } });\nclass Lexer {}\nexports.Lexer = Lexer;\nfunction parse(input) {}\nexports.parse = parse;",
+                "test",
+            )
+            .unwrap();
+        assert_eq!(module.exports, vec!["Lexer", "parse", "tokenize"]);
+        assert!(module.constants.is_empty());
+        assert_eq!(module.star_exports, vec!["./types", "./legacy"]);
+    }
+
+    #[test]
+    fn test_reexport_declarations() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "export * from './all';\nexport * as ns from './ns';\nexport { a as b, default as c } from './a';\nimport { d } from './d';\nexport { d };\nconst e = require('./e');\nmodule.exports.e = e;",
+                "test",
+            )
+            .unwrap();
+        assert_eq!(module.star_exports, vec!["./all"]);
+        let reexports: Vec<_> = module
+            .reexports
+            .iter()
+            .map(|r| (r.source.as_str(), r.imported.as_str(), r.exported.as_str()))
+            .collect();
+        assert_eq!(
+            reexports,
+            vec![
+                ("./ns", "*", "ns"),
+                ("./a", "a", "b"),
+                ("./a", "default", "c"),
+                ("./d", "d", "d"),
+                ("./e", "*", "e"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_definitions() {
+        let parser = AstParser::new();
+        let module = parser
+            .parse_content(
+                "export type Handler<T> = { [K in keyof T]?: T[K][] } | null;\nexport interface Options extends Base { readonly name: string; size?: number }",
+                "test",
+            )
+            .unwrap();
+        assert_eq!(
+            module.types[0].definition,
+            "type Handler<T> = { [K in keyof T]?: T[K][] } | null"
+        );
+        assert_eq!(
+            module.types[1].definition,
+            "interface Options extends Base { readonly name: string; size?: number }"
+        );
+    }
+}
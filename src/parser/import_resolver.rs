@@ -1,4 +1,4 @@
-use crate::module_info::{NodeModuleInfo, SignatureInfo, SignatureKind, Parameter};
+use crate::module_info::{NodeModuleInfo, SignatureInfo, SignatureKind, SymbolSource};
 use crate::parser::ast_parser::AstParser;
 use anyhow::{anyhow, Result};
 use std::env;
@@ -26,6 +26,12 @@ pub struct ImportChainResolver {
     parser: AstParser,
 }
 
+impl Default for ImportChainResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ImportChainResolver {
     pub fn new() -> Self {
         Self {
@@ -88,11 +94,6 @@ impl ImportChainResolver {
                     }
                 }
             }
-
-            // Try smart signatures for known patterns
-            if let Some(sig) = self.try_smart_signatures(module_path, symbol_name) {
-                return Some(sig);
-            }
         }
 
         None
@@ -204,7 +205,7 @@ impl ImportChainResolver {
                 };
 
                 let resolved = current_dir.join(from_module.trim_start_matches("./"));
-                if let Some(resolved_str) = resolved.strip_prefix(package_path).ok() {
+                if let Ok(resolved_str) = resolved.strip_prefix(package_path) {
                     return Some(resolved_str.to_string_lossy().replace('/', "."));
                 }
             } else {
@@ -217,52 +218,27 @@ impl ImportChainResolver {
     }
 
     /// Find symbol in a module's exports
-    fn find_symbol_in_module(&self, module_info: &NodeModuleInfo, symbol_name: &str) -> Option<SignatureInfo> {
+    pub(crate) fn find_symbol_in_module(&self, module_info: &NodeModuleInfo, symbol_name: &str) -> Option<SignatureInfo> {
         // Check functions
         for function in &module_info.functions {
             if function.name == symbol_name {
-                return Some(SignatureInfo {
-                    name: function.name.clone(),
-                    kind: SignatureKind::Function,
-                    parameters: function.parameters.clone(),
-                    return_type: function.return_type.clone(),
-                    doc_comment: function.doc_comment.clone(),
-                });
+                return Some(function.to_signature(function.name.clone(), SignatureKind::Function));
             }
         }
 
         // Check classes
         for class in &module_info.classes {
             if class.name == symbol_name {
-                if let Some(constructor) = &class.constructor {
-                    return Some(SignatureInfo {
-                        name: class.name.clone(),
-                        kind: SignatureKind::Constructor,
-                        parameters: constructor.parameters.clone(),
-                        return_type: Some(class.name.clone()),
-                        doc_comment: class.doc_comment.clone(),
-                    });
-                } else {
-                    return Some(SignatureInfo {
-                        name: class.name.clone(),
-                        kind: SignatureKind::Constructor,
-                        parameters: Vec::new(),
-                        return_type: Some(class.name.clone()),
-                        doc_comment: class.doc_comment.clone(),
-                    });
-                }
+                return Some(class.constructor_signature());
             }
 
             // Check class methods
             for method in &class.methods {
                 if method.name == symbol_name {
-                    return Some(SignatureInfo {
-                        name: format!("{}.{}", class.name, method.name),
-                        kind: SignatureKind::Method,
-                        parameters: method.parameters.clone(),
-                        return_type: method.return_type.clone(),
-                        doc_comment: method.doc_comment.clone(),
-                    });
+                    return Some(method.to_signature(
+                        format!("{}.{}", class.name, method.name),
+                        SignatureKind::Method,
+                    ));
                 }
             }
         }
@@ -275,7 +251,10 @@ impl ImportChainResolver {
                     kind: SignatureKind::Function, // Treat as function for now
                     parameters: Vec::new(),
                     return_type: constant.value_type.clone(),
+                    type_params: Vec::new(),
                     doc_comment: constant.doc_comment.clone(),
+                    doc_tags: constant.doc_tags.clone(),
+                    source: SymbolSource::Package,
                 });
             }
         }
@@ -331,99 +310,4 @@ impl ImportChainResolver {
 
         None
     }
-
-    /// Generate smart signatures for known patterns
-    fn try_smart_signatures(&self, module_path: &str, symbol_name: &str) -> Option<SignatureInfo> {
-        debug_log!("Trying smart signatures for {}:{}", module_path, symbol_name);
-
-        // Express patterns
-        if module_path == "express" {
-            match symbol_name {
-                "Router" => {
-                    return Some(SignatureInfo {
-                        name: "Router".to_string(),
-                        kind: SignatureKind::Constructor,
-                        parameters: vec![],
-                        return_type: Some("Router".to_string()),
-                        doc_comment: Some("Express router constructor".to_string()),
-                    });
-                }
-                "Express" => {
-                    return Some(SignatureInfo {
-                        name: "Express".to_string(),
-                        kind: SignatureKind::Function,
-                        parameters: vec![],
-                        return_type: Some("Application".to_string()),
-                        doc_comment: Some("Express application factory".to_string()),
-                    });
-                }
-                _ => {}
-            }
-        }
-
-        // React patterns
-        if module_path == "react" {
-            match symbol_name {
-                "useState" => {
-                    return Some(SignatureInfo {
-                        name: "useState".to_string(),
-                        kind: SignatureKind::Function,
-                        parameters: vec![Parameter {
-                            name: "initialState".to_string(),
-                            param_type: Some("T".to_string()),
-                            is_optional: false,
-                            is_rest: false,
-                            default_value: None,
-                        }],
-                        return_type: Some("[T, Dispatch<SetStateAction<T>>]".to_string()),
-                        doc_comment: Some("React state hook".to_string()),
-                    });
-                }
-                "useEffect" => {
-                    return Some(SignatureInfo {
-                        name: "useEffect".to_string(),
-                        kind: SignatureKind::Function,
-                        parameters: vec![
-                            Parameter {
-                                name: "effect".to_string(),
-                                param_type: Some("EffectCallback".to_string()),
-                                is_optional: false,
-                                is_rest: false,
-                                default_value: None,
-                            },
-                            Parameter {
-                                name: "deps".to_string(),
-                                param_type: Some("DependencyList".to_string()),
-                                is_optional: true,
-                                is_rest: false,
-                                default_value: None,
-                            },
-                        ],
-                        return_type: Some("void".to_string()),
-                        doc_comment: Some("React effect hook".to_string()),
-                    });
-                }
-                _ => {}
-            }
-        }
-
-        // Lodash patterns
-        if module_path.starts_with("lodash") {
-            return Some(SignatureInfo {
-                name: symbol_name.to_string(),
-                kind: SignatureKind::Function,
-                parameters: vec![Parameter {
-                    name: "args".to_string(),
-                    param_type: Some("any[]".to_string()),
-                    is_optional: false,
-                    is_rest: true,
-                    default_value: None,
-                }],
-                return_type: Some("any".to_string()),
-                doc_comment: Some(format!("Lodash {} function", symbol_name)),
-            });
-        }
-
-        None
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,304 @@
+use crate::module_info::{DocParam, DocReturn, DocTags, FunctionInfo};
+
+/// Parser for JSDoc/TSDoc block comments
+///
+/// Splits a `/** ... */` comment into its free-text description and the
+/// tags pretty-node understands: `@param`, `@returns`, `@deprecated` and
+/// `@example`. Other tags are ignored.
+pub struct JsDocParser;
+
+impl JsDocParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether a block comment's text (without `/*` and `*/`) is a doc comment
+    pub fn is_doc_comment(text: &str) -> bool {
+        text.starts_with('*') && !text.starts_with("**")
+    }
+
+    /// Parse the text of a doc comment (without `/*` and `*/`)
+    pub fn parse(&self, text: &str) -> (Option<String>, Option<DocTags>) {
+        let mut description = Vec::new();
+        let mut tags: Vec<(String, Vec<String>)> = Vec::new();
+
+        for line in text.lines().map(strip_line_prefix) {
+            let trimmed = line.trim_start();
+            if let Some(tag) = trimmed.strip_prefix('@') {
+                let (name, rest) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+                tags.push((name.to_string(), vec![rest.to_string()]));
+            } else if let Some((_, body)) = tags.last_mut() {
+                body.push(line.to_string());
+            } else {
+                description.push(line.trim());
+            }
+        }
+
+        let description = description.join("\n").trim().to_string();
+        let mut doc_tags = DocTags::default();
+
+        for (name, body) in tags {
+            match name.as_str() {
+                "param" | "arg" | "argument" => {
+                    if let Some(param) = parse_param(&join_text(&body)) {
+                        doc_tags.params.push(param);
+                    }
+                }
+                "returns" | "return" => {
+                    let text = join_text(&body);
+                    let (return_type, rest) = split_type(&text);
+                    doc_tags.returns = Some(DocReturn {
+                        return_type,
+                        description: non_empty(rest),
+                    });
+                }
+                "deprecated" => {
+                    doc_tags.deprecated = Some(join_text(&body));
+                }
+                "example" => {
+                    let example = body.join("\n").trim_matches('\n').to_string();
+                    doc_tags.examples.push(dedent(&example));
+                }
+                _ => {}
+            }
+        }
+
+        (
+            non_empty(&description),
+            (!doc_tags.is_empty()).then_some(doc_tags),
+        )
+    }
+
+    /// Fill in missing parameter and return types from `@param {T}` and
+    /// `@returns {T}` tags, which is how untyped JavaScript documents them
+    pub fn apply_types(&self, function: &mut FunctionInfo) {
+        let Some(tags) = &function.doc_tags else {
+            return;
+        };
+
+        for parameter in &mut function.parameters {
+            let Some(doc) = tags.params.iter().find(|p| p.name == parameter.name) else {
+                continue;
+            };
+            if parameter.param_type.is_none() {
+                parameter.param_type = doc.param_type.clone();
+            }
+            if doc.is_optional {
+                parameter.is_optional = true;
+            }
+            if parameter.default_value.is_none() {
+                parameter.default_value = doc.default_value.clone();
+            }
+        }
+
+        if function.return_type.is_none() {
+            function.return_type = tags
+                .returns
+                .as_ref()
+                .and_then(|returns| returns.return_type.clone());
+        }
+    }
+}
+
+impl Default for JsDocParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Remove the leading `*` gutter from a comment line, keeping indentation
+/// after it so `@example` code stays readable
+fn strip_line_prefix(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    match trimmed.strip_prefix('*') {
+        Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+        None => trimmed,
+    }
+    .trim_end()
+}
+
+fn join_text(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn dedent(text: &str) -> String {
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    text.lines()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split a leading `{type}` off a tag body; braces may nest (`{{a: string}}`)
+fn split_type(body: &str) -> (Option<String>, &str) {
+    let body = body.trim_start();
+    if !body.starts_with('{') {
+        return (None, body);
+    }
+
+    let mut depth = 0;
+    for (i, c) in body.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let type_text = body[1..i].trim();
+                    return (non_empty(type_text), body[i + 1..].trim_start());
+                }
+            }
+            _ => {}
+        }
+    }
+    (None, body)
+}
+
+/// Find the `]` closing an optional parameter; its default may hold
+/// brackets and strings of its own (`[names=['a]']]`)
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 1;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if let Some(open) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == open {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' | '`' => quote = Some(c),
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse `{type} name description`, `{type} [name=default] description`
+/// and the `name - description` form
+fn parse_param(body: &str) -> Option<DocParam> {
+    let (mut param_type, rest) = split_type(body);
+
+    let (name_part, description) = if let Some(bracketed) = rest.strip_prefix('[') {
+        let end = closing_bracket(bracketed)?;
+        (&bracketed[..end], &bracketed[end + 1..])
+    } else {
+        rest.split_once(char::is_whitespace).unwrap_or((rest, ""))
+    };
+    let is_bracketed = rest.starts_with('[');
+
+    let (name, default_value) = match name_part.split_once('=') {
+        Some((name, default)) => (name.trim(), non_empty(default)),
+        None => (name_part.trim(), None),
+    };
+    if name.is_empty() {
+        return None;
+    }
+
+    // Closure Compiler style optional types: `{string=}`
+    let mut is_optional = is_bracketed;
+    if let Some(stripped) = param_type.as_deref().and_then(|t| t.strip_suffix('=')) {
+        is_optional = true;
+        param_type = Some(stripped.to_string());
+    }
+
+    let description = description.trim_start();
+    let description = description.strip_prefix("- ").unwrap_or(description);
+
+    Some(DocParam {
+        name: name.to_string(),
+        param_type,
+        description: non_empty(description),
+        is_optional,
+        default_value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_description_and_tags() {
+        let parser = JsDocParser::new();
+        let (description, tags) = parser.parse(
+            "*\n * Create a server.\n * Listens lazily.\n *\n * @param {number|string} port - Port to bind\n * @param {{host: string}} [options={}] Extra options\n * @returns {Promise<Server>} The server\n * @deprecated Use `listen` instead\n * @example\n *   const s = await create(80);\n *   s.close();\n ",
+        );
+        assert_eq!(
+            description.as_deref(),
+            Some("Create a server.\nListens lazily.")
+        );
+
+        let tags = tags.unwrap();
+        assert_eq!(tags.params[0].name, "port");
+        assert_eq!(tags.params[0].param_type.as_deref(), Some("number|string"));
+        assert_eq!(tags.params[0].description.as_deref(), Some("Port to bind"));
+        assert_eq!(tags.params[1].param_type.as_deref(), Some("{host: string}"));
+        assert!(tags.params[1].is_optional);
+        assert_eq!(tags.params[1].default_value.as_deref(), Some("{}"));
+        let returns = tags.returns.unwrap();
+        assert_eq!(returns.return_type.as_deref(), Some("Promise<Server>"));
+        assert_eq!(returns.description.as_deref(), Some("The server"));
+        assert_eq!(tags.deprecated.as_deref(), Some("Use `listen` instead"));
+        assert_eq!(
+            tags.examples,
+            vec!["const s = await create(80);\ns.close();"]
+        );
+    }
+
+    #[test]
+    fn test_defaults_with_brackets() {
+        let parser = JsDocParser::new();
+        let (_, tags) = parser.parse(
+            "*\n * @param {string[]} [names=['a', ']']] The names\n * @param {Object} [opts={a: [1, [2]]}] - Options\n ",
+        );
+        let tags = tags.unwrap();
+        assert_eq!(tags.params[0].name, "names");
+        assert_eq!(tags.params[0].default_value.as_deref(), Some("['a', ']']"));
+        assert_eq!(tags.params[0].description.as_deref(), Some("The names"));
+        assert_eq!(tags.params[1].name, "opts");
+        assert_eq!(tags.params[1].default_value.as_deref(), Some("{a: [1, [2]]}"));
+        assert_eq!(tags.params[1].description.as_deref(), Some("Options"));
+    }
+
+    #[test]
+    fn test_description_only() {
+        let parser = JsDocParser::new();
+        let (description, tags) = parser.parse("* Just text ");
+        assert_eq!(description.as_deref(), Some("Just text"));
+        assert!(tags.is_none());
+    }
+
+    #[test]
+    fn test_is_doc_comment() {
+        assert!(JsDocParser::is_doc_comment("* doc "));
+        assert!(!JsDocParser::is_doc_comment(" plain "));
+        assert!(!JsDocParser::is_doc_comment("*****"));
+    }
+}
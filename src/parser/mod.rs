@@ -1,6 +1,8 @@
 pub mod ast_parser;
 pub mod import_resolver;
+pub mod jsdoc;
 pub mod parameter_parser;
 pub mod semantic_analyzer;
 pub mod signature;
+pub mod type_printer;
 pub mod typescript;
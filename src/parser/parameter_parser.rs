@@ -78,8 +78,7 @@ impl ParameterParser {
         let trimmed = param_str.trim();
         
         // Handle rest parameters (...args)
-        if trimmed.starts_with("...") {
-            let rest_param = &trimmed[3..];
+        if let Some(rest_param) = trimmed.strip_prefix("...") {
             let (name, param_type) = self.extract_name_and_type(rest_param);
             return Parameter {
                 name,
@@ -106,10 +105,9 @@ impl ParameterParser {
         }
         
         // Check for optional parameter (param? or param?: type)
-        let (name_type_part, is_optional) = if trimmed.ends_with('?') {
-            (&trimmed[..trimmed.len() - 1], true)
-        } else {
-            (trimmed, false)
+        let (name_type_part, is_optional) = match trimmed.strip_suffix('?') {
+            Some(stripped) => (stripped, true),
+            None => (trimmed, false),
         };
         
         let (mut name, param_type) = self.extract_name_and_type(name_type_part);
@@ -242,6 +240,12 @@ impl ParameterParser {
     }
 }
 
+impl Default for ParameterParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
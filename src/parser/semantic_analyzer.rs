@@ -1,9 +1,9 @@
-use crate::module_info::{NodeModuleInfo, FunctionInfo, ClassInfo, Parameter, PropertyInfo};
+use crate::module_info::{NodeModuleInfo, FunctionInfo, ClassInfo, Parameter, SymbolSource};
+use crate::parser::type_printer::TypePrinter;
 use anyhow::Result;
 use std::collections::HashMap;
 use std::env;
 use std::path::Path;
-use swc_common::{SourceMap, sync::Lrc};
 use swc_ecma_ast::*;
 use swc_ecma_parser::{Parser, StringInput, Syntax, TsConfig};
 use swc_ecma_visit::{Visit, VisitWith};
@@ -27,8 +27,16 @@ pub struct SemanticAnalyzer {
     classes: HashMap<String, ClassInfo>,
     /// Current class being analyzed
     current_class: Option<String>,
+    printer: TypePrinter,
 }
 
+impl Default for SemanticAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 enum ScopeContext {
     Module,
@@ -44,6 +52,7 @@ impl SemanticAnalyzer {
             signatures: HashMap::new(),
             classes: HashMap::new(),
             current_class: None,
+            printer: TypePrinter::new(),
         }
     }
 
@@ -52,12 +61,11 @@ impl SemanticAnalyzer {
         let source_code = std::fs::read_to_string(file_path)?;
         
         // Set up swc parser
-        let cm: Lrc<SourceMap> = Default::default();
-        let syntax = if file_path.extension().map_or(false, |ext| ext == "ts" || ext == "d.ts") {
+        let syntax = if file_path.extension().is_some_and(|ext| ext == "ts" || ext == "d.ts") {
             Syntax::Typescript(TsConfig {
                 tsx: false,
                 decorators: true,
-                dts: file_path.extension().map_or(false, |ext| ext == "d.ts"),
+                dts: file_path.extension().is_some_and(|ext| ext == "d.ts"),
                 no_early_errors: true,
                 disallow_ambiguous_jsx_like: false,
             })
@@ -117,14 +125,16 @@ impl SemanticAnalyzer {
     }
 
     /// Extract parameters from function/method
-    fn extract_parameters(&self, params: &[Param]) -> Vec<Parameter> {
-        params
-            .iter()
-            .filter_map(|param| match &param.pat {
+    fn extract_parameters<'a>(&self, pats: impl IntoIterator<Item = &'a Pat>) -> Vec<Parameter> {
+        pats.into_iter()
+            .filter_map(|pat| match pat {
                 Pat::Ident(ident) => Some(Parameter {
                     name: ident.id.sym.to_string(),
-                    param_type: None, // TODO: Extract type annotations
-                    is_optional: false,
+                    param_type: ident
+                        .type_ann
+                        .as_ref()
+                        .map(|ann| self.printer.print_type_ann(ann)),
+                    is_optional: ident.id.optional,
                     is_rest: false,
                     default_value: None,
                 }),
@@ -135,26 +145,40 @@ impl SemanticAnalyzer {
 
     /// Extract return type from function
     fn extract_return_type(&self, return_type: &Option<Box<TsTypeAnn>>) -> Option<String> {
-        return_type.as_ref().map(|_| "any".to_string()) // TODO: Parse actual types
+        return_type
+            .as_ref()
+            .map(|ann| self.printer.print_type_ann(ann))
+    }
+
+    /// Extract declared type parameters from function/class
+    fn extract_type_params(&self, type_params: &Option<Box<TsTypeParamDecl>>) -> Vec<String> {
+        type_params
+            .as_ref()
+            .map(|decl| self.printer.print_type_params(decl))
+            .unwrap_or_default()
     }
 }
 
 /// Custom visitor implementation for semantic analysis
 impl Visit for SemanticAnalyzer {
-    fn visit_function_decl(&mut self, func: &FunctionDecl) {
+    fn visit_fn_decl(&mut self, func: &FnDecl) {
         let function_name = func.ident.sym.to_string();
         debug_log!("Found function: {}", function_name);
 
-        let parameters = self.extract_parameters(&func.function.params);
+        let parameters = self.extract_parameters(func.function.params.iter().map(|p| &p.pat));
         let return_type = self.extract_return_type(&func.function.return_type);
 
         let function_info = FunctionInfo {
             name: function_name.clone(),
             parameters,
             return_type,
+            type_params: self.extract_type_params(&func.function.type_params),
             is_async: func.function.is_async,
             is_generator: func.function.is_generator,
             doc_comment: None, // TODO: Extract JSDoc comments
+            doc_tags: None,
+            defined_in: None,
+            source: SymbolSource::Package,
         };
 
         if let Some(class_name) = self.in_class_scope().cloned() {
@@ -186,7 +210,22 @@ impl Visit for SemanticAnalyzer {
             constructor: None, // Will be filled when we visit constructor
             methods: Vec::new(),
             properties: Vec::new(),
+            extends: class
+                .class
+                .super_class
+                .as_ref()
+                .map(|super_class| self.printer.print_expr(super_class)),
+            implements: class
+                .class
+                .implements
+                .iter()
+                .map(|i| self.printer.print_expr_with_type_args(i))
+                .collect(),
+            type_params: self.extract_type_params(&class.class.type_params),
             doc_comment: None, // TODO: Extract JSDoc comments
+            doc_tags: None,
+            defined_in: None,
+            source: SymbolSource::Package,
         };
 
         self.classes.insert(class_name.clone(), class_info);
@@ -207,16 +246,25 @@ impl Visit for SemanticAnalyzer {
         debug_log!("Found constructor");
 
         if let Some(class_name) = &self.current_class {
-            let parameters = self.extract_parameters(&constructor.params);
-            
+            let parameters = self.extract_parameters(constructor.params.iter().filter_map(
+                |param| match param {
+                    ParamOrTsParamProp::Param(p) => Some(&p.pat),
+                    ParamOrTsParamProp::TsParamProp(_) => None,
+                },
+            ));
+
             if let Some(class_info) = self.classes.get_mut(class_name) {
                 class_info.constructor = Some(FunctionInfo {
                     name: "constructor".to_string(),
                     parameters,
                     return_type: Some(class_name.clone()),
+                    type_params: Vec::new(),
                     is_async: false,
                     is_generator: false,
                     doc_comment: None,
+                    doc_tags: None,
+                    defined_in: None,
+                    source: SymbolSource::Package,
                 });
             }
         }
@@ -231,17 +279,21 @@ impl Visit for SemanticAnalyzer {
             debug_log!("Found class method: {}", method_name);
 
             if let Some(class_name) = &self.current_class {
-                let parameters = self.extract_parameters(&method.function.params);
+                let parameters =
+                    self.extract_parameters(method.function.params.iter().map(|p| &p.pat));
                 let return_type = self.extract_return_type(&method.function.return_type);
-                let is_static = method.is_static;
 
                 let method_info = FunctionInfo {
                     name: method_name.clone(),
                     parameters,
                     return_type,
+                    type_params: self.extract_type_params(&method.function.type_params),
                     is_async: method.function.is_async,
                     is_generator: method.function.is_generator,
                     doc_comment: None,
+                    doc_tags: None,
+                    defined_in: None,
+                    source: SymbolSource::Package,
                 };
 
                 if let Some(class_info) = self.classes.get_mut(class_name) {
@@ -260,7 +312,7 @@ impl Visit for SemanticAnalyzer {
         }
     }
 
-    fn visit_export_named_decl(&mut self, export: &NamedExport) {
+    fn visit_named_export(&mut self, export: &NamedExport) {
         debug_log!("Found named export");
         // TODO: Track exports for better symbol resolution
         export.visit_with(self);
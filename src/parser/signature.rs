@@ -1,46 +1,248 @@
-use crate::module_info::SignatureInfo;
+use crate::module_info::{SignatureInfo, SymbolSource};
 use crate::npm_client::NpmClient;
+use crate::overrides::SignatureOverrides;
+use crate::package_json::{active_conditions, PackageJson};
+use crate::package_layout::InstalledPackage;
 use crate::parser::ast_parser::AstParser;
 use crate::parser::import_resolver::ImportChainResolver;
-use crate::utils::{extract_base_package, parse_package_spec};
+use crate::parser::typescript::TypeScriptParser;
+use crate::utils::{
+    extract_base_package, is_dts_file, parse_package_source, parse_package_spec,
+    types_package_name, PackageSource,
+};
 use anyhow::{anyhow, Result};
-use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Extract signature information for a given import path
 pub async fn extract_signature(import_path: &str, quiet: bool) -> Result<SignatureInfo> {
-    let (module_path, symbol_name) = parse_import_path(import_path)?;
+    extract_signature_with_client(import_path, quiet, &NpmClient::new()).await
+}
 
-    // Try to find the package locally first
-    let npm_client = NpmClient::new();
-    let search_paths = vec![
-        env::current_dir()?,
-        env::current_dir()?.join(".."),
-        env::current_dir()?.join("../.."),
-    ];
+/// Extract signature information, downloading through the given client
+pub async fn extract_signature_with_client(
+    import_path: &str,
+    quiet: bool,
+    npm_client: &NpmClient,
+) -> Result<SignatureInfo> {
+    let (module_path, symbol_name) = parse_import_path(import_path)?;
 
+    // A path, tarball or git URL names the package itself rather than a module in it
+    let source = parse_package_source(&module_path)?;
+    let (module_path, signature, package) = match source {
+        PackageSource::Registry { .. } => {
+            let (signature, package) =
+                find_registry_signature(&module_path, &symbol_name, quiet, npm_client).await?;
+            (module_path, signature, package)
+        }
+        source => {
+            let package = npm_client.open_source(source, quiet).await?;
+            let name = PackageJson::read(&package.path().join("package.json"))
+                .ok()
+                .and_then(|package_json| package_json.name)
+                .unwrap_or(module_path);
+            let signature = extract_signature_from_local(package.path(), &name, &symbol_name);
+            (name, signature, package)
+        }
+    };
     let base_package = extract_base_package(&module_path);
+    let (package_name, _) = parse_package_spec(&base_package);
+    let facts = PackageFacts::read(package.path());
 
-    if let Some(local_path) = npm_client.find_local_package(&base_package, &search_paths) {
-        if let Ok(signature) = extract_signature_from_local(&local_path, &module_path, &symbol_name)
-        {
-            return Ok(signature);
+    // A curated signature replaces whatever could be parsed
+    let module = module_path.replacen(&base_package, &package_name, 1);
+    if let Some(curated) =
+        SignatureOverrides::load(quiet).lookup(&module, facts.version.as_deref(), &symbol_name)
+    {
+        return Ok(curated);
+    }
+
+    if signature.as_ref().is_ok_and(SignatureInfo::has_types) {
+        return signature;
+    }
+
+    // The package's own declarations come before DefinitelyTyped's
+    if facts.declares_types {
+        let subpath = module_subpath(&module_path, &base_package);
+        let Some(declared) = declared_signature(package.path(), &subpath, &symbol_name) else {
+            return signature;
+        };
+        return match signature {
+            Ok(mut signature) => {
+                signature.merge_own_declaration(declared);
+                Ok(signature)
+            }
+            Err(_) => Ok(declared),
+        };
+    }
+
+    // A JavaScript-only package: take the types from its DefinitelyTyped package
+    let Some(types_name) = types_package_name(&package_name) else {
+        return signature;
+    };
+    let Some(types) = npm_client
+        .find_types_package(&package_name, facts.version.as_deref(), quiet)
+        .await
+    else {
+        return signature;
+    };
+    let types_module_path = module_path.replacen(&base_package, &types_name, 1);
+    let declared = extract_signature_from_local(types.path(), &types_module_path, &symbol_name)
+        .ok()
+        .or_else(|| {
+            // `module.exports = make` and `export = lib` are the same function
+            // under different names
+            if facts.export_assignment.as_deref() != Some(symbol_name.as_str()) {
+                return None;
+            }
+            let declared_name = assigned_export(types.path())?;
+            let mut declared =
+                extract_signature_from_local(types.path(), &types_module_path, &declared_name)
+                    .ok()?;
+            declared.name = symbol_name.clone();
+            Some(declared)
+        });
+    let Some(mut declared) = declared else {
+        return signature;
+    };
+    match signature {
+        Ok(mut signature) => {
+            signature.merge_types(declared, &types_name);
+            Ok(signature)
+        }
+        Err(_) => {
+            declared.source = SymbolSource::TypesPackage(types_name);
+            Ok(declared)
         }
     }
+}
 
-    // Try to download and extract signature
+/// `express/lib/router` → `./lib/router`; the package itself is `.`
+fn module_subpath(module_path: &str, base_package: &str) -> String {
+    match module_path.strip_prefix(base_package) {
+        Some(rest) if !rest.is_empty() => format!(".{}", rest),
+        _ => ".".to_string(),
+    }
+}
+
+/// The signature of `symbol_name` in the declaration file a package ships
+/// for `subpath`. A symbol assigned to `module.exports` is looked up under
+/// the name `export =` gives it.
+fn declared_signature(
+    package_path: &Path,
+    subpath: &str,
+    symbol_name: &str,
+) -> Option<SignatureInfo> {
+    let package_json = PackageJson::read(&package_path.join("package.json")).unwrap_or_default();
+    let exported = package_json
+        .resolve_export_file(package_path, subpath, &["types".to_string()])
+        .filter(|file| is_dts_file(file));
+    let file = match exported {
+        Some(file) => file,
+        None if subpath == "." => package_json.types_entry(package_path).or_else(|| {
+            ["index.d.ts", "lib/index.d.ts", "types/index.d.ts", "dist/index.d.ts"]
+                .into_iter()
+                .map(|file| package_path.join(file))
+                .find(|path| path.is_file())
+        })?,
+        None => {
+            let base = package_path.join(subpath.trim_start_matches("./"));
+            [
+                PathBuf::from(format!("{}.d.ts", base.display())),
+                base.join("index.d.ts"),
+            ]
+            .into_iter()
+            .find(|path| path.is_file())?
+        }
+    };
+
+    let module = TypeScriptParser::new().parse_declaration_file(&file).ok()?;
+    if let Some(signature) = find_symbol_in_module(&module, symbol_name) {
+        return Some(signature);
+    }
+    let declared_name = module.export_assignment.as_deref()?;
+    let assigned = package_json
+        .main_entry(package_path, &active_conditions(&[]))
+        .filter(|entry| !is_dts_file(entry))
+        .and_then(|entry| AstParser::new().parse_file(&entry).ok()?.export_assignment);
+    if assigned.as_deref() != Some(symbol_name) {
+        return None;
+    }
+    let mut signature = find_symbol_in_module(&module, declared_name)?;
+    signature.name = symbol_name.to_string();
+    Some(signature)
+}
+
+/// What the `@types` fallback needs to know about the package a signature
+/// was looked up in
+struct PackageFacts {
+    declares_types: bool,
+    version: Option<String>,
+    /// The symbol its entry point assigns to `module.exports`
+    export_assignment: Option<String>,
+}
+
+impl PackageFacts {
+    fn read(package_path: &Path) -> Self {
+        let package_json =
+            PackageJson::read(&package_path.join("package.json")).unwrap_or_default();
+        Self {
+            declares_types: package_json.declares_types(package_path),
+            version: package_json.version,
+            export_assignment: assigned_export(package_path),
+        }
+    }
+}
+
+/// The symbol a package's entry point assigns to `module.exports` (or
+/// `export =`, in declarations)
+fn assigned_export(package_path: &Path) -> Option<String> {
+    let package_json = PackageJson::read(&package_path.join("package.json")).unwrap_or_default();
+    let entry = package_json
+        .types_entry(package_path)
+        .or_else(|| package_json.main_entry(package_path, &active_conditions(&[])))
+        .or_else(|| {
+            ["index.d.ts", "index.js"]
+                .into_iter()
+                .map(|file| package_path.join(file))
+                .find(|path| path.is_file())
+        })?;
+    let module = if is_dts_file(&entry) {
+        TypeScriptParser::new().parse_declaration_file(&entry)
+    } else {
+        AstParser::new().parse_file(&entry)
+    };
+    module.ok()?.export_assignment
+}
+
+/// Find a registry package's signature, preferring an installed copy
+async fn find_registry_signature(
+    module_path: &str,
+    symbol_name: &str,
+    quiet: bool,
+    npm_client: &NpmClient,
+) -> Result<(Result<SignatureInfo>, InstalledPackage)> {
+    let base_package = extract_base_package(module_path);
     let (package_name, version) = parse_package_spec(&base_package);
+
+    // Try to find the package locally first
+    if let Some(package) = npm_client.find_installed_package(&package_name, version.as_deref()) {
+        if let Ok(signature) = extract_signature_from_local(package.path(), module_path, symbol_name) {
+            return Ok((Ok(signature), package));
+        }
+    }
+
+    // Try to download and extract signature
     let package_info = npm_client
         .get_package_info(&package_name, version.as_deref())
         .await?;
-    let temp_dir = npm_client.download_package(&package_info, quiet).await?;
-
-    let package_path = temp_dir.path().join("package");
-    extract_signature_from_local(&package_path, &module_path, &symbol_name)
+    let package: InstalledPackage = npm_client.download_package(&package_info, quiet).await?.into();
+    let signature = extract_signature_from_local(package.path(), module_path, symbol_name);
+    Ok((signature, package))
 }
 
 fn parse_import_path(import_path: &str) -> Result<(String, String)> {
-    if let Some(colon_pos) = import_path.find(':') {
+    // Git URLs contain colons of their own; symbol names never do
+    if let Some(colon_pos) = import_path.rfind(':') {
         let module_path = import_path[..colon_pos].to_string();
         let symbol_name = import_path[colon_pos + 1..].to_string();
         Ok((module_path, symbol_name))
@@ -64,31 +266,24 @@ fn extract_signature_from_local(
         return Ok(signature);
     }
 
-    // Try to find the main entry point
-    let package_json_path = package_path.join("package.json");
-    let main_file = if package_json_path.exists() {
-        let package_json = std::fs::read_to_string(&package_json_path)?;
-        let package_data: serde_json::Value = serde_json::from_str(&package_json)?;
-
-        // Check for main, index, or types field
-        package_data
-            .get("main")
-            .or_else(|| package_data.get("types"))
-            .or_else(|| package_data.get("typings"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("index.js")
-            .to_string()
-    } else {
-        "index.js".to_string()
-    };
-
     // List of files to try parsing for signature discovery
     let mut files_to_try = vec![];
-    
-    // Add main entry point
-    let entry_path = package_path.join(&main_file);
-    if entry_path.exists() {
-        files_to_try.push(entry_path);
+
+    // Add the entry point for the imported (sub)path, honouring `exports`
+    let package_json_path = package_path.join("package.json");
+    if package_json_path.exists() {
+        let package_json = PackageJson::read(&package_json_path)?;
+        let conditions = active_conditions(&[]);
+        let subpath = module_subpath(module_path, &extract_base_package(module_path));
+
+        if let Some(file) = package_json.resolve_export_file(package_path, &subpath, &conditions) {
+            files_to_try.push(file);
+        } else if subpath == "." {
+            files_to_try.extend(package_json.main_entry(package_path, &conditions));
+            files_to_try.extend(package_json.types_entry(package_path));
+        }
+    } else if package_path.join("index.js").exists() {
+        files_to_try.push(package_path.join("index.js"));
     }
 
     // Add common entry points
@@ -173,13 +368,10 @@ fn find_symbol_in_module(module_info: &crate::module_info::NodeModuleInfo, symbo
     // Look for the symbol in functions
     for function in &module_info.functions {
         if function.name == symbol_name {
-            return Some(SignatureInfo {
-                name: function.name.clone(),
-                kind: crate::module_info::SignatureKind::Function,
-                parameters: function.parameters.clone(),
-                return_type: function.return_type.clone(),
-                doc_comment: function.doc_comment.clone(),
-            });
+            return Some(function.to_signature(
+                function.name.clone(),
+                crate::module_info::SignatureKind::Function,
+            ));
         }
     }
 
@@ -187,35 +379,16 @@ fn find_symbol_in_module(module_info: &crate::module_info::NodeModuleInfo, symbo
     for class in &module_info.classes {
         if class.name == symbol_name {
             // Return constructor signature for classes
-            if let Some(constructor) = &class.constructor {
-                return Some(SignatureInfo {
-                    name: class.name.clone(),
-                    kind: crate::module_info::SignatureKind::Constructor,
-                    parameters: constructor.parameters.clone(),
-                    return_type: Some(class.name.clone()),
-                    doc_comment: class.doc_comment.clone(),
-                });
-            } else {
-                return Some(SignatureInfo {
-                    name: class.name.clone(),
-                    kind: crate::module_info::SignatureKind::Constructor,
-                    parameters: Vec::new(),
-                    return_type: Some(class.name.clone()),
-                    doc_comment: class.doc_comment.clone(),
-                });
-            }
+            return Some(class.constructor_signature());
         }
 
         // Check class methods
         for method in &class.methods {
             if method.name == symbol_name {
-                return Some(SignatureInfo {
-                    name: format!("{}.{}", class.name, method.name),
-                    kind: crate::module_info::SignatureKind::Method,
-                    parameters: method.parameters.clone(),
-                    return_type: method.return_type.clone(),
-                    doc_comment: method.doc_comment.clone(),
-                });
+                return Some(method.to_signature(
+                    format!("{}.{}", class.name, method.name),
+                    crate::module_info::SignatureKind::Method,
+                ));
             }
         }
     }
@@ -228,7 +401,10 @@ fn find_symbol_in_module(module_info: &crate::module_info::NodeModuleInfo, symbo
                 kind: crate::module_info::SignatureKind::Function, // Treat as function for now
                 parameters: Vec::new(),
                 return_type: constant.value_type.clone(),
+                type_params: Vec::new(),
                 doc_comment: constant.doc_comment.clone(),
+                doc_tags: constant.doc_tags.clone(),
+                source: SymbolSource::Package,
             });
         }
     }
@@ -0,0 +1,880 @@
+use swc_ecma_ast::*;
+
+/// Render swc TypeScript type and expression nodes back to source text
+///
+/// The output is normalized (single spaces, `;`-separated members) rather than a
+/// byte-for-byte copy of the original source, which keeps signatures compact.
+pub struct TypePrinter;
+
+impl Default for TypePrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypePrinter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Print a type annotation without the leading colon
+    pub fn print_type_ann(&self, ann: &TsTypeAnn) -> String {
+        self.print_type(&ann.type_ann)
+    }
+
+    /// Print a TypeScript type
+    pub fn print_type(&self, ty: &TsType) -> String {
+        match ty {
+            TsType::TsKeywordType(keyword) => self.print_keyword(keyword.kind).to_string(),
+            TsType::TsThisType(_) => "this".to_string(),
+            TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(fn_type)) => format!(
+                "{}({}) => {}",
+                self.print_optional_type_params(fn_type.type_params.as_deref()),
+                self.print_fn_params(&fn_type.params),
+                self.print_type_ann(&fn_type.type_ann)
+            ),
+            TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsConstructorType(ctor)) => {
+                format!(
+                    "{}new {}({}) => {}",
+                    if ctor.is_abstract { "abstract " } else { "" },
+                    self.print_optional_type_params(ctor.type_params.as_deref()),
+                    self.print_fn_params(&ctor.params),
+                    self.print_type_ann(&ctor.type_ann)
+                )
+            }
+            TsType::TsTypeRef(type_ref) => format!(
+                "{}{}",
+                self.print_entity_name(&type_ref.type_name),
+                self.print_optional_type_args(type_ref.type_params.as_deref())
+            ),
+            TsType::TsTypeQuery(query) => {
+                let expr = match &query.expr_name {
+                    TsTypeQueryExpr::TsEntityName(name) => self.print_entity_name(name),
+                    TsTypeQueryExpr::Import(import) => self.print_import_type(import),
+                };
+                format!(
+                    "typeof {}{}",
+                    expr,
+                    self.print_optional_type_args(query.type_args.as_deref())
+                )
+            }
+            TsType::TsTypeLit(lit) => self.print_type_members(&lit.members),
+            TsType::TsArrayType(array) => {
+                format!("{}[]", self.print_type_wrapped(&array.elem_type))
+            }
+            TsType::TsTupleType(tuple) => {
+                let elems: Vec<String> = tuple
+                    .elem_types
+                    .iter()
+                    .map(|elem| match &elem.label {
+                        Some(label) => {
+                            format!("{}: {}", self.print_pat(label), self.print_type(&elem.ty))
+                        }
+                        None => self.print_type(&elem.ty),
+                    })
+                    .collect();
+                format!("[{}]", elems.join(", "))
+            }
+            TsType::TsOptionalType(optional) => {
+                format!("{}?", self.print_type_wrapped(&optional.type_ann))
+            }
+            TsType::TsRestType(rest) => format!("...{}", self.print_type(&rest.type_ann)),
+            TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(union)) => {
+                self.print_type_list(&union.types, " | ")
+            }
+            TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(
+                intersection,
+            )) => self.print_type_list(&intersection.types, " & "),
+            TsType::TsConditionalType(cond) => format!(
+                "{} extends {} ? {} : {}",
+                self.print_type_wrapped(&cond.check_type),
+                self.print_type(&cond.extends_type),
+                self.print_type(&cond.true_type),
+                self.print_type(&cond.false_type)
+            ),
+            TsType::TsInferType(infer) => {
+                format!("infer {}", self.print_type_param(&infer.type_param))
+            }
+            TsType::TsParenthesizedType(paren) => format!("({})", self.print_type(&paren.type_ann)),
+            TsType::TsTypeOperator(operator) => {
+                let op = match operator.op {
+                    TsTypeOperatorOp::KeyOf => "keyof",
+                    TsTypeOperatorOp::Unique => "unique",
+                    TsTypeOperatorOp::ReadOnly => "readonly",
+                };
+                format!("{} {}", op, self.print_type_wrapped(&operator.type_ann))
+            }
+            TsType::TsIndexedAccessType(indexed) => format!(
+                "{}{}[{}]",
+                if indexed.readonly { "readonly " } else { "" },
+                self.print_type_wrapped(&indexed.obj_type),
+                self.print_type(&indexed.index_type)
+            ),
+            TsType::TsMappedType(mapped) => self.print_mapped_type(mapped),
+            TsType::TsLitType(lit) => self.print_lit_type(&lit.lit),
+            TsType::TsTypePredicate(predicate) => {
+                let param = match &predicate.param_name {
+                    TsThisTypeOrIdent::TsThisType(_) => "this".to_string(),
+                    TsThisTypeOrIdent::Ident(ident) => ident.sym.to_string(),
+                };
+                let asserts = if predicate.asserts { "asserts " } else { "" };
+                match &predicate.type_ann {
+                    Some(ann) => format!("{}{} is {}", asserts, param, self.print_type_ann(ann)),
+                    None => format!("{}{}", asserts, param),
+                }
+            }
+            TsType::TsImportType(import) => self.print_import_type(import),
+        }
+    }
+
+    /// Print each type parameter of a declaration, e.g. `T extends object = {}`
+    pub fn print_type_params(&self, decl: &TsTypeParamDecl) -> Vec<String> {
+        decl.params
+            .iter()
+            .map(|param| self.print_type_param(param))
+            .collect()
+    }
+
+    /// Print type arguments of an instantiation, including angle brackets
+    pub fn print_type_args(&self, args: &TsTypeParamInstantiation) -> String {
+        let params: Vec<String> = args.params.iter().map(|p| self.print_type(p)).collect();
+        format!("<{}>", params.join(", "))
+    }
+
+    /// Print an `extends`/`implements` clause entry such as `Base<T>`
+    pub fn print_expr_with_type_args(&self, expr: &TsExprWithTypeArgs) -> String {
+        format!(
+            "{}{}",
+            self.print_expr(&expr.expr),
+            self.print_optional_type_args(expr.type_args.as_deref())
+        )
+    }
+
+    /// Print a dotted entity name such as `NodeJS.ReadableStream`
+    pub fn print_entity_name(&self, name: &TsEntityName) -> String {
+        match name {
+            TsEntityName::Ident(ident) => ident.sym.to_string(),
+            TsEntityName::TsQualifiedName(qualified) => format!(
+                "{}.{}",
+                self.print_entity_name(&qualified.left),
+                qualified.right.sym
+            ),
+        }
+    }
+
+    /// Print an object type literal or interface body
+    pub fn print_type_members(&self, members: &[TsTypeElement]) -> String {
+        if members.is_empty() {
+            return "{}".to_string();
+        }
+        let members: Vec<String> = members.iter().map(|m| self.print_type_element(m)).collect();
+        format!("{{ {} }}", members.join("; "))
+    }
+
+    /// Print a single member of an object type literal or interface
+    pub fn print_type_element(&self, element: &TsTypeElement) -> String {
+        match element {
+            TsTypeElement::TsCallSignatureDecl(call) => format!(
+                "{}({}){}",
+                self.print_optional_type_params(call.type_params.as_deref()),
+                self.print_fn_params(&call.params),
+                self.print_return_ann(call.type_ann.as_deref())
+            ),
+            TsTypeElement::TsConstructSignatureDecl(ctor) => format!(
+                "new {}({}){}",
+                self.print_optional_type_params(ctor.type_params.as_deref()),
+                self.print_fn_params(&ctor.params),
+                self.print_return_ann(ctor.type_ann.as_deref())
+            ),
+            TsTypeElement::TsPropertySignature(prop) => format!(
+                "{}{}{}{}",
+                if prop.readonly { "readonly " } else { "" },
+                self.print_member_key(&prop.key, prop.computed),
+                if prop.optional { "?" } else { "" },
+                self.print_return_ann(prop.type_ann.as_deref())
+            ),
+            TsTypeElement::TsGetterSignature(getter) => format!(
+                "get {}(){}",
+                self.print_member_key(&getter.key, getter.computed),
+                self.print_return_ann(getter.type_ann.as_deref())
+            ),
+            TsTypeElement::TsSetterSignature(setter) => format!(
+                "set {}({})",
+                self.print_member_key(&setter.key, setter.computed),
+                self.print_fn_param(&setter.param)
+            ),
+            TsTypeElement::TsMethodSignature(method) => format!(
+                "{}{}{}({}){}",
+                self.print_member_key(&method.key, method.computed),
+                if method.optional { "?" } else { "" },
+                self.print_optional_type_params(method.type_params.as_deref()),
+                self.print_fn_params(&method.params),
+                self.print_return_ann(method.type_ann.as_deref())
+            ),
+            TsTypeElement::TsIndexSignature(index) => format!(
+                "{}{}[{}]{}",
+                if index.is_static { "static " } else { "" },
+                if index.readonly { "readonly " } else { "" },
+                self.print_fn_params(&index.params),
+                self.print_return_ann(index.type_ann.as_deref())
+            ),
+        }
+    }
+
+    /// Print a parameter list of a function type or signature
+    pub fn print_fn_params(&self, params: &[TsFnParam]) -> String {
+        params
+            .iter()
+            .map(|p| self.print_fn_param(p))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Print a binding pattern, including its type annotation
+    pub fn print_pat(&self, pat: &Pat) -> String {
+        match pat {
+            Pat::Ident(ident) => self.print_binding_ident(ident),
+            Pat::Rest(rest) => format!(
+                "...{}{}",
+                self.print_pat_name(&rest.arg),
+                self.print_return_ann(rest.type_ann.as_deref())
+            ),
+            Pat::Assign(assign) => format!(
+                "{} = {}",
+                self.print_pat(&assign.left),
+                self.print_expr(&assign.right)
+            ),
+            Pat::Array(array) => format!(
+                "{}{}",
+                self.print_pat_name(pat),
+                self.print_return_ann(array.type_ann.as_deref())
+            ),
+            Pat::Object(object) => format!(
+                "{}{}",
+                self.print_pat_name(pat),
+                self.print_return_ann(object.type_ann.as_deref())
+            ),
+            Pat::Expr(expr) => self.print_expr(expr),
+            Pat::Invalid(_) => "unknown".to_string(),
+        }
+    }
+
+    /// Print only the binding part of a pattern, without type annotations
+    pub fn print_pat_name(&self, pat: &Pat) -> String {
+        match pat {
+            Pat::Ident(ident) => ident.id.sym.to_string(),
+            Pat::Rest(rest) => format!("...{}", self.print_pat_name(&rest.arg)),
+            Pat::Assign(assign) => self.print_pat_name(&assign.left),
+            Pat::Array(array) => {
+                let elems: Vec<String> = array
+                    .elems
+                    .iter()
+                    .map(|elem| match elem {
+                        Some(pat) => self.print_pat_name(pat),
+                        None => String::new(),
+                    })
+                    .collect();
+                format!("[{}]", elems.join(", "))
+            }
+            Pat::Object(object) => {
+                let props: Vec<String> = object
+                    .props
+                    .iter()
+                    .map(|prop| match prop {
+                        ObjectPatProp::KeyValue(kv) => format!(
+                            "{}: {}",
+                            self.print_prop_name(&kv.key),
+                            self.print_pat_name(&kv.value)
+                        ),
+                        ObjectPatProp::Assign(assign) => assign.key.id.sym.to_string(),
+                        ObjectPatProp::Rest(rest) => {
+                            format!("...{}", self.print_pat_name(&rest.arg))
+                        }
+                    })
+                    .collect();
+                if props.is_empty() {
+                    "{}".to_string()
+                } else {
+                    format!("{{ {} }}", props.join(", "))
+                }
+            }
+            Pat::Expr(expr) => self.print_expr(expr),
+            Pat::Invalid(_) => "unknown".to_string(),
+        }
+    }
+
+    /// Print a property name as it would appear in an object literal
+    pub fn print_prop_name(&self, name: &PropName) -> String {
+        match name {
+            PropName::Ident(ident) => ident.sym.to_string(),
+            PropName::Str(s) => self.print_str(s),
+            PropName::Num(n) => self.print_number(n),
+            PropName::BigInt(b) => format!("{}n", b.value),
+            PropName::Computed(computed) => format!("[{}]", self.print_expr(&computed.expr)),
+        }
+    }
+
+    /// Print an expression
+    ///
+    /// Only the shapes that commonly appear in declarations (superclasses, default
+    /// values, enum initializers) are printed faithfully; function bodies are elided.
+    pub fn print_expr(&self, expr: &Expr) -> String {
+        match expr {
+            Expr::This(_) => "this".to_string(),
+            Expr::Ident(ident) => ident.sym.to_string(),
+            Expr::Lit(lit) => self.print_lit(lit),
+            Expr::Array(array) => {
+                let elems: Vec<String> = array
+                    .elems
+                    .iter()
+                    .map(|elem| match elem {
+                        Some(elem) => self.print_expr_or_spread(elem),
+                        None => String::new(),
+                    })
+                    .collect();
+                format!("[{}]", elems.join(", "))
+            }
+            Expr::Object(object) => self.print_object_lit(object),
+            Expr::Fn(fn_expr) => {
+                let name = fn_expr
+                    .ident
+                    .as_ref()
+                    .map(|i| format!(" {}", i.sym))
+                    .unwrap_or_default();
+                format!(
+                    "{}function{}{}({}) {{ ... }}",
+                    if fn_expr.function.is_async {
+                        "async "
+                    } else {
+                        ""
+                    },
+                    if fn_expr.function.is_generator {
+                        "*"
+                    } else {
+                        ""
+                    },
+                    name,
+                    self.print_params(&fn_expr.function.params)
+                )
+            }
+            Expr::Arrow(arrow) => {
+                let params: Vec<String> = arrow.params.iter().map(|p| self.print_pat(p)).collect();
+                let body = match &*arrow.body {
+                    BlockStmtOrExpr::BlockStmt(block) if block.stmts.is_empty() => "{}".to_string(),
+                    BlockStmtOrExpr::BlockStmt(_) => "{ ... }".to_string(),
+                    BlockStmtOrExpr::Expr(expr) => self.print_expr(expr),
+                };
+                format!(
+                    "{}({}) => {}",
+                    if arrow.is_async { "async " } else { "" },
+                    params.join(", "),
+                    body
+                )
+            }
+            Expr::Class(class_expr) => match &class_expr.ident {
+                Some(ident) => format!("class {} {{ ... }}", ident.sym),
+                None => "class { ... }".to_string(),
+            },
+            Expr::Unary(unary) => {
+                let op = unary.op.as_str();
+                if op.chars().all(|c| c.is_ascii_alphabetic()) {
+                    format!("{} {}", op, self.print_expr(&unary.arg))
+                } else {
+                    format!("{}{}", op, self.print_expr(&unary.arg))
+                }
+            }
+            Expr::Update(update) => {
+                if update.prefix {
+                    format!("{}{}", update.op.as_str(), self.print_expr(&update.arg))
+                } else {
+                    format!("{}{}", self.print_expr(&update.arg), update.op.as_str())
+                }
+            }
+            Expr::Bin(bin) => format!(
+                "{} {} {}",
+                self.print_expr(&bin.left),
+                bin.op.as_str(),
+                self.print_expr(&bin.right)
+            ),
+            Expr::Assign(assign) => {
+                format!(
+                    "... {} {}",
+                    assign.op.as_str(),
+                    self.print_expr(&assign.right)
+                )
+            }
+            Expr::Member(member) => format!(
+                "{}{}",
+                self.print_expr(&member.obj),
+                self.print_member_prop(&member.prop)
+            ),
+            Expr::SuperProp(super_prop) => match &super_prop.prop {
+                SuperProp::Ident(ident) => format!("super.{}", ident.sym),
+                SuperProp::Computed(computed) => {
+                    format!("super[{}]", self.print_expr(&computed.expr))
+                }
+            },
+            Expr::Cond(cond) => format!(
+                "{} ? {} : {}",
+                self.print_expr(&cond.test),
+                self.print_expr(&cond.cons),
+                self.print_expr(&cond.alt)
+            ),
+            Expr::Call(call) => {
+                let callee = match &call.callee {
+                    Callee::Super(_) => "super".to_string(),
+                    Callee::Import(_) => "import".to_string(),
+                    Callee::Expr(expr) => self.print_expr(expr),
+                };
+                let args: Vec<String> = call
+                    .args
+                    .iter()
+                    .map(|a| self.print_expr_or_spread(a))
+                    .collect();
+                format!(
+                    "{}{}({})",
+                    callee,
+                    self.print_optional_type_args(call.type_args.as_deref()),
+                    args.join(", ")
+                )
+            }
+            Expr::New(new_expr) => {
+                let args: Vec<String> = new_expr
+                    .args
+                    .iter()
+                    .flatten()
+                    .map(|a| self.print_expr_or_spread(a))
+                    .collect();
+                format!(
+                    "new {}{}({})",
+                    self.print_expr(&new_expr.callee),
+                    self.print_optional_type_args(new_expr.type_args.as_deref()),
+                    args.join(", ")
+                )
+            }
+            Expr::Seq(seq) => {
+                let exprs: Vec<String> = seq.exprs.iter().map(|e| self.print_expr(e)).collect();
+                format!("({})", exprs.join(", "))
+            }
+            Expr::Tpl(tpl) => self.print_tpl(tpl),
+            Expr::TaggedTpl(tagged) => {
+                format!(
+                    "{}{}",
+                    self.print_expr(&tagged.tag),
+                    self.print_tpl(&tagged.tpl)
+                )
+            }
+            Expr::Yield(_) => "yield".to_string(),
+            Expr::Await(await_expr) => format!("await {}", self.print_expr(&await_expr.arg)),
+            Expr::MetaProp(meta) => match meta.kind {
+                MetaPropKind::NewTarget => "new.target".to_string(),
+                MetaPropKind::ImportMeta => "import.meta".to_string(),
+            },
+            Expr::Paren(paren) => format!("({})", self.print_expr(&paren.expr)),
+            Expr::TsTypeAssertion(assertion) => format!(
+                "<{}>{}",
+                self.print_type(&assertion.type_ann),
+                self.print_expr(&assertion.expr)
+            ),
+            Expr::TsConstAssertion(assertion) => {
+                format!("{} as const", self.print_expr(&assertion.expr))
+            }
+            Expr::TsNonNull(non_null) => format!("{}!", self.print_expr(&non_null.expr)),
+            Expr::TsAs(as_expr) => format!(
+                "{} as {}",
+                self.print_expr(&as_expr.expr),
+                self.print_type(&as_expr.type_ann)
+            ),
+            Expr::TsInstantiation(instantiation) => format!(
+                "{}{}",
+                self.print_expr(&instantiation.expr),
+                self.print_type_args(&instantiation.type_args)
+            ),
+            Expr::TsSatisfies(satisfies) => format!(
+                "{} satisfies {}",
+                self.print_expr(&satisfies.expr),
+                self.print_type(&satisfies.type_ann)
+            ),
+            Expr::OptChain(chain) => match &*chain.base {
+                OptChainBase::Member(member) => format!(
+                    "{}?.{}",
+                    self.print_expr(&member.obj),
+                    self.print_member_prop(&member.prop).trim_start_matches('.')
+                ),
+                OptChainBase::Call(call) => {
+                    let args: Vec<String> = call
+                        .args
+                        .iter()
+                        .map(|a| self.print_expr_or_spread(a))
+                        .collect();
+                    format!("{}?.({})", self.print_expr(&call.callee), args.join(", "))
+                }
+            },
+            Expr::PrivateName(name) => format!("#{}", name.id.sym),
+            Expr::JSXMember(_)
+            | Expr::JSXNamespacedName(_)
+            | Expr::JSXEmpty(_)
+            | Expr::JSXElement(_)
+            | Expr::JSXFragment(_) => "<JSX />".to_string(),
+            Expr::Invalid(_) => "unknown".to_string(),
+        }
+    }
+
+    fn print_keyword(&self, kind: TsKeywordTypeKind) -> &'static str {
+        match kind {
+            TsKeywordTypeKind::TsAnyKeyword => "any",
+            TsKeywordTypeKind::TsUnknownKeyword => "unknown",
+            TsKeywordTypeKind::TsNumberKeyword => "number",
+            TsKeywordTypeKind::TsObjectKeyword => "object",
+            TsKeywordTypeKind::TsBooleanKeyword => "boolean",
+            TsKeywordTypeKind::TsBigIntKeyword => "bigint",
+            TsKeywordTypeKind::TsStringKeyword => "string",
+            TsKeywordTypeKind::TsSymbolKeyword => "symbol",
+            TsKeywordTypeKind::TsVoidKeyword => "void",
+            TsKeywordTypeKind::TsUndefinedKeyword => "undefined",
+            TsKeywordTypeKind::TsNullKeyword => "null",
+            TsKeywordTypeKind::TsNeverKeyword => "never",
+            TsKeywordTypeKind::TsIntrinsicKeyword => "intrinsic",
+        }
+    }
+
+    /// Print a type, wrapping it in parentheses when it would otherwise bind
+    /// incorrectly as an array element or operator operand
+    fn print_type_wrapped(&self, ty: &TsType) -> String {
+        match ty {
+            TsType::TsUnionOrIntersectionType(_)
+            | TsType::TsFnOrConstructorType(_)
+            | TsType::TsConditionalType(_)
+            | TsType::TsTypeOperator(_)
+            | TsType::TsInferType(_) => format!("({})", self.print_type(ty)),
+            _ => self.print_type(ty),
+        }
+    }
+
+    fn print_type_list(&self, types: &[Box<TsType>], separator: &str) -> String {
+        types
+            .iter()
+            .map(|t| match &**t {
+                TsType::TsFnOrConstructorType(_) | TsType::TsConditionalType(_) => {
+                    format!("({})", self.print_type(t))
+                }
+                _ => self.print_type(t),
+            })
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    fn print_type_param(&self, param: &TsTypeParam) -> String {
+        let mut out = String::new();
+        if param.is_const {
+            out.push_str("const ");
+        }
+        if param.is_in {
+            out.push_str("in ");
+        }
+        if param.is_out {
+            out.push_str("out ");
+        }
+        out.push_str(&param.name.sym);
+        if let Some(constraint) = &param.constraint {
+            out.push_str(" extends ");
+            out.push_str(&self.print_type(constraint));
+        }
+        if let Some(default) = &param.default {
+            out.push_str(" = ");
+            out.push_str(&self.print_type(default));
+        }
+        out
+    }
+
+    fn print_optional_type_params(&self, decl: Option<&TsTypeParamDecl>) -> String {
+        match decl {
+            Some(decl) => format!("<{}>", self.print_type_params(decl).join(", ")),
+            None => String::new(),
+        }
+    }
+
+    fn print_optional_type_args(&self, args: Option<&TsTypeParamInstantiation>) -> String {
+        args.map(|a| self.print_type_args(a)).unwrap_or_default()
+    }
+
+    fn print_return_ann(&self, ann: Option<&TsTypeAnn>) -> String {
+        match ann {
+            Some(ann) => format!(": {}", self.print_type_ann(ann)),
+            None => String::new(),
+        }
+    }
+
+    fn print_fn_param(&self, param: &TsFnParam) -> String {
+        match param {
+            TsFnParam::Ident(ident) => self.print_binding_ident(ident),
+            TsFnParam::Array(array) => self.print_pat(&Pat::Array(array.clone())),
+            TsFnParam::Rest(rest) => self.print_pat(&Pat::Rest(rest.clone())),
+            TsFnParam::Object(object) => self.print_pat(&Pat::Object(object.clone())),
+        }
+    }
+
+    fn print_params(&self, params: &[Param]) -> String {
+        params
+            .iter()
+            .map(|p| self.print_pat(&p.pat))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn print_binding_ident(&self, ident: &BindingIdent) -> String {
+        format!(
+            "{}{}{}",
+            ident.id.sym,
+            if ident.id.optional { "?" } else { "" },
+            self.print_return_ann(ident.type_ann.as_deref())
+        )
+    }
+
+    fn print_member_key(&self, key: &Expr, computed: bool) -> String {
+        if computed {
+            format!("[{}]", self.print_expr(key))
+        } else {
+            self.print_expr(key)
+        }
+    }
+
+    fn print_member_prop(&self, prop: &MemberProp) -> String {
+        match prop {
+            MemberProp::Ident(ident) => format!(".{}", ident.sym),
+            MemberProp::PrivateName(name) => format!(".#{}", name.id.sym),
+            MemberProp::Computed(computed) => format!("[{}]", self.print_expr(&computed.expr)),
+        }
+    }
+
+    fn print_mapped_type(&self, mapped: &TsMappedType) -> String {
+        let readonly = match mapped.readonly {
+            Some(TruePlusMinus::True) => "readonly ",
+            Some(TruePlusMinus::Plus) => "+readonly ",
+            Some(TruePlusMinus::Minus) => "-readonly ",
+            None => "",
+        };
+        let optional = match mapped.optional {
+            Some(TruePlusMinus::True) => "?",
+            Some(TruePlusMinus::Plus) => "+?",
+            Some(TruePlusMinus::Minus) => "-?",
+            None => "",
+        };
+        let constraint = mapped
+            .type_param
+            .constraint
+            .as_ref()
+            .map(|c| format!(" in {}", self.print_type(c)))
+            .unwrap_or_default();
+        let name_type = mapped
+            .name_type
+            .as_ref()
+            .map(|n| format!(" as {}", self.print_type(n)))
+            .unwrap_or_default();
+        let type_ann = mapped
+            .type_ann
+            .as_ref()
+            .map(|t| format!(": {}", self.print_type(t)))
+            .unwrap_or_default();
+        format!(
+            "{{ {}[{}{}{}]{}{} }}",
+            readonly, mapped.type_param.name.sym, constraint, name_type, optional, type_ann
+        )
+    }
+
+    fn print_lit_type(&self, lit: &TsLit) -> String {
+        match lit {
+            TsLit::Number(n) => self.print_number(n),
+            TsLit::Str(s) => self.print_str(s),
+            TsLit::Bool(b) => b.value.to_string(),
+            TsLit::BigInt(b) => format!("{}n", b.value),
+            TsLit::Tpl(tpl) => {
+                let mut out = String::from("`");
+                for (i, quasi) in tpl.quasis.iter().enumerate() {
+                    out.push_str(&quasi.raw);
+                    if let Some(ty) = tpl.types.get(i) {
+                        out.push_str(&format!("${{{}}}", self.print_type(ty)));
+                    }
+                }
+                out.push('`');
+                out
+            }
+        }
+    }
+
+    fn print_import_type(&self, import: &TsImportType) -> String {
+        let qualifier = import
+            .qualifier
+            .as_ref()
+            .map(|q| format!(".{}", self.print_entity_name(q)))
+            .unwrap_or_default();
+        format!(
+            "import({}){}{}",
+            self.print_str(&import.arg),
+            qualifier,
+            self.print_optional_type_args(import.type_args.as_deref())
+        )
+    }
+
+    fn print_lit(&self, lit: &Lit) -> String {
+        match lit {
+            Lit::Str(s) => self.print_str(s),
+            Lit::Bool(b) => b.value.to_string(),
+            Lit::Null(_) => "null".to_string(),
+            Lit::Num(n) => self.print_number(n),
+            Lit::BigInt(b) => format!("{}n", b.value),
+            Lit::Regex(regex) => format!("/{}/{}", regex.exp, regex.flags),
+            Lit::JSXText(text) => text.value.to_string(),
+        }
+    }
+
+    fn print_str(&self, s: &Str) -> String {
+        match &s.raw {
+            Some(raw) => raw.to_string(),
+            None => format!("'{}'", s.value),
+        }
+    }
+
+    fn print_number(&self, n: &Number) -> String {
+        match &n.raw {
+            Some(raw) => raw.to_string(),
+            None => n.value.to_string(),
+        }
+    }
+
+    fn print_tpl(&self, tpl: &Tpl) -> String {
+        let mut out = String::from("`");
+        for (i, quasi) in tpl.quasis.iter().enumerate() {
+            out.push_str(&quasi.raw);
+            if let Some(expr) = tpl.exprs.get(i) {
+                out.push_str(&format!("${{{}}}", self.print_expr(expr)));
+            }
+        }
+        out.push('`');
+        out
+    }
+
+    fn print_expr_or_spread(&self, expr: &ExprOrSpread) -> String {
+        match expr.spread {
+            Some(_) => format!("...{}", self.print_expr(&expr.expr)),
+            None => self.print_expr(&expr.expr),
+        }
+    }
+
+    fn print_object_lit(&self, object: &ObjectLit) -> String {
+        if object.props.is_empty() {
+            return "{}".to_string();
+        }
+        let props: Vec<String> = object
+            .props
+            .iter()
+            .map(|prop| match prop {
+                PropOrSpread::Spread(spread) => format!("...{}", self.print_expr(&spread.expr)),
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::Shorthand(ident) => ident.sym.to_string(),
+                    Prop::KeyValue(kv) => format!(
+                        "{}: {}",
+                        self.print_prop_name(&kv.key),
+                        self.print_expr(&kv.value)
+                    ),
+                    Prop::Assign(assign) => {
+                        format!("{} = {}", assign.key.sym, self.print_expr(&assign.value))
+                    }
+                    Prop::Getter(getter) => {
+                        format!("get {}() {{ ... }}", self.print_prop_name(&getter.key))
+                    }
+                    Prop::Setter(setter) => {
+                        format!("set {}() {{ ... }}", self.print_prop_name(&setter.key))
+                    }
+                    Prop::Method(method) => format!(
+                        "{}({}) {{ ... }}",
+                        self.print_prop_name(&method.key),
+                        self.print_params(&method.function.params)
+                    ),
+                },
+            })
+            .collect();
+        format!("{{ {} }}", props.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swc_common::BytePos;
+    use swc_ecma_parser::{Parser, StringInput, Syntax, TsConfig};
+
+    /// Print the right-hand side of `type T = <source>`
+    fn print(source: &str) -> String {
+        let source = format!("type T = {};", source);
+        let input = StringInput::new(&source, BytePos(0), BytePos(source.len() as u32));
+        let module = Parser::new(Syntax::Typescript(TsConfig::default()), input, None)
+            .parse_module()
+            .unwrap();
+        match &module.body[0] {
+            ModuleItem::Stmt(Stmt::Decl(Decl::TsTypeAlias(alias))) => {
+                TypePrinter::new().print_type(&alias.type_ann)
+            }
+            item => panic!("not a type alias: {:?}", item),
+        }
+    }
+
+    #[test]
+    fn test_unions_and_intersections() {
+        assert_eq!(print("string | number | null"), "string | number | null");
+        assert_eq!(print("'a' | \"b\" | 1 | true"), "'a' | \"b\" | 1 | true");
+        assert_eq!(print("A & B"), "A & B");
+        assert_eq!(print("(A | B) & C"), "(A | B) & C");
+        assert_eq!(print("(string | number)[]"), "(string | number)[]");
+    }
+
+    #[test]
+    fn test_generics() {
+        assert_eq!(print("Promise<Array<string>>"), "Promise<Array<string>>");
+        assert_eq!(print("Map<string, Set<number>>"), "Map<string, Set<number>>");
+        assert_eq!(
+            print("React.ComponentProps<typeof Button>"),
+            "React.ComponentProps<typeof Button>"
+        );
+        assert_eq!(print("import('./types').Options<T>"), "import('./types').Options<T>");
+    }
+
+    #[test]
+    fn test_function_types() {
+        assert_eq!(print("(a: string, b?: number) => void"), "(a: string, b?: number) => void");
+        assert_eq!(print("<T>(value: T, ...rest: T[]) => T"), "<T>(value: T, ...rest: T[]) => T");
+        assert_eq!(print("new (options: Options) => Client"), "new (options: Options) => Client");
+        assert_eq!(print("(x: unknown) => x is string"), "(x: unknown) => x is string");
+        assert_eq!(
+            print("{ (req: Request): Response; readonly name: string; get?(key: K): V }"),
+            "{ (req: Request): Response; readonly name: string; get?(key: K): V }"
+        );
+    }
+
+    #[test]
+    fn test_mapped_and_conditional_types() {
+        assert_eq!(print("{ [K in keyof T]?: T[K] }"), "{ [K in keyof T]?: T[K] }");
+        assert_eq!(
+            print("{ readonly [K in keyof T as `get${K}`]: () => T[K] }"),
+            "{ readonly [K in keyof T as `get${K}`]: () => T[K] }"
+        );
+        assert_eq!(print("{ -readonly [K in Keys]-?: V }"), "{ -readonly [K in Keys]-?: V }");
+        assert_eq!(
+            print("T extends Promise<infer U> ? U : never"),
+            "T extends Promise<infer U> ? U : never"
+        );
+        assert_eq!(
+            print("T extends string ? 'str' : T extends number ? 'num' : never"),
+            "T extends string ? 'str' : T extends number ? 'num' : never"
+        );
+    }
+
+    #[test]
+    fn test_tuples() {
+        assert_eq!(print("[string, number]"), "[string, number]");
+        assert_eq!(print("[first: string, second?: number]"), "[first: string, second?: number]");
+        assert_eq!(print("[string, ...number[]]"), "[string, ...number[]]");
+        assert_eq!(print("readonly [x: number, y: number]"), "readonly [x: number, y: number]");
+        assert_eq!(print("[]"), "[]");
+    }
+}
@@ -1,15 +1,25 @@
 use crate::module_info::*;
-use crate::parser::parameter_parser::ParameterParser;
+use crate::parser::ast_parser::AstParser;
 use anyhow::Result;
 use std::fs;
 use std::path::Path;
-
-/// TypeScript-specific parsing utilities
-pub struct TypeScriptParser;
+use swc_ecma_ast::*;
+use swc_ecma_parser::{Syntax, TsConfig};
+
+/// TypeScript declaration file (.d.ts) analyzer
+///
+/// Regular declarations are extracted by [`AstParser`]; this layer adds the
+/// ambient-only constructs: `declare module` / `declare namespace` blocks,
+/// `declare global`, `export =` and the implicit exports of global scripts.
+pub struct TypeScriptParser {
+    ast_parser: AstParser,
+}
 
 impl TypeScriptParser {
     pub fn new() -> Self {
-        Self
+        Self {
+            ast_parser: AstParser::new(),
+        }
     }
 
     /// Parse TypeScript definition files (.d.ts)
@@ -18,8 +28,9 @@ impl TypeScriptParser {
         self.parse_declaration_content(
             &content,
             file_path
-                .file_stem()
+                .file_name()
                 .and_then(|s| s.to_str())
+                .map(|name| name.trim_end_matches(".d.ts"))
                 .unwrap_or("unknown"),
         )
     }
@@ -30,216 +41,263 @@ impl TypeScriptParser {
         content: &str,
         module_name: &str,
     ) -> Result<NodeModuleInfo> {
+        let module = self.ast_parser.parse_module(
+            content,
+            Syntax::Typescript(TsConfig {
+                dts: true,
+                decorators: true,
+                ..Default::default()
+            }),
+        )?;
+
         let mut module_info = NodeModuleInfo::new(module_name.to_string());
 
-        // Simple regex-based parsing for common TypeScript patterns
-        // This is a simplified approach - a full implementation would use a proper TS parser
+        // A declaration file without any import/export is a global script:
+        // everything it declares is visible to consumers
+        let is_global_script = !module
+            .body
+            .iter()
+            .any(|item| matches!(item, ModuleItem::ModuleDecl(_)));
 
-        self.extract_interfaces(content, &mut module_info);
-        self.extract_type_aliases(content, &mut module_info);
-        self.extract_function_declarations(content, &mut module_info);
-        self.extract_class_declarations(content, &mut module_info);
-        self.extract_exports(content, &mut module_info);
+        self.process_items(&module.body, &mut module_info, is_global_script)?;
+        self.ast_parser
+            .collect_import_reexports(&module.body, &mut module_info);
+        dedup_exports(&mut module_info);
 
         Ok(module_info)
     }
 
-    fn extract_interfaces(&self, content: &str, module_info: &mut NodeModuleInfo) {
-        let interface_regex = regex::Regex::new(r"(?m)^export\s+interface\s+(\w+)").unwrap();
-
-        for cap in interface_regex.captures_iter(content) {
-            if let Some(name) = cap.get(1) {
-                let type_info = TypeInfo {
-                    name: name.as_str().to_string(),
-                    kind: TypeKind::Interface,
-                    definition: format!("interface {}", name.as_str()),
-                    doc_comment: None,
-                };
-                module_info.add_type(type_info);
-                module_info.exports.push(name.as_str().to_string());
+    fn process_items(
+        &self,
+        items: &[ModuleItem],
+        module_info: &mut NodeModuleInfo,
+        implicit_export: bool,
+    ) -> Result<()> {
+        for item in items {
+            match item {
+                ModuleItem::Stmt(Stmt::Decl(Decl::TsModule(module_decl))) => {
+                    self.process_ts_module(module_decl, module_info, implicit_export)?;
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                    decl: Decl::TsModule(module_decl),
+                    ..
+                })) => {
+                    self.process_ts_module(module_decl, module_info, true)?;
+                }
+                ModuleItem::Stmt(Stmt::Decl(decl)) => {
+                    self.ast_parser
+                        .process_decl(decl, module_info, implicit_export)?;
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::TsExportAssignment(assignment)) => {
+                    self.process_export_assignment(&assignment.expr, module_info);
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::TsImportEquals(import_equals))
+                    if import_equals.is_export =>
+                {
+                    module_info.exports.push(import_equals.id.sym.to_string());
+                }
+                _ => {
+                    self.ast_parser.process_module_item(item, module_info)?;
+                }
             }
         }
+        Ok(())
     }
 
-    fn extract_type_aliases(&self, content: &str, module_info: &mut NodeModuleInfo) {
-        let type_regex = regex::Regex::new(r"(?m)^export\s+type\s+(\w+)").unwrap();
-
-        for cap in type_regex.captures_iter(content) {
-            if let Some(name) = cap.get(1) {
-                let type_info = TypeInfo {
-                    name: name.as_str().to_string(),
-                    kind: TypeKind::Type,
-                    definition: format!("type {}", name.as_str()),
-                    doc_comment: None,
-                };
-                module_info.add_type(type_info);
-                module_info.exports.push(name.as_str().to_string());
+    /// Handle `declare module "x" { }`, `namespace A.B { }` and `declare global { }`
+    fn process_ts_module(
+        &self,
+        module_decl: &TsModuleDecl,
+        parent: &mut NodeModuleInfo,
+        is_export: bool,
+    ) -> Result<()> {
+        let name = if module_decl.global {
+            "global".to_string()
+        } else {
+            match &module_decl.id {
+                TsModuleName::Ident(ident) => ident.sym.to_string(),
+                TsModuleName::Str(s) => s.value.to_string(),
             }
-        }
-    }
-
-    fn extract_function_declarations(&self, content: &str, module_info: &mut NodeModuleInfo) {
-        let func_regex =
-            regex::Regex::new(r"(?m)^export\s+(?:declare\s+)?function\s+(\w+)\s*\(([^)]*)\)")
-                .unwrap();
-
-        let param_parser = ParameterParser::new();
+        };
 
-        for cap in func_regex.captures_iter(content) {
-            if let (Some(name), Some(params)) = (cap.get(1), cap.get(2)) {
-                // Use the improved parameter parser
-                let parameters = param_parser.split_parameters(params.as_str())
-                    .iter()
-                    .map(|p| param_parser.parse_parameter(p))
-                    .collect();
-
-                let func_info = FunctionInfo {
-                    name: name.as_str().to_string(),
-                    parameters,
-                    return_type: None, // TODO: extract return type
-                    is_async: false,
-                    is_generator: false,
-                    doc_comment: None,
-                };
-
-                module_info.add_function(func_info);
-                module_info.exports.push(name.as_str().to_string());
-            }
-        }
-    }
+        // Shorthand ambient modules (`declare module "foo";`) have no body
+        let Some(body) = &module_decl.body else {
+            parent
+                .submodules
+                .entry(name.clone())
+                .or_insert_with(|| NodeModuleInfo::new(name));
+            return Ok(());
+        };
 
-    fn extract_class_declarations(&self, content: &str, module_info: &mut NodeModuleInfo) {
-        let class_regex = regex::Regex::new(r"(?m)^export\s+(?:declare\s+)?class\s+(\w+)").unwrap();
-
-        for cap in class_regex.captures_iter(content) {
-            if let Some(name) = cap.get(1) {
-                let class_info = ClassInfo {
-                    name: name.as_str().to_string(),
-                    constructor: None,      // TODO: extract constructor
-                    methods: Vec::new(),    // TODO: extract methods
-                    properties: Vec::new(), // TODO: extract properties
-                    extends: None,
-                    implements: Vec::new(),
-                    doc_comment: None,
-                };
-
-                module_info.add_class(class_info);
-                module_info.exports.push(name.as_str().to_string());
-            }
+        if is_export && matches!(module_decl.id, TsModuleName::Ident(_)) {
+            parent.exports.push(name.clone());
         }
-    }
 
-    fn extract_exports(&self, content: &str, module_info: &mut NodeModuleInfo) {
-        // Extract named exports
-        let export_regex = regex::Regex::new(r"export\s*\{\s*([^}]+)\s*\}").unwrap();
+        let mut namespace = parent
+            .submodules
+            .remove(&name)
+            .unwrap_or_else(|| NodeModuleInfo::new(name.clone()));
+        self.process_namespace_body(body, &mut namespace)?;
+        dedup_exports(&mut namespace);
+        parent.add_submodule(name, namespace);
 
-        for cap in export_regex.captures_iter(content) {
-            if let Some(exports) = cap.get(1) {
-                for export in exports.as_str().split(',') {
-                    let export_name = export.split_whitespace().next().unwrap_or("").to_string();
+        Ok(())
+    }
 
-                    if !export_name.is_empty() && !module_info.exports.contains(&export_name) {
-                        module_info.exports.push(export_name);
-                    }
-                }
+    fn process_namespace_body(
+        &self,
+        body: &TsNamespaceBody,
+        namespace: &mut NodeModuleInfo,
+    ) -> Result<()> {
+        match body {
+            TsNamespaceBody::TsModuleBlock(block) => {
+                // Inside an ambient block every declaration is exported unless
+                // the block uses explicit `export` modifiers
+                let has_explicit_exports = block
+                    .body
+                    .iter()
+                    .any(|item| matches!(item, ModuleItem::ModuleDecl(_)));
+                self.process_items(&block.body, namespace, !has_explicit_exports)
+            }
+            TsNamespaceBody::TsNamespaceDecl(nested) => {
+                // `namespace A.B { }` nests B inside A
+                let name = nested.id.sym.to_string();
+                namespace.exports.push(name.clone());
+                let mut child = namespace
+                    .submodules
+                    .remove(&name)
+                    .unwrap_or_else(|| NodeModuleInfo::new(name.clone()));
+                self.process_namespace_body(&nested.body, &mut child)?;
+                dedup_exports(&mut child);
+                namespace.add_submodule(name, child);
+                Ok(())
             }
         }
     }
 
-    fn parse_parameter_list(&self, params_str: &str) -> Vec<Parameter> {
-        if params_str.trim().is_empty() {
-            return Vec::new();
-        }
-
-        // Simple parameter parsing - split by comma but respect nested brackets
-        let mut parameters = Vec::new();
-        let mut current_param = String::new();
-        let mut bracket_depth = 0;
-        let mut in_string = false;
-        let mut string_char = '\0';
-
-        for ch in params_str.chars() {
-            match ch {
-                '"' | '\'' if !in_string => {
-                    in_string = true;
-                    string_char = ch;
-                    current_param.push(ch);
-                }
-                ch if in_string && ch == string_char => {
-                    in_string = false;
-                    current_param.push(ch);
-                }
-                '(' | '[' | '{' if !in_string => {
-                    bracket_depth += 1;
-                    current_param.push(ch);
-                }
-                ')' | ']' | '}' if !in_string => {
-                    bracket_depth -= 1;
-                    current_param.push(ch);
-                }
-                ',' if !in_string && bracket_depth == 0 => {
-                    if !current_param.trim().is_empty() {
-                        parameters.push(self.parse_parameter(current_param.trim()));
-                    }
-                    current_param.clear();
-                }
-                _ => {
-                    current_param.push(ch);
-                }
+    /// `export = foo` makes `foo` the module itself; when `foo` is a namespace
+    /// (often merged with a function of the same name) its members become the
+    /// module's members
+    fn process_export_assignment(&self, expr: &Expr, module_info: &mut NodeModuleInfo) {
+        let Expr::Ident(ident) = expr else {
+            return;
+        };
+        let name = ident.sym.to_string();
+
+        if let Some(namespace) = module_info.submodules.remove(&name) {
+            module_info.exports.extend(namespace.exports);
+            module_info.functions.extend(namespace.functions);
+            module_info.classes.extend(namespace.classes);
+            module_info.types.extend(namespace.types);
+            module_info.constants.extend(namespace.constants);
+            for (sub_name, submodule) in namespace.submodules {
+                module_info.add_submodule(sub_name, submodule);
             }
         }
 
-        // Add the last parameter
-        if !current_param.trim().is_empty() {
-            parameters.push(self.parse_parameter(current_param.trim()));
-        }
-
-        parameters
+        module_info.exports.push(name.clone());
+        module_info.export_assignment = Some(name);
     }
+}
 
-    fn parse_parameter(&self, param_str: &str) -> Parameter {
-        let param_str = param_str.trim();
-
-        // Check for rest parameter
-        let is_rest = param_str.starts_with("...");
-        let param_str = if is_rest { &param_str[3..] } else { param_str };
+impl Default for TypeScriptParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        // Check for optional parameter
-        let is_optional = param_str.contains('?');
+/// Overloaded declarations appear once per signature; keep each export name once
+fn dedup_exports(module_info: &mut NodeModuleInfo) {
+    let mut seen = std::collections::HashSet::new();
+    module_info.exports.retain(|name| seen.insert(name.clone()));
+}
 
-        // Split on colon to separate name and type
-        let parts: Vec<&str> = param_str.split(':').collect();
-        let name_part = parts[0].trim();
-        let type_part = if parts.len() > 1 {
-            Some(parts[1].trim().to_string())
-        } else {
-            None
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declare_function_overloads() {
+        let parser = TypeScriptParser::new();
+        let module = parser
+            .parse_declaration_content(
+                "export declare function parse(input: string): Ast;\nexport declare function parse(input: Buffer, encoding?: string): Ast;",
+                "test",
+            )
+            .unwrap();
+        assert_eq!(module.functions.len(), 2);
+        assert_eq!(module.functions[1].parameters.len(), 2);
+        assert_eq!(module.functions[0].return_type.as_deref(), Some("Ast"));
+        assert_eq!(module.exports, vec!["parse"]);
+    }
 
-        // Extract parameter name (remove ? if present)
-        let name = name_part.replace('?', "");
+    #[test]
+    fn test_declare_class_members() {
+        let parser = TypeScriptParser::new();
+        let module = parser
+            .parse_declaration_content(
+                "export declare class Server extends EventEmitter {\n  constructor(options?: ServerOptions);\n  readonly port: number;\n  listen(port: number): this;\n}\nexport declare const VERSION: string;",
+                "test",
+            )
+            .unwrap();
+        let class = &module.classes[0];
+        assert_eq!(class.constructor.as_ref().unwrap().parameters.len(), 1);
+        assert_eq!(class.methods[0].return_type.as_deref(), Some("this"));
+        assert_eq!(class.properties[0].property_type.as_deref(), Some("number"));
+        assert!(class.properties[0].is_readonly);
+        assert_eq!(module.constants[0].value_type.as_deref(), Some("string"));
+    }
 
-        // Check for default value
-        let (name, default_value) = if let Some(eq_pos) = name.find('=') {
-            let param_name = name[..eq_pos].trim().to_string();
-            let default_val = name[eq_pos + 1..].trim().to_string();
-            (param_name, Some(default_val))
-        } else {
-            (name, None)
-        };
+    #[test]
+    fn test_declaration_doc_comments() {
+        let parser = TypeScriptParser::new();
+        let module = parser
+            .parse_declaration_content(
+                "/**\n * Parse a document.\n * @example\n * parse('# hi')\n */\nexport declare function parse(input: string): Ast;\ndeclare namespace util {\n  /** Format a value */\n  function format(value: unknown): string;\n}",
+                "test",
+            )
+            .unwrap();
+        let func = &module.functions[0];
+        assert_eq!(func.doc_comment.as_deref(), Some("Parse a document."));
+        assert_eq!(
+            func.doc_tags.as_ref().unwrap().examples,
+            vec!["parse('# hi')"]
+        );
+        assert_eq!(
+            module.submodules["util"].functions[0].doc_comment.as_deref(),
+            Some("Format a value")
+        );
+    }
 
-        Parameter {
-            name,
-            param_type: type_part,
-            is_optional: is_optional || default_value.is_some(),
-            is_rest,
-            default_value,
-        }
+    #[test]
+    fn test_ambient_module_and_namespace() {
+        let parser = TypeScriptParser::new();
+        let module = parser
+            .parse_declaration_content(
+                "declare module \"fs\" {\n  function readFileSync(path: string): Buffer;\n  namespace promises { function readFile(path: string): Promise<Buffer>; }\n}",
+                "fs",
+            )
+            .unwrap();
+        let fs_module = &module.submodules["fs"];
+        assert_eq!(fs_module.functions[0].name, "readFileSync");
+        assert!(fs_module.exports.contains(&"readFileSync".to_string()));
+        assert_eq!(fs_module.submodules["promises"].functions[0].name, "readFile");
     }
-}
 
-impl Default for TypeScriptParser {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn test_export_assignment_merges_namespace() {
+        let parser = TypeScriptParser::new();
+        let module = parser
+            .parse_declaration_content(
+                "declare function e(): e.Express;\ndeclare namespace e {\n  interface Express { listen(port: number): void }\n  var json: () => void;\n  enum Mode { Strict = 1, Loose }\n}\nexport = e;",
+                "index",
+            )
+            .unwrap();
+        assert!(module.submodules.is_empty());
+        assert_eq!(module.functions[0].name, "e");
+        assert_eq!(module.constants[0].name, "json");
+        assert_eq!(module.types[1].definition, "enum Mode { Strict = 1, Loose }");
+        assert!(module.exports.contains(&"e".to_string()));
+        assert!(module.exports.contains(&"Express".to_string()));
     }
 }
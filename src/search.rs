@@ -0,0 +1,396 @@
+use crate::explorer::NodeModuleExplorer;
+use crate::module_info::NodeModuleInfo;
+use crate::npm_client::NpmClient;
+use crate::package_json::{active_conditions, PackageJson};
+use crate::utils::{is_dts_file, is_js_file, normalize_path};
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// How a search pattern is compared against symbol names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Case-insensitive substring
+    Substring,
+    /// Regular expression, case-sensitive unless the pattern says otherwise
+    Regex,
+    /// Case-insensitive subsequence, ranked by how tightly the letters cluster
+    Fuzzy,
+}
+
+/// A compiled search pattern
+pub struct SymbolMatcher {
+    mode: MatchMode,
+    pattern: String,
+    regex: Option<Regex>,
+}
+
+impl SymbolMatcher {
+    pub fn new(pattern: &str, mode: MatchMode) -> Result<Self> {
+        let regex = match mode {
+            MatchMode::Regex => Some(
+                Regex::new(pattern)
+                    .map_err(|e| anyhow!("Invalid search pattern '{}': {}", pattern, e))?,
+            ),
+            _ => None,
+        };
+        Ok(Self {
+            mode,
+            pattern: pattern.to_lowercase(),
+            regex,
+        })
+    }
+
+    /// Score a symbol name against the pattern; `None` means no match and
+    /// higher scores are better matches
+    pub fn score(&self, name: &str) -> Option<u32> {
+        match self.mode {
+            MatchMode::Regex => self.regex.as_ref()?.is_match(name).then_some(100),
+            MatchMode::Substring => {
+                let lower = name.to_lowercase();
+                let position = lower.find(&self.pattern)?;
+                Some(if lower == self.pattern {
+                    300
+                } else if position == 0 {
+                    200
+                } else {
+                    100
+                })
+            }
+            MatchMode::Fuzzy => fuzzy_score(&self.pattern, name),
+        }
+    }
+}
+
+/// Subsequence match: every pattern character must appear in order. Runs of
+/// consecutive characters and hits on word boundaries score higher, unmatched
+/// characters cost a little so shorter names win ties.
+fn fuzzy_score(pattern: &str, name: &str) -> Option<u32> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    let chars: Vec<char> = name.chars().collect();
+    let mut score: i64 = 0;
+    let mut previous: Option<usize> = None;
+    let mut next = 0;
+
+    for wanted in pattern.chars() {
+        let index =
+            (next..chars.len()).find(|&i| chars[i].to_lowercase().eq(wanted.to_lowercase()))?;
+        score += 10;
+        if previous.is_some_and(|p| p + 1 == index) {
+            score += 15;
+        }
+        let at_boundary = index == 0
+            || matches!(chars[index - 1], '_' | '-' | '.' | '$')
+            || (chars[index].is_uppercase() && chars[index - 1].is_lowercase());
+        if at_boundary {
+            score += 20;
+        }
+        previous = Some(index);
+        next = index + 1;
+    }
+
+    score -= (chars.len() - pattern.chars().count()) as i64;
+    Some(score.max(1) as u32)
+}
+
+/// Kind of symbol a search result refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Function,
+    Class,
+    Method,
+    Type,
+    Constant,
+}
+
+/// A symbol matching a search pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// Symbol name; methods and namespace members are qualified (`Class.method`)
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Import path of the module exposing the symbol, usable with `sig`
+    pub module_path: String,
+    /// File the symbol is defined in, relative to the package root
+    pub file: String,
+    #[serde(skip)]
+    pub score: u32,
+}
+
+/// Finds symbols by name across every JavaScript and declaration file of a
+/// package. Files are parsed by the explorer, so a barrel file lists the
+/// symbols it re-exports.
+pub struct PackageSearcher {
+    explorer: NodeModuleExplorer,
+}
+
+impl PackageSearcher {
+    pub fn new(explorer: NodeModuleExplorer) -> Self {
+        Self { explorer }
+    }
+
+    /// Search an unpacked package; results are ordered best match first
+    pub fn search_path(
+        &self,
+        package_path: &Path,
+        package_name: &str,
+        matcher: &SymbolMatcher,
+    ) -> Result<Vec<SearchResult>> {
+        let package_path = normalize_path(package_path);
+        let entry_points = entry_points(&package_path);
+
+        let mut results = Vec::new();
+        let files = WalkDir::new(&package_path)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0
+                    || !(entry.file_name() == "node_modules"
+                        || entry.file_name().to_string_lossy().starts_with('.'))
+            })
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file());
+
+        for entry in files {
+            let path = entry.path();
+            let searchable = is_dts_file(path)
+                || (is_js_file(path) && !path.to_string_lossy().ends_with(".min.js"));
+            if !searchable {
+                continue;
+            }
+            // Files that fail to parse (e.g. Flow or JSX sources) are skipped
+            let Ok(module_info) = self.explorer.parse_entry(path, &package_path) else {
+                continue;
+            };
+
+            let relative = path
+                .strip_prefix(&package_path)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let module_path = if entry_points.contains(path) {
+                package_name.to_string()
+            } else {
+                format!("{}/{}", package_name, module_subpath(&relative))
+            };
+            collect_matches(
+                &module_info,
+                "",
+                &module_path,
+                &relative,
+                matcher,
+                &mut results,
+            );
+        }
+
+        // The same symbol is usually both implemented in .js and declared in .d.ts
+        let mut seen = HashSet::new();
+        results.retain(|result: &SearchResult| {
+            seen.insert((result.module_path.clone(), result.name.clone(), result.kind))
+        });
+        results.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.module_path.cmp(&b.module_path))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(results)
+    }
+}
+
+/// Search a package by spec, path or git URL, found the way `tree` finds it
+pub async fn search_package(
+    package: &str,
+    matcher: &SymbolMatcher,
+    quiet: bool,
+    npm_client: NpmClient,
+) -> Result<Vec<SearchResult>> {
+    let explorer =
+        NodeModuleExplorer::new(package.to_string(), 1, quiet).with_npm_client(npm_client);
+    let (package, package_name) = explorer.locate().await?;
+    PackageSearcher::new(explorer).search_path(package.path(), &package_name, matcher)
+}
+
+/// Files whose symbols are importable from the package root
+fn entry_points(package_path: &Path) -> HashSet<std::path::PathBuf> {
+    let Ok(package_json) = PackageJson::read(&package_path.join("package.json")) else {
+        return HashSet::new();
+    };
+    let conditions = active_conditions(&[]);
+    [
+        package_json.main_entry(package_path, &conditions),
+        package_json.types_entry(package_path),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|path| normalize_path(&path))
+    .collect()
+}
+
+/// `lib/utils/index.d.ts` -> `lib/utils`, `lib/parse.js` -> `lib/parse`
+fn module_subpath(relative: &str) -> String {
+    let stem = [
+        ".d.ts", ".d.mts", ".d.cts", ".js", ".mjs", ".cjs", ".ts", ".tsx", ".mts", ".cts",
+    ]
+    .iter()
+    .find_map(|ext| relative.strip_suffix(ext))
+    .unwrap_or(relative);
+    match stem.strip_suffix("/index") {
+        Some(dir) => dir.to_string(),
+        None if stem == "index" => String::new(),
+        None => stem.to_string(),
+    }
+}
+
+fn collect_matches(
+    module_info: &NodeModuleInfo,
+    prefix: &str,
+    module_path: &str,
+    file: &str,
+    matcher: &SymbolMatcher,
+    results: &mut Vec<SearchResult>,
+) {
+    // Re-exported symbols point at the file that defines them
+    let mut push = |name: &str, qualified: String, kind: SymbolKind, defined_in: &Option<String>| {
+        if let Some(score) = matcher.score(name) {
+            results.push(SearchResult {
+                name: qualified,
+                kind,
+                module_path: module_path.to_string(),
+                file: defined_in.as_deref().unwrap_or(file).to_string(),
+                score,
+            });
+        }
+    };
+
+    for function in &module_info.functions {
+        push(
+            &function.name,
+            format!("{}{}", prefix, function.name),
+            SymbolKind::Function,
+            &function.defined_in,
+        );
+    }
+    for class in &module_info.classes {
+        push(
+            &class.name,
+            format!("{}{}", prefix, class.name),
+            SymbolKind::Class,
+            &class.defined_in,
+        );
+        for method in &class.methods {
+            push(
+                &method.name,
+                format!("{}{}.{}", prefix, class.name, method.name),
+                SymbolKind::Method,
+                &class.defined_in,
+            );
+        }
+    }
+    for type_info in &module_info.types {
+        push(
+            &type_info.name,
+            format!("{}{}", prefix, type_info.name),
+            SymbolKind::Type,
+            &type_info.defined_in,
+        );
+    }
+    for constant in &module_info.constants {
+        push(
+            &constant.name,
+            format!("{}{}", prefix, constant.name),
+            SymbolKind::Constant,
+            &constant.defined_in,
+        );
+    }
+
+    // Namespaces declared inside the file (`declare namespace x { }`)
+    for (name, namespace) in &module_info.submodules {
+        let prefix = format!("{}{}.", prefix, name);
+        collect_matches(namespace, &prefix, module_path, file, matcher, results);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_match_modes() {
+        let substring = SymbolMatcher::new("parse", MatchMode::Substring).unwrap();
+        assert!(substring.score("parse") > substring.score("parseRange"));
+        assert!(substring.score("parseRange") > substring.score("safeParse"));
+        assert_eq!(substring.score("format"), None);
+
+        let regex = SymbolMatcher::new("^create[A-Z]", MatchMode::Regex).unwrap();
+        assert!(regex.score("createServer").is_some());
+        assert!(regex.score("recreate").is_none());
+        assert!(SymbolMatcher::new("(", MatchMode::Regex).is_err());
+
+        let fuzzy = SymbolMatcher::new("rfs", MatchMode::Fuzzy).unwrap();
+        assert!(fuzzy.score("readFileSync") > fuzzy.score("rafters"));
+        assert_eq!(fuzzy.score("stat"), None);
+    }
+
+    #[test]
+    fn test_search_path_module_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let package = temp_dir.path();
+        std::fs::create_dir_all(package.join("lib/utils")).unwrap();
+        std::fs::create_dir_all(package.join("node_modules/dep")).unwrap();
+        std::fs::write(
+            package.join("package.json"),
+            r#"{"name": "pkg", "main": "index.js"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            package.join("index.js"),
+            "export function parse(input) {}\nexport { parseHeader } from \"./lib/header.js\";",
+        )
+        .unwrap();
+        std::fs::write(package.join("lib/header.js"), "export function parseHeader(line) {}")
+            .unwrap();
+        std::fs::write(
+            package.join("lib/parser.js"),
+            "export class Parser { parseValue(text) {} }\nexport const PARSE_LIMIT = 10;",
+        )
+        .unwrap();
+        std::fs::write(
+            package.join("lib/utils/index.d.ts"),
+            "export declare function parseArgs(argv: string[]): object;",
+        )
+        .unwrap();
+        std::fs::write(
+            package.join("node_modules/dep/index.js"),
+            "export function parseDep() {}",
+        )
+        .unwrap();
+
+        let searcher = PackageSearcher::new(NodeModuleExplorer::new("pkg".to_string(), 1, true));
+        let matcher = SymbolMatcher::new("parse", MatchMode::Substring).unwrap();
+        let results = searcher.search_path(package, "pkg", &matcher).unwrap();
+        let found: Vec<_> = results
+            .iter()
+            .map(|r| (r.name.as_str(), r.kind, r.module_path.as_str()))
+            .collect();
+        assert_eq!(found[0], ("parse", SymbolKind::Function, "pkg"));
+        assert!(found.contains(&("Parser.parseValue", SymbolKind::Method, "pkg/lib/parser")));
+        assert!(found.contains(&("PARSE_LIMIT", SymbolKind::Constant, "pkg/lib/parser")));
+        assert!(found.contains(&("parseArgs", SymbolKind::Function, "pkg/lib/utils")));
+        assert!(!found.iter().any(|(name, _, _)| *name == "parseDep"));
+
+        // Re-exported from the entry point, so importable from the root
+        let header = results
+            .iter()
+            .find(|r| r.name == "parseHeader" && r.module_path == "pkg")
+            .unwrap();
+        assert_eq!(header.file, "lib/header.js");
+    }
+}
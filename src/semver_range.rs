@@ -0,0 +1,340 @@
+use anyhow::{anyhow, Result};
+use semver::{Prerelease, Version};
+
+/// An npm version range (`^4.18`, `~1.2.3`, `>=4 <5`, `1.x || 2.0.0 - 2.3`)
+///
+/// Follows node-semver: a range is a `||`-separated list of comparator
+/// sets, each of which must be fully satisfied. Prereleases only match a
+/// set that names a prerelease of the same `major.minor.patch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+    sets: Vec<Vec<Comparator>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: Version,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+/// A version with optional (wildcard) components, as written in a range
+#[derive(Debug, Clone)]
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Prerelease,
+}
+
+impl VersionRange {
+    pub fn parse(range: &str) -> Result<Self> {
+        let sets = range
+            .split("||")
+            .map(|set| parse_set(set.trim()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow!("Invalid version range '{}'", range))?;
+        Ok(Self { sets })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.sets.iter().any(|set| {
+            set.iter().all(|comparator| comparator.matches(version))
+                && (version.pre.is_empty()
+                    || set.iter().any(|comparator| {
+                        !comparator.version.pre.is_empty()
+                            && comparator.version.major == version.major
+                            && comparator.version.minor == version.minor
+                            && comparator.version.patch == version.patch
+                    }))
+        })
+    }
+
+    /// The highest of `versions` that satisfies the range; strings that are
+    /// not valid versions are ignored
+    pub fn max_satisfying<'a>(
+        &self,
+        versions: impl IntoIterator<Item = &'a str>,
+    ) -> Option<&'a str> {
+        versions
+            .into_iter()
+            .filter_map(|raw| Some((parse_version(raw)?, raw)))
+            .filter(|(version, _)| self.matches(version))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, raw)| raw)
+    }
+}
+
+/// Parse a version leniently, as npm does for `v1.2.3` and `=1.2.3`
+pub fn parse_version(version: &str) -> Option<Version> {
+    let version = version
+        .trim()
+        .trim_start_matches('=')
+        .trim_start_matches('v');
+    Version::parse(version).ok()
+}
+
+impl Comparator {
+    fn new(op: Op, version: Version) -> Self {
+        Self { op, version }
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let ordering = cmp_precedence(version, &self.version);
+        match self.op {
+            Op::Lt => ordering.is_lt(),
+            Op::Le => ordering.is_le(),
+            Op::Gt => ordering.is_gt(),
+            Op::Ge => ordering.is_ge(),
+            Op::Eq => ordering.is_eq(),
+        }
+    }
+}
+
+/// Semver precedence, which ignores build metadata
+fn cmp_precedence(a: &Version, b: &Version) -> std::cmp::Ordering {
+    (a.major, a.minor, a.patch)
+        .cmp(&(b.major, b.minor, b.patch))
+        .then_with(|| a.pre.cmp(&b.pre))
+}
+
+fn parse_set(set: &str) -> Option<Vec<Comparator>> {
+    if set.is_empty() {
+        return Some(vec![any()]);
+    }
+
+    // Hyphen ranges: `1.2.3 - 2.3.4`
+    let tokens: Vec<&str> = set.split_whitespace().collect();
+    if let [from, "-", to] = tokens[..] {
+        let mut comparators = lower_bound(&parse_partial(from)?, Op::Ge);
+        comparators.extend(upper_bound(&parse_partial(to)?, Op::Le));
+        return Some(comparators);
+    }
+
+    // Operators may be separated from their version by whitespace (`>= 1.2`)
+    let mut comparators = Vec::new();
+    let mut pending_op: Option<String> = None;
+    for token in tokens {
+        let token = match pending_op.take() {
+            Some(op) => format!("{}{}", op, token),
+            None => token.to_string(),
+        };
+        let split = token
+            .find(|c: char| !matches!(c, '<' | '>' | '=' | '~' | '^'))
+            .unwrap_or(token.len());
+        let (op, version) = token.split_at(split);
+        if version.is_empty() {
+            pending_op = Some(op.to_string());
+            continue;
+        }
+        comparators.extend(desugar(op, &parse_partial(version)?)?);
+    }
+    if pending_op.is_some() {
+        return None;
+    }
+    Some(comparators)
+}
+
+fn desugar(op: &str, partial: &Partial) -> Option<Vec<Comparator>> {
+    let base = partial.floor();
+    let comparators = match op {
+        "" | "=" => match partial.patch {
+            Some(_) => vec![Comparator::new(Op::Eq, base)],
+            None => {
+                let mut comparators = lower_bound(partial, Op::Ge);
+                comparators.extend(upper_bound(partial, Op::Le));
+                comparators
+            }
+        },
+        "~" | "~>" => {
+            let upper = match (partial.major, partial.minor) {
+                (None, _) => return Some(vec![any()]),
+                (Some(major), None) => Version::new(major + 1, 0, 0),
+                (Some(major), Some(minor)) => Version::new(major, minor + 1, 0),
+            };
+            vec![Comparator::new(Op::Ge, base), exclusive(upper)]
+        }
+        "^" => {
+            // Everything below the next bump of the first non-zero component
+            let upper = match (partial.major, partial.minor, partial.patch) {
+                (None, _, _) => return Some(vec![any()]),
+                (Some(0), Some(0), Some(patch)) => Version::new(0, 0, patch + 1),
+                (Some(0), Some(minor), _) => Version::new(0, minor + 1, 0),
+                (Some(major), _, _) => Version::new(major + 1, 0, 0),
+            };
+            vec![Comparator::new(Op::Ge, base), exclusive(upper)]
+        }
+        ">=" => lower_bound(partial, Op::Ge),
+        ">" => lower_bound(partial, Op::Gt),
+        "<" => match (partial.major, partial.patch) {
+            // `<*` matches nothing
+            (None, _) => vec![exclusive(Version::new(0, 0, 0))],
+            (Some(_), Some(_)) => vec![Comparator::new(Op::Lt, base)],
+            (Some(_), None) => vec![exclusive(base)],
+        },
+        "<=" => upper_bound(partial, Op::Le),
+        _ => return None,
+    };
+    Some(comparators)
+}
+
+/// `>=1.2` → `>=1.2.0`; `>1.2` → `>=1.3.0`
+fn lower_bound(partial: &Partial, op: Op) -> Vec<Comparator> {
+    match (op, partial.major, partial.minor, partial.patch) {
+        (_, None, _, _) => vec![any()],
+        (Op::Gt, Some(major), None, _) => {
+            vec![Comparator::new(Op::Ge, Version::new(major + 1, 0, 0))]
+        }
+        (Op::Gt, Some(major), Some(minor), None) => {
+            vec![Comparator::new(Op::Ge, Version::new(major, minor + 1, 0))]
+        }
+        (op, ..) => vec![Comparator::new(op, partial.floor())],
+    }
+}
+
+/// `<=1.2` → `<1.3.0-0`; `<=1.2.3` → `<=1.2.3`
+fn upper_bound(partial: &Partial, op: Op) -> Vec<Comparator> {
+    match (partial.major, partial.minor, partial.patch) {
+        (None, _, _) => vec![any()],
+        (Some(major), None, _) => vec![exclusive(Version::new(major + 1, 0, 0))],
+        (Some(major), Some(minor), None) => {
+            vec![exclusive(Version::new(major, minor + 1, 0))]
+        }
+        _ => vec![Comparator::new(op, partial.floor())],
+    }
+}
+
+/// `<X-0`, which also keeps out X's own prereleases
+fn exclusive(mut version: Version) -> Comparator {
+    version.pre = Prerelease::new("0").expect("valid prerelease");
+    Comparator::new(Op::Lt, version)
+}
+
+fn any() -> Comparator {
+    Comparator::new(Op::Ge, Version::new(0, 0, 0))
+}
+
+impl Partial {
+    /// The lowest version the partial covers
+    fn floor(&self) -> Version {
+        let mut version = Version::new(
+            self.major.unwrap_or(0),
+            self.minor.unwrap_or(0),
+            self.patch.unwrap_or(0),
+        );
+        if self.patch.is_some() {
+            version.pre = self.pre.clone();
+        }
+        version
+    }
+}
+
+fn parse_partial(text: &str) -> Option<Partial> {
+    let text = text.trim_start_matches('v');
+    let text = text.split('+').next().unwrap_or(text);
+    let (numbers, pre) = match text.split_once('-') {
+        Some((numbers, pre)) => (numbers, Prerelease::new(pre).ok()?),
+        None => (text, Prerelease::EMPTY),
+    };
+
+    let mut parts = numbers.split('.');
+    let mut component = || -> Option<Option<u64>> {
+        match parts.next() {
+            None | Some("x" | "X" | "*") => Some(None),
+            Some(number) => number.parse().ok().map(Some),
+        }
+    };
+    let major = component()?;
+    let minor = component()?.filter(|_| major.is_some());
+    let patch = component()?.filter(|_| minor.is_some());
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(Partial {
+        major,
+        minor,
+        patch,
+        pre,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBLISHED: [&str; 9] = [
+        "3.9.0",
+        "4.17.3",
+        "4.18.0",
+        "4.18.2",
+        "4.19.0-beta.1",
+        "5.0.0-rc.1",
+        "5.0.0",
+        "5.1.0",
+        "junk",
+    ];
+
+    fn max(range: &str) -> Option<&'static str> {
+        VersionRange::parse(range)
+            .unwrap()
+            .max_satisfying(PUBLISHED)
+    }
+
+    #[test]
+    fn test_max_satisfying() {
+        assert_eq!(max("^4"), Some("4.18.2"));
+        assert_eq!(max("^4.17"), Some("4.18.2"));
+        assert_eq!(max("~4.17.1"), Some("4.17.3"));
+        assert_eq!(max("~4"), Some("4.18.2"));
+        assert_eq!(max(">=4 <5"), Some("4.18.2"));
+        assert_eq!(max(">= 4.18.0 < 5"), Some("4.18.2"));
+        assert_eq!(max("4.x"), Some("4.18.2"));
+        assert_eq!(max("4.18"), Some("4.18.2"));
+        assert_eq!(max("*"), Some("5.1.0"));
+        assert_eq!(max(""), Some("5.1.0"));
+        assert_eq!(max("<4"), Some("3.9.0"));
+        assert_eq!(max("<=4.17"), Some("4.17.3"));
+        assert_eq!(max(">4.18"), Some("5.1.0"));
+        assert_eq!(max("3 || 4.17"), Some("4.17.3"));
+        assert_eq!(max("4.0.0 - 4.18"), Some("4.18.2"));
+        assert_eq!(max("=4.18.0"), Some("4.18.0"));
+        assert_eq!(max("v4.18.0"), Some("4.18.0"));
+        assert_eq!(max("^6"), None);
+    }
+
+    #[test]
+    fn test_prereleases_need_an_opt_in() {
+        assert_eq!(max("^5.0.0-rc.0"), Some("5.1.0"));
+        assert_eq!(max("5.0.0-rc.1"), Some("5.0.0-rc.1"));
+        assert_eq!(max(">=4.19.0-beta.0 <4.20"), Some("4.19.0-beta.1"));
+        assert_eq!(max(">=4.18.2 <5"), Some("4.18.2"));
+    }
+
+    #[test]
+    fn test_zero_major_caret() {
+        let versions = ["0.0.3", "0.0.4", "0.1.0", "0.2.5", "0.3.0", "1.0.0"];
+        let max = |range: &str| VersionRange::parse(range).unwrap().max_satisfying(versions);
+        assert_eq!(max("^0.2.3"), Some("0.2.5"));
+        assert_eq!(max("^0.0.3"), Some("0.0.3"));
+        assert_eq!(max("^0.0"), Some("0.0.4"));
+        assert_eq!(max("^0.x"), Some("0.3.0"));
+        assert_eq!(max("^0.1"), Some("0.1.0"));
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        assert!(VersionRange::parse("next").is_err());
+        assert!(VersionRange::parse("^1.2.3.4").is_err());
+        assert!(VersionRange::parse(">=").is_err());
+        assert!(VersionRange::parse("1.2.3 -").is_err());
+        assert!(VersionRange::parse("=>1.2").is_err());
+    }
+}
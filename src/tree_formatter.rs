@@ -1,5 +1,7 @@
+use crate::api_diff::{ApiDiff, ChangeType, Severity};
 use crate::config::Config;
 use crate::module_info::*;
+use crate::search::{SearchResult, SymbolKind};
 use colored::*;
 
 pub struct TreeFormatter {
@@ -21,7 +23,7 @@ impl TreeFormatter {
 
     pub fn format_tree(&self, module: &NodeModuleInfo) -> String {
         let mut output = String::new();
-        self.format_module(module, &mut output, "", true);
+        self.format_module(module, &mut output, "", true, true);
         output
     }
 
@@ -40,7 +42,55 @@ impl TreeFormatter {
             signature.name.bright_blue().to_string()
         };
 
-        output.push_str(&format!("{} {}\n", icon, name));
+        let type_params = if signature.type_params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", signature.type_params.join(", "))
+        };
+
+        output.push_str(&format!("{} {}{}\n", icon, name, type_params));
+
+        // Only the summary line of the doc comment; the full text is in the JSON output
+        if let Some(summary) = signature
+            .doc_comment
+            .as_deref()
+            .and_then(|doc| doc.lines().next())
+        {
+            output.push_str(&format!("├── {}\n", summary));
+        }
+
+        let types_note = match &signature.source {
+            SymbolSource::Package => None,
+            SymbolSource::Merged(package) => Some(format!("Types from {}", package)),
+            SymbolSource::TypesPackage(package) => Some(format!("Declared in {} only", package)),
+            SymbolSource::Curated(origin) => {
+                Some(format!("Curated signature from {}, not parsed from the package", origin))
+            }
+        };
+        if let Some(note) = types_note {
+            if self.config.no_color {
+                output.push_str(&format!("├── {}\n", note));
+            } else {
+                output.push_str(&format!("├── {}\n", note.dimmed()));
+            }
+        }
+
+        if let Some(reason) = signature
+            .doc_tags
+            .as_ref()
+            .and_then(|tags| tags.deprecated.as_ref())
+        {
+            let label = if self.config.no_color {
+                "Deprecated".to_string()
+            } else {
+                "Deprecated".yellow().to_string()
+            };
+            if reason.is_empty() {
+                output.push_str(&format!("├── {}\n", label));
+            } else {
+                output.push_str(&format!("├── {}: {}\n", label, reason));
+            }
+        }
 
         if !signature.parameters.is_empty() {
             output.push_str("├── Parameters:\n");
@@ -66,14 +116,147 @@ impl TreeFormatter {
         output
     }
 
+    pub fn format_search_results(&self, pattern: &str, results: &[SearchResult]) -> String {
+        if results.is_empty() {
+            return format!("no symbols matching '{}'", pattern);
+        }
+
+        let mut output = String::new();
+        for result in results {
+            let (icon, kind) = match result.kind {
+                SymbolKind::Function | SymbolKind::Method => {
+                    let icon = if self.config.no_color {
+                        self.config.function_icon.clone()
+                    } else {
+                        self.config.function_icon.bright_green().to_string()
+                    };
+                    let kind = if result.kind == SymbolKind::Method {
+                        "method"
+                    } else {
+                        "function"
+                    };
+                    (icon, kind)
+                }
+                SymbolKind::Class => {
+                    let icon = if self.config.no_color {
+                        self.config.class_icon.clone()
+                    } else {
+                        self.config.class_icon.bright_blue().to_string()
+                    };
+                    (icon, "class")
+                }
+                SymbolKind::Type => ("🔷".to_string(), "type"),
+                SymbolKind::Constant => {
+                    let icon = if self.config.no_color {
+                        self.config.constant_icon.clone()
+                    } else {
+                        self.config.constant_icon.bright_red().to_string()
+                    };
+                    (icon, "constant")
+                }
+            };
+
+            let path = if self.config.no_color {
+                format!("{}:{}", result.module_path, result.name)
+            } else {
+                format!("{}:{}", result.module_path.dimmed(), result.name.bright_blue())
+            };
+            let kind = if self.config.no_color {
+                format!("({}, {})", kind, result.file)
+            } else {
+                format!("({}, {})", kind, result.file).dimmed().to_string()
+            };
+            output.push_str(&format!("{} {} {}\n", icon, path, kind));
+        }
+
+        output.trim_end().to_string()
+    }
+
+    pub fn format_api_diff(&self, diff: &ApiDiff) -> String {
+        let version = |v: &Option<String>| v.clone().unwrap_or_else(|| "?".to_string());
+        let icon = if self.config.no_color {
+            self.config.module_icon.clone()
+        } else {
+            self.config.module_icon.bright_yellow().to_string()
+        };
+        let mut output = format!(
+            "{} {} {} → {}\n",
+            icon,
+            diff.package,
+            version(&diff.from_version),
+            version(&diff.to_version)
+        );
+
+        if diff.changes.is_empty() {
+            output.push_str("└── no API changes");
+            return output;
+        }
+
+        let sections = [
+            ("breaking", Severity::Breaking, diff.breaking().count()),
+            ("additive", Severity::Additive, diff.additive().count()),
+        ];
+        let sections: Vec<_> = sections.iter().filter(|(_, _, count)| *count > 0).collect();
+        for (i, (title, severity, count)) in sections.iter().enumerate() {
+            let is_last_section = i == sections.len() - 1;
+            let heading = format!("{} ({})", title, count);
+            let heading = match (self.config.no_color, severity) {
+                (true, _) => heading,
+                (false, Severity::Breaking) => heading.red().bold().to_string(),
+                (false, Severity::Additive) => heading.green().bold().to_string(),
+            };
+            output.push_str(&format!(
+                "{}{}\n",
+                if is_last_section { "└── " } else { "├── " },
+                heading
+            ));
+
+            let child_prefix = if is_last_section { "    " } else { "│   " };
+            let changes: Vec<_> = diff
+                .changes
+                .iter()
+                .filter(|change| change.severity == *severity)
+                .collect();
+            for (j, change) in changes.iter().enumerate() {
+                let marker = match change.change {
+                    ChangeType::Added => "+",
+                    ChangeType::Removed => "-",
+                    ChangeType::Changed => "~",
+                };
+                let details = match (&change.before, &change.after) {
+                    (Some(before), Some(after)) => format!(": {} → {}", before, after),
+                    (Some(value), None) | (None, Some(value)) => format!(": {}", value),
+                    (None, None) => String::new(),
+                };
+                let path = if self.config.no_color {
+                    change.path.clone()
+                } else {
+                    change.path.bright_blue().to_string()
+                };
+                output.push_str(&format!(
+                    "{}{}{} {} {}{}\n",
+                    child_prefix,
+                    if j == changes.len() - 1 { "└── " } else { "├── " },
+                    marker,
+                    change.item.label(),
+                    path,
+                    details
+                ));
+            }
+        }
+
+        output.trim_end().to_string()
+    }
+
     fn format_module(
         &self,
         module: &NodeModuleInfo,
         output: &mut String,
         prefix: &str,
         is_last: bool,
+        is_root: bool,
     ) {
-        let current_prefix = if prefix.is_empty() {
+        let current_prefix = if is_root {
             ""
         } else if is_last {
             "└── "
@@ -93,7 +276,7 @@ impl TreeFormatter {
             module.name.bright_blue().to_string()
         };
 
-        let version_str = if let Some(version) = &module.version {
+        let mut version_str = if let Some(version) = &module.version {
             if self.config.no_color {
                 format!("@{}", version)
             } else {
@@ -102,13 +285,31 @@ impl TreeFormatter {
         } else {
             String::new()
         };
+        // What the range or dist-tag resolved from
+        if let Some(requested) = &module.requested_version {
+            let requested = format!(" ({})", requested);
+            if self.config.no_color {
+                version_str.push_str(&requested);
+            } else {
+                version_str.push_str(&requested.dimmed().to_string());
+            }
+        }
+        // Declarations merged in from DefinitelyTyped
+        if let Some(types_package) = &module.types_package {
+            let types = format!(" [types from {}]", types_package);
+            if self.config.no_color {
+                version_str.push_str(&types);
+            } else {
+                version_str.push_str(&types.dimmed().to_string());
+            }
+        }
 
         output.push_str(&format!(
             "{}{}{} {}{}\n",
             prefix, current_prefix, icon, name, version_str
         ));
 
-        let child_prefix = if prefix.is_empty() {
+        let child_prefix = if is_root {
             String::new()
         } else if is_last {
             format!("{}    ", prefix)
@@ -139,7 +340,13 @@ impl TreeFormatter {
 
         // Show functions
         if !module.functions.is_empty() {
-            let func_names: Vec<String> = module.functions.iter().map(|f| f.name.clone()).collect();
+            // Overloads share a name, so list each function once
+            let mut func_names: Vec<String> = Vec::new();
+            for function in &module.functions {
+                if !func_names.contains(&function.name) {
+                    func_names.push(function.name.clone());
+                }
+            }
             let functions_str = func_names.join(", ");
 
             let icon = if self.config.no_color {
@@ -225,7 +432,7 @@ impl TreeFormatter {
         let submodule_count = module.submodules.len();
         for (i, (_, submodule)) in module.submodules.iter().enumerate() {
             let is_last_submodule = i == submodule_count - 1;
-            self.format_module(submodule, output, &child_prefix, is_last_submodule);
+            self.format_module(submodule, output, &child_prefix, is_last_submodule, false);
         }
     }
 
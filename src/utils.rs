@@ -1,4 +1,5 @@
-use std::path::Path;
+use anyhow::{anyhow, Result};
+use std::path::{Component, Path, PathBuf};
 
 /// Parse package specification (e.g., "express@4.18.0", "@types/node", "lodash")
 pub fn parse_package_spec(spec: &str) -> (String, Option<String>) {
@@ -23,6 +24,81 @@ pub fn parse_package_spec(spec: &str) -> (String, Option<String>) {
     (spec.to_string(), None)
 }
 
+/// Where a package spec points: the registry, or a source npm accepts in
+/// place of a version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageSource {
+    Registry {
+        name: String,
+        version: Option<String>,
+    },
+    /// An unpacked package (`./packages/core`, `file:../lib`)
+    Directory(PathBuf),
+    /// A packed tarball (`./foo-1.0.0.tgz`, `file:foo.tar.gz`)
+    Tarball(PathBuf),
+    /// A git repository (`git+https://host/repo.git#v1.2.0`); `url` has the
+    /// `git+` prefix and the committish removed
+    Git {
+        url: String,
+        committish: Option<String>,
+    },
+}
+
+/// Classify a package spec as npm does for `npm install <spec>`
+///
+/// Git URLs and committishs starting with `-` are rejected: they are passed
+/// to `git fetch` and would be read as options.
+pub fn parse_package_source(spec: &str) -> Result<PackageSource> {
+    let is_tarball = |path: &str| {
+        [".tgz", ".tar.gz", ".tar"]
+            .iter()
+            .any(|ext| path.ends_with(ext))
+    };
+    let local = |path: &str| {
+        let expanded = match path.strip_prefix("~/") {
+            Some(rest) => std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(rest))
+                .unwrap_or_else(|| PathBuf::from(path)),
+            None => PathBuf::from(path),
+        };
+        if is_tarball(path) {
+            PackageSource::Tarball(expanded)
+        } else {
+            PackageSource::Directory(expanded)
+        }
+    };
+
+    if let Some(path) = spec.strip_prefix("file:") {
+        // `file://` URLs and bare `file:` paths both name a local path
+        return Ok(local(path.strip_prefix("//").unwrap_or(path)));
+    }
+    let git_url = spec
+        .strip_prefix("git+")
+        .or_else(|| spec.starts_with("git://").then_some(spec));
+    if let Some(url) = git_url {
+        let (url, committish) = match url.split_once('#') {
+            Some((url, committish)) => (url, Some(committish.to_string())),
+            None => (url, None),
+        };
+        if url.starts_with('-') || committish.as_deref().is_some_and(|c| c.starts_with('-')) {
+            return Err(anyhow!(
+                "Invalid git spec '{}': the URL and committish must not start with '-'",
+                spec
+            ));
+        }
+        return Ok(PackageSource::Git {
+            url: url.to_string(),
+            committish: committish.filter(|c| !c.is_empty()),
+        });
+    }
+    if spec == "." || spec == ".." || ["./", "../", "/", "~/"].iter().any(|p| spec.starts_with(p)) {
+        return Ok(local(spec));
+    }
+
+    let (name, version) = parse_package_spec(spec);
+    Ok(PackageSource::Registry { name, version })
+}
+
 /// Extract base package name from a module path (e.g., "express/lib/router" -> "express")
 pub fn extract_base_package(module_path: &str) -> String {
     let parts: Vec<&str> = module_path.split('/').collect();
@@ -36,6 +112,20 @@ pub fn extract_base_package(module_path: &str) -> String {
     }
 }
 
+/// The DefinitelyTyped package for a package name: `express` →
+/// `@types/express`, `@babel/core` → `@types/babel__core`; `None` for
+/// `@types` packages themselves
+pub fn types_package_name(package_name: &str) -> Option<String> {
+    if package_name.starts_with("@types/") {
+        return None;
+    }
+    let mangled = match package_name.strip_prefix('@') {
+        Some(scoped) => scoped.replacen('/', "__", 1),
+        None => package_name.to_string(),
+    };
+    Some(format!("@types/{}", mangled))
+}
+
 /// Check if a path is likely a JavaScript/TypeScript file
 pub fn is_js_file(path: &Path) -> bool {
     if let Some(ext) = path.extension() {
@@ -61,6 +151,49 @@ pub fn is_dts_file(path: &Path) -> bool {
             .unwrap_or(false)
 }
 
+/// Resolve a relative import specifier (`./router`, `../util.js`) from
+/// `from_file` to a file on disk, trying the extensions and index files that
+/// Node and TypeScript would
+pub fn resolve_relative_module(from_file: &Path, specifier: &str) -> Option<PathBuf> {
+    let base = normalize_path(&from_file.parent()?.join(specifier));
+    let base_str = base.to_string_lossy();
+
+    let mut candidates = Vec::new();
+    // Declarations refer to siblings by their runtime name (`./x.js` is `./x.d.ts`)
+    if is_dts_file(from_file) {
+        let stem = ["js", "mjs", "cjs"]
+            .iter()
+            .find_map(|ext| base_str.strip_suffix(&format!(".{}", ext)))
+            .unwrap_or(&base_str);
+        candidates.push(PathBuf::from(format!("{}.d.ts", stem)));
+        candidates.push(base.join("index.d.ts"));
+    }
+    candidates.push(base.clone());
+    for ext in ["js", "mjs", "cjs", "ts", "tsx", "d.ts"] {
+        candidates.push(PathBuf::from(format!("{}.{}", base_str, ext)));
+    }
+    for index in ["index.js", "index.ts", "index.d.ts"] {
+        candidates.push(base.join(index));
+    }
+
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+/// Remove `.` and `..` components without touching the filesystem
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,6 +215,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_package_source() {
+        assert_eq!(
+            parse_package_source("@types/node@18").unwrap(),
+            PackageSource::Registry {
+                name: "@types/node".to_string(),
+                version: Some("18".to_string())
+            }
+        );
+        assert_eq!(
+            parse_package_source("./packages/core").unwrap(),
+            PackageSource::Directory(PathBuf::from("./packages/core"))
+        );
+        assert_eq!(
+            parse_package_source("file:../lib").unwrap(),
+            PackageSource::Directory(PathBuf::from("../lib"))
+        );
+        assert_eq!(
+            parse_package_source("./foo-1.0.0.tgz").unwrap(),
+            PackageSource::Tarball(PathBuf::from("./foo-1.0.0.tgz"))
+        );
+        assert_eq!(
+            parse_package_source("file:///tmp/foo.tar.gz").unwrap(),
+            PackageSource::Tarball(PathBuf::from("/tmp/foo.tar.gz"))
+        );
+        assert_eq!(
+            parse_package_source("git+https://github.com/expressjs/express.git#v4.18.2").unwrap(),
+            PackageSource::Git {
+                url: "https://github.com/expressjs/express.git".to_string(),
+                committish: Some("v4.18.2".to_string())
+            }
+        );
+        assert_eq!(
+            parse_package_source("git://example.com/repo.git").unwrap(),
+            PackageSource::Git {
+                url: "git://example.com/repo.git".to_string(),
+                committish: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_git_spec_options_are_rejected() {
+        assert!(parse_package_source(
+            "git+https://host/r.git#--upload-pack=touch /tmp/PWNED; git-upload-pack"
+        )
+        .is_err());
+        assert!(parse_package_source("git+--upload-pack=touch /tmp/PWNED").is_err());
+        assert!(parse_package_source("git+https://host/r.git#v1-beta").is_ok());
+    }
+
     #[test]
     fn test_extract_base_package() {
         assert_eq!(extract_base_package("express"), "express");
@@ -89,4 +273,45 @@ mod tests {
         assert_eq!(extract_base_package("@types/node"), "@types/node");
         assert_eq!(extract_base_package("@types/node/fs"), "@types/node");
     }
+
+    #[test]
+    fn test_types_package_name() {
+        assert_eq!(types_package_name("express").unwrap(), "@types/express");
+        assert_eq!(
+            types_package_name("@babel/core").unwrap(),
+            "@types/babel__core"
+        );
+        assert_eq!(types_package_name("@types/node"), None);
+    }
+
+    #[test]
+    fn test_resolve_relative_module() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("lib/router")).unwrap();
+        std::fs::write(root.join("index.js"), "").unwrap();
+        std::fs::write(root.join("index.d.ts"), "").unwrap();
+        std::fs::write(root.join("lib/util.js"), "").unwrap();
+        std::fs::write(root.join("lib/util.d.ts"), "").unwrap();
+        std::fs::write(root.join("lib/router/index.js"), "").unwrap();
+
+        let from = root.join("index.js");
+        assert_eq!(
+            resolve_relative_module(&from, "./lib/util"),
+            Some(root.join("lib/util.js"))
+        );
+        assert_eq!(
+            resolve_relative_module(&from, "./lib/router"),
+            Some(root.join("lib/router/index.js"))
+        );
+        assert_eq!(
+            resolve_relative_module(&root.join("index.d.ts"), "./lib/util.js"),
+            Some(root.join("lib/util.d.ts"))
+        );
+        assert_eq!(
+            resolve_relative_module(&root.join("lib/util.js"), "../index"),
+            Some(root.join("index.js"))
+        );
+        assert_eq!(resolve_relative_module(&from, "./missing"), None);
+    }
 }
@@ -1,8 +1,118 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
 
+/// Gzipped tarball with the given `(path, content)` entries
+fn package_tarball(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    for (path, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, content.as_bytes()).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+/// Minimal HTTP registry on localhost serving fixed `path → body` routes,
+/// built from the registry's own URL
+///
+/// With a `token`, requests without `Authorization: Bearer <token>` get a
+/// 401. Every request line is logged together with its auth header.
+struct MockRegistry {
+    url: String,
+    requests: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+impl MockRegistry {
+    fn start(
+        token: Option<&str>,
+        routes: impl FnOnce(&str) -> Vec<(String, Vec<u8>)>,
+    ) -> Self {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let routes = routes(&url);
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let expected_auth = token.map(|token| format!("Bearer {}", token));
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut auth = None;
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap() == 0 || header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("authorization") {
+                            auth = Some(value.trim().to_string());
+                        }
+                    }
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("").to_string();
+                log.lock().unwrap().push(format!(
+                    "{} {}",
+                    path,
+                    auth.as_deref().unwrap_or("-")
+                ));
+                let (status, body) = if expected_auth.is_some() && auth != expected_auth {
+                    ("401 Unauthorized", Vec::new())
+                } else {
+                    match routes.iter().find(|(route, _)| *route == path) {
+                        Some((_, body)) => ("200 OK", body.clone()),
+                        None => ("404 Not Found", Vec::new()),
+                    }
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                let _ = stream.write_all(&body);
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Registry document for a single release whose tarball `registry` serves
+/// at `tarball_path`
+fn registry_document(registry: &str, name: &str, version: &str, tarball_path: &str) -> Vec<u8> {
+    serde_json::json!({
+        "name": name,
+        "dist-tags": {"latest": version},
+        "versions": {
+            version: {
+                "name": name,
+                "version": version,
+                "dist": {"tarball": format!("{}{}", registry, tarball_path)}
+            }
+        }
+    })
+    .to_string()
+    .into_bytes()
+}
+
 /// Test CLI command execution and argument parsing
 #[cfg(test)]
+#[allow(clippy::needless_borrows_for_generic_args)]
 mod cli_tests {
     use super::*;
 
@@ -88,6 +198,1147 @@ mod cli_tests {
             .stdout(predicate::str::contains("signature not available"));
     }
 
+    #[test]
+    fn test_markdown_output() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("node_modules/md-fixture");
+        std::fs::create_dir_all(package_dir.join("lib")).unwrap();
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{"name": "md-fixture", "version": "1.2.0", "types": "index.d.ts"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            package_dir.join("index.d.ts"),
+            "/** Parse a value\n * @param input Raw text\n */\nexport declare function parse(input: string | Buffer): Value;\nexport declare class Parser { parse(input: string): Value; }\n",
+        )
+        .unwrap();
+        std::fs::write(package_dir.join("lib/util.js"), "export function noop() {}").unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["tree", "md-fixture", "--quiet", "-o", "markdown"])
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with("# `md-fixture@1.2.0`"))
+            .stdout(predicate::str::contains(
+                "| `parse` | `(input: string \\| Buffer): Value` | Parse a value |",
+            ))
+            .stdout(predicate::str::contains("```ts\nclass Parser {\n  parse(input: string): Value;\n}\n```"))
+            .stdout(predicate::str::contains("## `md-fixture/lib`"))
+            .stdout(predicate::str::contains("### `md-fixture/lib/util`"));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["sig", "md-fixture:parse", "--quiet", "-o", "markdown"])
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with(
+                "### `parse`\n\n```ts\nfunction parse(input: string | Buffer): Value\n```",
+            ))
+            .stdout(predicate::str::contains("| `input: string \\| Buffer` | `string \\| Buffer` | Raw text |"));
+    }
+
+    #[test]
+    fn test_dts_output() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("node_modules/js-only");
+        std::fs::create_dir_all(package_dir.join("lib")).unwrap();
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{"name": "js-only", "version": "0.3.1", "main": "index.js"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            package_dir.join("index.js"),
+            "/** Format a value */\nexport function format(value, options = {}) {}\nexport class Formatter { constructor(locale) {} }\n",
+        )
+        .unwrap();
+        std::fs::write(package_dir.join("lib/units.js"), "export function bytes(n) {}").unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["tree", "js-only", "--quiet", "-o", "dts"])
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with("// Declarations for js-only@0.3.1"))
+            .stdout(predicate::str::contains(
+                "declare module \"js-only\" {\n  /** Format a value */\n  export function format(value: any, options?: any): any;\n",
+            ))
+            .stdout(predicate::str::contains(
+                "  export class Formatter {\n    constructor(locale: any);\n  }\n",
+            ))
+            .stdout(predicate::str::contains(
+                "declare module \"js-only/lib/units\" {\n  export function bytes(n: any): any;\n}",
+            ));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["sig", "js-only:format", "--quiet", "-o", "dts"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "/** Format a value */\nexport declare function format(value: any, options?: any): any;",
+            ));
+    }
+
+    #[test]
+    fn test_llm_output() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("node_modules/budgeted");
+        std::fs::create_dir_all(package_dir.join("lib")).unwrap();
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{"name": "budgeted", "version": "1.2.0", "main": "index.js"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            package_dir.join("index.js"),
+            "/** Start a server listening on the given port, retrying with backoff until the socket is bound */\nexport function listen(port, options = {}) {}\n",
+        )
+        .unwrap();
+        std::fs::write(package_dir.join("lib/units.js"), "export function bytes(n) {}").unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["tree", "budgeted", "--quiet", "-o", "llm"])
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with("# budgeted@1.2.0\n"))
+            .stdout(predicate::str::contains(
+                "fn listen(port, options?) // Start a server listening on the given port, retrying with backoff until the socket is bound\n",
+            ))
+            .stdout(predicate::str::contains("# budgeted/lib/units\n"))
+            .stdout(predicate::str::contains("[elided").not());
+
+        // A budget implies -o llm and trims the output to fit
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["tree", "budgeted", "--quiet", "--max-tokens", "45"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("fn listen(port, options?)\n"))
+            .stdout(predicate::str::contains("[elided to fit ~45 tokens: 1 doc comment(s)]"));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["tree", "budgeted", "--quiet", "-o", "json", "--max-tokens", "40"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--max-tokens only applies to the llm output format"));
+    }
+
+    #[test]
+    fn test_docs_llms_txt() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("node_modules/documented");
+        std::fs::create_dir_all(package_dir.join("lib")).unwrap();
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{"name": "documented", "version": "0.4.0", "main": "index.js"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            package_dir.join("index.js"),
+            "/**\n * Parse a duration.\n * Accepts '1h' or '30m'.\n */\nexport function parse(text) {}\nfunction helper() {}\n",
+        )
+        .unwrap();
+        std::fs::write(package_dir.join("lib/units.js"), "export const UNITS = ['h', 'm'];").unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["docs", "documented", "--format", "llms-txt", "--out-dir", "docs/documented"])
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::contains(
+                "Wrote llms.txt and llms-full.txt to docs/documented",
+            ));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["docs", "documented", "--out-dir", "docs/quiet", "--quiet"])
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::is_empty());
+        assert!(temp_dir.path().join("docs/quiet/llms.txt").exists());
+
+        let out_dir = temp_dir.path().join("docs/documented");
+        let index = std::fs::read_to_string(out_dir.join("llms.txt")).unwrap();
+        assert!(index.starts_with("# documented\n\n> API reference for documented 0.4.0: 2 modules exporting 1 function, 0 classes, 0 types and 1 constant.\n"));
+        assert!(index.contains("- `documented`: parse()\n- `documented/lib/units`: UNITS\n"));
+
+        let full = std::fs::read_to_string(out_dir.join("llms-full.txt")).unwrap();
+        assert!(full.contains(
+            "## documented\n\n### parse\n\n```ts\nfunction parse(text)\n```\n\nParse a duration.\nAccepts '1h' or '30m'.\n"
+        ));
+        assert!(full.contains("## documented/lib/units\n\n### UNITS\n"));
+        assert!(!full.contains("helper"));
+    }
+
+    #[test]
+    fn test_search_command_modes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("node_modules/search-fixture");
+        std::fs::create_dir_all(package_dir.join("lib")).unwrap();
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{"name": "search-fixture", "main": "index.js"}"#,
+        )
+        .unwrap();
+        std::fs::write(package_dir.join("index.js"), "export function readFileSync(path) {}").unwrap();
+        std::fs::write(package_dir.join("lib/stream.js"), "export class ReadStream {}").unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .env("NO_COLOR", "1")
+            .args(["search", "search-fixture", "read"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("search-fixture:readFileSync (function, index.js)"))
+            .stdout(predicate::str::contains("search-fixture/lib/stream:ReadStream (class"));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["search", "search-fixture", "rfs", "--mode", "fuzzy", "-o", "json"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\"name\": \"readFileSync\""))
+            .stdout(predicate::str::contains("ReadStream").not());
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .args(["search", "search-fixture", "(", "--mode", "regex"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid search pattern"));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .env("NO_COLOR", "1")
+            .args(["search", "./node_modules/search-fixture", "stream", "--quiet"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("search-fixture/lib/stream:ReadStream (class"));
+    }
+
+    #[test]
+    fn test_cache_subcommands() {
+        use pretty_node::cache::PackageCache;
+
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let tarball = package_tarball(&[("package/package.json", "{}")]);
+        PackageCache::new(cache_dir.path().to_path_buf())
+            .put_package(None, "left-pad", "1.3.0", None, "https://example.test/left-pad.tgz", &tarball)
+            .unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.env("PRETTY_NODE_CACHE_DIR", cache_dir.path())
+            .args(["cache", "ls"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("left-pad@1.3.0"));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.env("PRETTY_NODE_CACHE_DIR", cache_dir.path())
+            .args(["cache", "prune", "--older-than", "7d"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Pruned 0 package(s)"));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.env("PRETTY_NODE_CACHE_DIR", cache_dir.path())
+            .args(["cache", "prune", "--older-than", "soon"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid duration"));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.env("PRETTY_NODE_CACHE_DIR", cache_dir.path())
+            .args(["cache", "clean"])
+            .assert()
+            .success();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.env("PRETTY_NODE_CACHE_DIR", cache_dir.path())
+            .args(["cache", "ls", "-o", "json"])
+            .assert()
+            .success()
+            .stdout(predicate::str::diff("[]\n"));
+    }
+
+    #[test]
+    fn test_stale_cache_entry_is_downloaded_again() {
+        use base64::Engine;
+        use pretty_node::cache::PackageCache;
+        use sha2::Digest;
+
+        let tarball = package_tarball(&[
+            (
+                "package/package.json",
+                r#"{"name": "left-pad", "version": "1.3.0", "main": "index.js"}"#,
+            ),
+            ("package/index.js", "export function publishedPad(str) {}"),
+        ]);
+        let integrity = format!(
+            "sha512-{}",
+            base64::engine::general_purpose::STANDARD.encode(sha2::Sha512::digest(&tarball))
+        );
+        let registry = MockRegistry::start(None, |url| {
+            let tarball_path = "left-pad/-/left-pad-1.3.0.tgz";
+            let document = serde_json::json!({
+                "name": "left-pad",
+                "dist-tags": {"latest": "1.3.0"},
+                "versions": {
+                    "1.3.0": {
+                        "name": "left-pad",
+                        "version": "1.3.0",
+                        "dist": {
+                            "tarball": format!("{}{}", url, tarball_path),
+                            "integrity": integrity,
+                        }
+                    }
+                }
+            });
+            vec![
+                ("/left-pad".to_string(), document.to_string().into_bytes()),
+                (format!("/{}", tarball_path), tarball),
+            ]
+        });
+
+        // A poisoned release in the cache, stored without any hash
+        let home = tempfile::TempDir::new().unwrap();
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let poisoned = package_tarball(&[
+            (
+                "package/package.json",
+                r#"{"name": "left-pad", "version": "1.3.0", "main": "index.js"}"#,
+            ),
+            ("package/index.js", "export function poisonedPad(str) {}"),
+        ]);
+        PackageCache::new(cache_dir.path().to_path_buf())
+            .put_package(
+                Some(&registry.url),
+                "left-pad",
+                "1.3.0",
+                None,
+                "https://example.test/left-pad.tgz",
+                &poisoned,
+            )
+            .unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(home.path())
+            .env("HOME", home.path())
+            .env("NPM_CONFIG_GLOBALCONFIG", home.path().join("npmrc"))
+            .env("PRETTY_NODE_CACHE_DIR", cache_dir.path())
+            .args(["tree", "left-pad@1.3.0", "--registry", &registry.url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("publishedPad"))
+            .stdout(predicate::str::contains("poisonedPad").not())
+            .stderr(predicate::str::contains("does not match the registry's integrity"));
+        assert!(registry
+            .requests()
+            .contains(&"/left-pad/-/left-pad-1.3.0.tgz -".to_string()));
+    }
+
+    #[test]
+    fn test_offline_mode() {
+        use pretty_node::cache::PackageCache;
+
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let work_dir = tempfile::TempDir::new().unwrap();
+        let tarball = package_tarball(&[
+            (
+                "package/package.json",
+                r#"{"name": "left-pad", "version": "1.3.0", "main": "index.js"}"#,
+            ),
+            ("package/index.js", "export function leftPad(str, len, ch) {}"),
+        ]);
+        PackageCache::new(cache_dir.path().to_path_buf())
+            .put_package(None, "left-pad", "1.3.0", None, "https://example.test/left-pad.tgz", &tarball)
+            .unwrap();
+
+        // Served from the cache without registry metadata
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(work_dir.path())
+            .env("PRETTY_NODE_CACHE_DIR", cache_dir.path())
+            .args(["tree", "left-pad", "--offline"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("leftPad"));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(work_dir.path())
+            .env("PRETTY_NODE_CACHE_DIR", cache_dir.path())
+            .env("PRETTY_NODE_OFFLINE", "1")
+            .args(["tree", "right-pad"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("network access is disabled"));
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(work_dir.path())
+            .env("PRETTY_NODE_CACHE_DIR", cache_dir.path())
+            .args(["sig", "left-pad@2.0.0:leftPad", "--offline"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("'left-pad@2.0.0' is neither installed"));
+    }
+
+    #[test]
+    fn test_npmrc_scoped_registry_and_auth() {
+        let tarball = package_tarball(&[
+            (
+                "package/package.json",
+                r#"{"name": "@acme/widget", "version": "1.2.0", "main": "index.js"}"#,
+            ),
+            ("package/index.js", "export function renderWidget(props) {}"),
+        ]);
+        let registry = MockRegistry::start(Some("s3cret"), |url| {
+            let tarball_path = "@acme/widget/-/widget-1.2.0.tgz";
+            vec![
+                (
+                    "/@acme%2fwidget".to_string(),
+                    registry_document(url, "@acme/widget", "1.2.0", tarball_path),
+                ),
+                (format!("/{}", tarball_path), tarball),
+            ]
+        });
+
+        let home = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        std::fs::write(project.path().join("package.json"), "{}").unwrap();
+        let host = registry.url.trim_start_matches("http:");
+        std::fs::write(
+            project.path().join(".npmrc"),
+            format!(
+                "registry=http://127.0.0.1:9/unreachable/\n\
+                 @acme:registry=${{ACME_REGISTRY}}\n\
+                 {}:_authToken=${{ACME_TOKEN}}\n",
+                host
+            ),
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(project.path())
+            .env("HOME", home.path())
+            .env("NPM_CONFIG_GLOBALCONFIG", home.path().join("npmrc"))
+            .env("PRETTY_NODE_NO_CACHE", "1")
+            .env("ACME_REGISTRY", &registry.url)
+            .env("ACME_TOKEN", "s3cret")
+            .args(["tree", "@acme/widget"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("@acme/widget@1.2.0"))
+            .stdout(predicate::str::contains("renderWidget"));
+        assert_eq!(
+            registry.requests(),
+            vec![
+                "/@acme%2fwidget Bearer s3cret",
+                "/@acme/widget/-/widget-1.2.0.tgz Bearer s3cret",
+            ]
+        );
+
+        // A wrong token is reported as such rather than as a missing package
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(project.path())
+            .env("HOME", home.path())
+            .env("NPM_CONFIG_GLOBALCONFIG", home.path().join("npmrc"))
+            .env("PRETTY_NODE_NO_CACHE", "1")
+            .env("ACME_REGISTRY", &registry.url)
+            .env("ACME_TOKEN", "wrong")
+            .args(["tree", "@acme/widget"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("check the auth token"));
+    }
+
+    #[test]
+    fn test_always_auth_stays_on_the_registry_host() {
+        let tarball = package_tarball(&[
+            (
+                "package/package.json",
+                r#"{"name": "@acme/widget", "version": "1.2.0", "main": "index.js"}"#,
+            ),
+            ("package/index.js", "export function renderWidget(props) {}"),
+        ]);
+        let tarball_path = "@acme/widget/-/widget-1.2.0.tgz";
+        let cdn = MockRegistry::start(None, |_| {
+            vec![(format!("/{}", tarball_path), tarball)]
+        });
+        let registry = MockRegistry::start(Some("s3cret"), |_| {
+            vec![(
+                "/@acme%2fwidget".to_string(),
+                registry_document(&cdn.url, "@acme/widget", "1.2.0", tarball_path),
+            )]
+        });
+
+        let home = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        std::fs::write(project.path().join("package.json"), "{}").unwrap();
+        let host = registry.url.trim_start_matches("http:");
+        std::fs::write(
+            project.path().join(".npmrc"),
+            format!(
+                "registry=http://127.0.0.1:9/unreachable/\n\
+                 @acme:registry={}\n\
+                 {}:_authToken=s3cret\n\
+                 {}:always-auth=true\n",
+                registry.url, host, host
+            ),
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(project.path())
+            .env("HOME", home.path())
+            .env("NPM_CONFIG_GLOBALCONFIG", home.path().join("npmrc"))
+            .env("PRETTY_NODE_NO_CACHE", "1")
+            .args(["tree", "@acme/widget"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("renderWidget"));
+        assert_eq!(registry.requests(), vec!["/@acme%2fwidget Bearer s3cret"]);
+        assert_eq!(cdn.requests(), vec!["/@acme/widget/-/widget-1.2.0.tgz -"]);
+    }
+
+    #[test]
+    fn test_registry_flag() {
+        let tarball = package_tarball(&[
+            (
+                "package/package.json",
+                r#"{"name": "left-pad", "version": "9.9.9", "main": "index.js"}"#,
+            ),
+            ("package/index.js", "export function mirroredPad(str) {}"),
+        ]);
+        let registry = MockRegistry::start(None, |url| {
+            let tarball_path = "left-pad/-/left-pad-9.9.9.tgz";
+            vec![
+                (
+                    "/left-pad".to_string(),
+                    registry_document(url, "left-pad", "9.9.9", tarball_path),
+                ),
+                (format!("/{}", tarball_path), tarball),
+            ]
+        });
+        let home = tempfile::TempDir::new().unwrap();
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(home.path())
+            .env("HOME", home.path())
+            .env("NPM_CONFIG_GLOBALCONFIG", home.path().join("npmrc"))
+            .env("PRETTY_NODE_NO_CACHE", "1")
+            .args(["tree", "left-pad", "--registry", registry.url.trim_end_matches('/')])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("mirroredPad"));
+        // The package ships no declarations, so its @types package is looked up too
+        assert_eq!(
+            registry.requests(),
+            vec![
+                "/left-pad -",
+                "/left-pad/-/left-pad-9.9.9.tgz -",
+                "/@types%2fleft-pad -",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_version_ranges_and_dist_tags() {
+        let published = ["1.2.0", "1.4.0", "1.5.0-rc.1", "2.0.0-beta.1"];
+        let registry = MockRegistry::start(None, |url| {
+            let mut versions = serde_json::Map::new();
+            let mut routes = Vec::new();
+            for version in published {
+                let tarball_path = format!("ranged/-/ranged-{}.tgz", version);
+                versions.insert(
+                    version.to_string(),
+                    serde_json::json!({
+                        "name": "ranged",
+                        "version": version,
+                        "dist": {"tarball": format!("{}{}", url, tarball_path)}
+                    }),
+                );
+                let package_json = format!(r#"{{"name": "ranged", "version": "{}"}}"#, version);
+                let index = format!("export const release = '{}';", version);
+                routes.push((
+                    format!("/{}", tarball_path),
+                    package_tarball(&[
+                        ("package/package.json", &package_json),
+                        ("package/index.js", &index),
+                    ]),
+                ));
+            }
+            let document = serde_json::json!({
+                "name": "ranged",
+                "dist-tags": {"latest": "1.2.0", "next": "2.0.0-beta.1"},
+                "versions": versions,
+            });
+            routes.push(("/ranged".to_string(), document.to_string().into_bytes()));
+            routes
+        });
+        let home = tempfile::TempDir::new().unwrap();
+
+        let tree = |spec: &str| {
+            let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+            cmd.current_dir(home.path())
+                .env("HOME", home.path())
+                .env("NPM_CONFIG_GLOBALCONFIG", home.path().join("npmrc"))
+                .env("PRETTY_NODE_NO_CACHE", "1")
+                .env("NO_COLOR", "1")
+                .args(["tree", spec, "--quiet", "--registry", &registry.url])
+                .assert()
+        };
+
+        // `latest` wins whenever the range allows it
+        tree("ranged@^1").success().stdout(predicate::str::contains("ranged@1.2.0 (^1)"));
+        tree("ranged@>=1 <1.3").success().stdout(predicate::str::contains("ranged@1.2.0 (>=1 <1.3)"));
+        // Otherwise the highest match, skipping prereleases
+        tree("ranged@>1.2").success().stdout(predicate::str::contains("ranged@1.4.0 (>1.2)"));
+        tree("ranged@~1.5.0-rc.0").success().stdout(predicate::str::contains("ranged@1.5.0-rc.1 (~1.5.0-rc.0)"));
+        tree("ranged@next").success().stdout(predicate::str::contains("ranged@2.0.0-beta.1 (next)"));
+        tree("ranged@1.4.0").success().stdout(predicate::str::contains("ranged@1.4.0\n"));
+        tree("ranged@^3").success().stdout(predicate::str::contains("release").not());
+    }
+
+    #[test]
+    fn test_local_package_sources() {
+        let work_dir = tempfile::TempDir::new().unwrap();
+        let package_dir = work_dir.path().join("packages/core");
+        std::fs::create_dir_all(&package_dir).unwrap();
+        let package_json = r#"{"name": "@me/core", "version": "0.1.0", "main": "index.js"}"#;
+        std::fs::write(package_dir.join("package.json"), package_json).unwrap();
+        std::fs::write(package_dir.join("index.js"), "export function hello(name) {}").unwrap();
+        std::fs::write(
+            work_dir.path().join("core-0.1.0.tgz"),
+            package_tarball(&[
+                ("package/package.json", package_json),
+                ("package/index.js", "export function packed(name) {}"),
+            ]),
+        )
+        .unwrap();
+
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(&package_dir)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "-A"]);
+        git(&["commit", "--quiet", "-m", "v1"]);
+        git(&["tag", "v1"]);
+        std::fs::write(package_dir.join("index.js"), "export function unreleased() {}").unwrap();
+        let git_spec = format!("git+file://{}#v1", package_dir.display());
+
+        for (spec, symbol) in [
+            ("./packages/core", "unreleased"),
+            ("file:packages/core", "unreleased"),
+            ("./core-0.1.0.tgz", "packed"),
+            (git_spec.as_str(), "hello"),
+        ] {
+            let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+            cmd.current_dir(work_dir.path())
+                .args(["tree", spec, "--quiet"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("@me/core@0.1.0"))
+                .stdout(predicate::str::contains(symbol));
+        }
+    }
+
+    #[test]
+    fn test_self_typed_package() {
+        let package_dir = tempfile::TempDir::new().unwrap();
+        let write = |path: &str, content: &str| {
+            std::fs::write(package_dir.path().join(path), content).unwrap();
+        };
+        write(
+            "package.json",
+            r#"{"name": "self-typed", "version": "1.0.0", "main": "index.js", "types": "index.d.ts"}"#,
+        );
+        write(
+            "index.js",
+            "/** Count the characters */\nexports.foo = function (a) { return a.length; };\n",
+        );
+        write(
+            "index.d.ts",
+            "export declare function foo(a: string): number;\nexport declare function bar(): void;\n",
+        );
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        let output = cmd
+            .current_dir(package_dir.path())
+            .args(["tree", "./", "--quiet", "-o", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let tree: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(tree["types_package"], serde_json::Value::Null);
+        let functions = tree["functions"].as_array().unwrap();
+        let foo = functions.iter().find(|f| f["name"] == "foo").unwrap();
+        assert_eq!(foo["parameters"][0]["param_type"], "string");
+        assert_eq!(foo["return_type"], "number");
+        assert_eq!(foo["doc_comment"], "Count the characters");
+        assert_eq!(foo["source"], "package");
+        let bar = functions.iter().find(|f| f["name"] == "bar").unwrap();
+        assert_eq!(bar["source"], "package");
+
+        let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+        cmd.current_dir(package_dir.path())
+            .args(["sig", "./:foo", "--quiet"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("a: string"))
+            .stdout(predicate::str::contains("number"))
+            .stdout(predicate::str::contains("signature not available").not());
+    }
+
+    #[test]
+    fn test_node_module_resolution() {
+        let root = tempfile::TempDir::new().unwrap();
+        let install = |modules_dir: std::path::PathBuf, name: &str, function: &str| {
+            let package_dir = modules_dir.join(name);
+            std::fs::create_dir_all(&package_dir).unwrap();
+            std::fs::write(
+                package_dir.join("package.json"),
+                format!(r#"{{"name": "{}", "version": "1.0.0", "main": "index.js"}}"#, name),
+            )
+            .unwrap();
+            std::fs::write(
+                package_dir.join("index.js"),
+                format!("export function {}() {{}}", function),
+            )
+            .unwrap();
+        };
+        install(root.path().join("repo/node_modules"), "hoisted-pkg", "fromRoot");
+        install(root.path().join("node-path"), "path-pkg", "fromNodePath");
+        install(root.path().join("prefix/lib/node_modules"), "global-pkg", "fromPrefix");
+        let deep_dir = root.path().join("repo/packages/a/src/b/c");
+        std::fs::create_dir_all(&deep_dir).unwrap();
+
+        // Offline without a cache, so anything not found locally fails
+        let tree = |cwd: &std::path::Path, args: &[&str]| {
+            let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+            cmd.current_dir(cwd)
+                .env("HOME", root.path())
+                .env("NODE_PATH", root.path().join("node-path"))
+                .env("NPM_CONFIG_PREFIX", root.path().join("prefix"))
+                .env("PRETTY_NODE_NO_CACHE", "1")
+                .args(["tree", "--offline", "--quiet"])
+                .args(args)
+                .assert()
+        };
+
+        tree(&deep_dir, &["hoisted-pkg"]).success().stdout(predicate::str::contains("fromRoot"));
+        tree(&deep_dir, &["path-pkg"]).success().stdout(predicate::str::contains("fromNodePath"));
+        tree(&deep_dir, &["global-pkg"]).success().stdout(predicate::str::contains("fromPrefix"));
+        // A local install that does not satisfy the range is passed over
+        tree(&deep_dir, &["hoisted-pkg@^2"]).failure();
+
+        let elsewhere = tempfile::TempDir::new().unwrap();
+        tree(elsewhere.path(), &["hoisted-pkg"]).failure();
+        let project = deep_dir.to_string_lossy().into_owned();
+        tree(elsewhere.path(), &["hoisted-pkg", "--project", &project])
+            .success()
+            .stdout(predicate::str::contains("fromRoot"));
+    }
+
+    #[test]
+    fn test_package_manager_layouts() {
+        let root = tempfile::TempDir::new().unwrap();
+        let write_package = |dir: &std::path::Path, name: &str, version: &str, function: &str| {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(
+                dir.join("package.json"),
+                format!(
+                    r#"{{"name": "{}", "version": "{}", "main": "index.js"}}"#,
+                    name, version
+                ),
+            )
+            .unwrap();
+            std::fs::write(
+                dir.join("index.js"),
+                format!("export function {}() {{}}", function),
+            )
+            .unwrap();
+        };
+        let tree = |cwd: &std::path::Path, package: &str| {
+            let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+            cmd.current_dir(cwd)
+                .env("HOME", root.path())
+                .env_remove("NODE_PATH")
+                .env("NPM_CONFIG_PREFIX", root.path().join("prefix"))
+                .env("PRETTY_NODE_NO_CACHE", "1")
+                .args(["tree", "--offline", "--quiet", package])
+                .assert()
+        };
+
+        // pnpm: top-level symlinks into the `.pnpm` store, transitive
+        // dependencies only in the store
+        let pnpm = root.path().join("pnpm-app");
+        let store = pnpm.join("node_modules/.pnpm");
+        write_package(
+            &store.join("@acme+util@1.0.0/node_modules/@acme/util"),
+            "@acme/util",
+            "1.0.0",
+            "utilOne",
+        );
+        write_package(
+            &store.join("@acme+util@2.1.0(react@18.2.0)/node_modules/@acme/util"),
+            "@acme/util",
+            "2.1.0",
+            "utilTwo",
+        );
+        write_package(
+            &store.join("deep-dep@3.0.0/node_modules/deep-dep"),
+            "deep-dep",
+            "3.0.0",
+            "deepDep",
+        );
+        std::fs::create_dir_all(pnpm.join("node_modules/@acme")).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(
+            "../.pnpm/@acme+util@2.1.0(react@18.2.0)/node_modules/@acme/util",
+            pnpm.join("node_modules/@acme/util"),
+        )
+        .unwrap();
+
+        tree(&pnpm, "@acme/util").success().stdout(predicate::str::contains("utilTwo"));
+        tree(&pnpm, "@acme/util@^1").success().stdout(predicate::str::contains("utilOne"));
+        tree(&pnpm, "deep-dep").success().stdout(predicate::str::contains("deepDep"));
+
+        // Yarn Plug'n'Play with the package in the zip cache
+        let yarn = root.path().join("yarn-app");
+        let archive = yarn.join(".yarn/cache/left-pad-npm-1.3.0-abc123.zip");
+        std::fs::create_dir_all(archive.parent().unwrap()).unwrap();
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (path, content) in [
+            (
+                "node_modules/left-pad/package.json",
+                r#"{"name": "left-pad", "version": "1.3.0", "main": "index.js"}"#,
+            ),
+            ("node_modules/left-pad/index.js", "export function leftPad() {}"),
+        ] {
+            zip.start_file(path, options).unwrap();
+            std::io::Write::write_all(&mut zip, content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        std::fs::write(
+            yarn.join(".pnp.data.json"),
+            r#"{"packageRegistryData": [
+                [null, [[null, {"packageLocation": "./", "packageDependencies": [["left-pad", "npm:1.3.0"]]}]]],
+                ["left-pad", [["npm:1.3.0", {"packageLocation": "./.yarn/cache/left-pad-npm-1.3.0-abc123.zip/node_modules/left-pad/", "packageDependencies": []}]]]
+            ]}"#,
+        )
+        .unwrap();
+        tree(&yarn, "left-pad").success().stdout(predicate::str::contains("leftPad"));
+        tree(&yarn, "left-pad@^2").failure();
+
+        // Workspace packages, found from inside a sibling package
+        let pnpm_workspace = root.path().join("pnpm-mono");
+        std::fs::create_dir_all(&pnpm_workspace).unwrap();
+        std::fs::write(
+            pnpm_workspace.join("pnpm-workspace.yaml"),
+            "packages:\n  - 'packages/*'\n  - '!packages/ignored'\n",
+        )
+        .unwrap();
+        write_package(&pnpm_workspace.join("packages/core"), "@mono/core", "0.1.0", "coreApi");
+        write_package(&pnpm_workspace.join("packages/ignored"), "@mono/ignored", "0.1.0", "nope");
+        let app = pnpm_workspace.join("packages/app/src");
+        std::fs::create_dir_all(&app).unwrap();
+        tree(&app, "@mono/core").success().stdout(predicate::str::contains("coreApi"));
+        tree(&app, "@mono/ignored").failure();
+
+        let npm_workspace = root.path().join("npm-mono");
+        write_package(&npm_workspace.join("libs/nested/shared"), "shared", "1.0.0", "sharedApi");
+        std::fs::write(
+            npm_workspace.join("package.json"),
+            r#"{"name": "npm-mono", "private": true, "workspaces": {"packages": ["libs/**"]}}"#,
+        )
+        .unwrap();
+        tree(&npm_workspace, "shared").success().stdout(predicate::str::contains("sharedApi"));
+    }
+
+    #[test]
+    fn test_types_package_fallback() {
+        let root = tempfile::TempDir::new().unwrap();
+        let modules = root.path().join("node_modules");
+        let write = |path: &str, content: &str| {
+            let path = modules.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "untyped-lib/package.json",
+            r#"{"name": "untyped-lib", "version": "1.2.3", "main": "index.js"}"#,
+        );
+        write(
+            "untyped-lib/index.js",
+            "/** Create a lib instance */\nfunction createLib(options) {}\nmodule.exports = createLib;\n",
+        );
+        write(
+            "@types/untyped-lib/package.json",
+            r#"{"name": "@types/untyped-lib", "version": "1.2.0", "types": "index.d.ts"}"#,
+        );
+        write(
+            "@types/untyped-lib/index.d.ts",
+            "declare function lib(options?: lib.Options): lib.Instance;\ndeclare namespace lib {\n  interface Options { verbose?: boolean }\n  interface Instance { run(): void }\n  function helper(value: number): string;\n}\nexport = lib;\n",
+        );
+        write(
+            "@acme/js-utils/package.json",
+            r#"{"name": "@acme/js-utils", "version": "2.0.0", "main": "index.js"}"#,
+        );
+        write(
+            "@acme/js-utils/index.js",
+            "/** Pad a string on the left */\nexport function leftPad(str, len) {}\n",
+        );
+        write(
+            "@types/acme__js-utils/index.d.ts",
+            "export declare function leftPad(str: string, len: number): string;\n",
+        );
+        write(
+            "@types/acme__js-utils/package.json",
+            r#"{"name": "@types/acme__js-utils", "version": "2.0.1", "types": "index.d.ts"}"#,
+        );
+
+        let run = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+            cmd.current_dir(root.path())
+                .env("HOME", root.path())
+                .env("PRETTY_NODE_NO_CACHE", "1")
+                .arg("--offline")
+                .args(args)
+                .assert()
+                .success()
+                .get_output()
+                .stdout
+                .clone()
+        };
+
+        let tree: serde_json::Value =
+            serde_json::from_slice(&run(&["tree", "untyped-lib", "--quiet", "-o", "json"]))
+                .unwrap();
+        assert_eq!(tree["types_package"], "@types/untyped-lib@1.2.0");
+        let functions = tree["functions"].as_array().unwrap();
+        let create_lib = functions.iter().find(|f| f["name"] == "createLib").unwrap();
+        assert_eq!(create_lib["source"]["merged"], "@types/untyped-lib");
+        assert_eq!(create_lib["parameters"][0]["param_type"], "lib.Options");
+        assert_eq!(create_lib["doc_comment"], "Create a lib instance");
+        let helper = functions.iter().find(|f| f["name"] == "helper").unwrap();
+        assert_eq!(helper["source"]["types_package"], "@types/untyped-lib");
+        let types: Vec<&str> = tree["types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, vec!["Options", "Instance"]);
+
+        let signature = String::from_utf8(run(&["sig", "@acme/js-utils:leftPad", "--quiet"])).unwrap();
+        assert!(signature.contains("Pad a string on the left"));
+        assert!(signature.contains("Types from @types/acme__js-utils"));
+        assert!(signature.contains("str: string"));
+        assert!(signature.contains("Returns: string"));
+
+        // `module.exports = createLib` is what `export = lib` declares
+        let signature = String::from_utf8(run(&["sig", "untyped-lib:createLib", "--quiet"])).unwrap();
+        assert!(signature.contains("options?: lib.Options"));
+        assert!(signature.contains("Returns: lib.Instance"));
+    }
+
+    #[test]
+    fn test_own_declarations_before_types_package() {
+        let root = tempfile::TempDir::new().unwrap();
+        let modules = root.path().join("node_modules");
+        let write = |path: &str, content: &str| {
+            let path = modules.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "typed-lib/package.json",
+            r#"{"name": "typed-lib", "version": "1.0.0", "main": "index.js", "types": "index.d.ts"}"#,
+        );
+        write(
+            "typed-lib/index.js",
+            "/** Format a value */\nexports.format = function (value, options) {};\n",
+        );
+        write(
+            "typed-lib/index.d.ts",
+            "export declare function format(value: unknown, options?: { pretty: boolean }): string;\n",
+        );
+        // An outdated DefinitelyTyped package that must not be used
+        write(
+            "@types/typed-lib/package.json",
+            r#"{"name": "@types/typed-lib", "version": "0.1.0", "types": "index.d.ts"}"#,
+        );
+        write(
+            "@types/typed-lib/index.d.ts",
+            "export declare function format(value: number): number;\n",
+        );
+
+        let run = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+            cmd.current_dir(root.path())
+                .env("HOME", root.path())
+                .env("PRETTY_NODE_NO_CACHE", "1")
+                .arg("--offline")
+                .args(args)
+                .assert()
+                .success()
+                .get_output()
+                .stdout
+                .clone()
+        };
+
+        let tree: serde_json::Value =
+            serde_json::from_slice(&run(&["tree", "typed-lib", "--quiet", "-o", "json"])).unwrap();
+        assert_eq!(tree["types_package"], serde_json::Value::Null);
+        let format = &tree["functions"][0];
+        assert_eq!(format["source"], "package");
+        assert_eq!(format["parameters"][0]["param_type"], "unknown");
+
+        let signature = String::from_utf8(run(&["sig", "typed-lib:format", "--quiet"])).unwrap();
+        assert!(signature.contains("Format a value"));
+        assert!(signature.contains("value: unknown"));
+        assert!(signature.contains("Returns: string"));
+        assert!(!signature.contains("@types/typed-lib"));
+    }
+
+    #[test]
+    fn test_signature_overrides() {
+        let root = tempfile::TempDir::new().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = root.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "node_modules/dynamic-lib/package.json",
+            r#"{"name": "dynamic-lib", "version": "2.1.0", "main": "index.js"}"#,
+        );
+        write(
+            "node_modules/dynamic-lib/index.js",
+            "exports.connect = function () { return open.apply(null, arguments); };\nexports.close = function (handle) {};\n",
+        );
+        write(
+            ".config/pretty-node/overrides/dynamic-lib.toml",
+            "[[overrides]]\npackage = \"dynamic-lib\"\nversions = \"^2\"\nsymbol = \"connect\"\nreturns = \"Connection\"\n\n[[overrides.parameters]]\nname = \"url\"\ntype = \"string\"\n\n[[overrides]]\npackage = \"dynamic-lib\"\nversions = \"^1\"\nsymbol = \"close\"\nreturns = \"never\"\n",
+        );
+        write(".config/pretty-node/overrides/broken.json", "{\"overrides\": [{}]}");
+
+        let run = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+            cmd.current_dir(root.path())
+                .env("HOME", root.path())
+                .env_remove("XDG_CONFIG_HOME")
+                .env("PRETTY_NODE_NO_CACHE", "1")
+                .arg("--offline")
+                .args(args)
+                .assert()
+                .success()
+        };
+
+        let overridden = run(&["sig", "dynamic-lib:connect"]).stderr(predicate::str::contains(
+            "Ignoring signature overrides in",
+        ));
+        let signature = String::from_utf8(overridden.get_output().stdout.clone()).unwrap();
+        assert!(signature.contains("url: string"));
+        assert!(signature.contains("Returns: Connection"));
+        assert!(signature.contains("Curated signature from"));
+        assert!(signature.contains("dynamic-lib.toml"));
+
+        let json: serde_json::Value = serde_json::from_slice(
+            &run(&["sig", "dynamic-lib:connect", "--quiet", "-o", "json"])
+                .get_output()
+                .stdout,
+        )
+        .unwrap();
+        assert!(json["source"]["curated"].as_str().unwrap().ends_with("dynamic-lib.toml"));
+
+        // The range doesn't cover 2.1.0, so the parsed signature stands
+        let json: serde_json::Value = serde_json::from_slice(
+            &run(&["sig", "dynamic-lib:close", "--quiet", "-o", "json"])
+                .get_output()
+                .stdout,
+        )
+        .unwrap();
+        assert_eq!(json["source"], "package");
+        assert_eq!(json["parameters"][0]["name"], "handle");
+    }
+
+    #[test]
+    fn test_tree_sort_orders() {
+        let root = tempfile::TempDir::new().unwrap();
+        let package = root.path().join("sorted");
+        let write = |path: &str, content: &str| {
+            let path = package.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("package.json", r#"{"name": "sorted", "version": "1.0.0", "main": "index.js"}"#);
+        write(
+            "index.js",
+            "export function zeta() {}\nexport async function beta() {}\nexport function Alpha() {}\nexport const B = 1;\nexport const a = 'x';\n",
+        );
+        for name in ["walk", "mount", "parse", "index-helpers", "cache"] {
+            write(&format!("lib/{}.js", name), &format!("export function {}() {{}}", name.replace('-', "_")));
+        }
+
+        let tree = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("pretty-node").unwrap();
+            let output = cmd
+                .arg("tree")
+                .arg(&package)
+                .args(["--quiet", "-o", "json"])
+                .args(args)
+                .assert()
+                .success()
+                .get_output()
+                .stdout
+                .clone();
+            serde_json::from_slice::<serde_json::Value>(&output).unwrap()
+        };
+        let names = |tree: &serde_json::Value, key: &str| -> Vec<String> {
+            tree[key]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let source_order = tree(&[]);
+        assert_eq!(names(&source_order, "functions"), vec!["zeta", "beta", "Alpha"]);
+        let submodules: Vec<&String> = source_order["submodules"]["lib"]["submodules"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        assert_eq!(submodules, vec!["cache", "index-helpers", "mount", "parse", "walk"]);
+        assert_eq!(tree(&[]), source_order);
+
+        let by_name = tree(&["--sort", "name"]);
+        assert_eq!(names(&by_name, "functions"), vec!["Alpha", "beta", "zeta"]);
+        assert_eq!(names(&by_name, "constants"), vec!["a", "B"]);
+        assert_eq!(by_name["exports"], serde_json::json!(["a", "Alpha", "B", "beta", "zeta"]));
+
+        let by_kind = tree(&["--sort", "kind"]);
+        assert_eq!(names(&by_kind, "functions"), vec!["Alpha", "zeta", "beta"]);
+    }
+
     #[test]
     fn test_tree_with_colon_syntax_error() {
         let mut cmd = Command::cargo_bin("pretty-node").unwrap();
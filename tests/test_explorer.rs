@@ -1,5 +1,6 @@
+use pretty_node::api_diff::{ApiDiff, ChangeType, Severity};
 use pretty_node::explorer::NodeModuleExplorer;
-use pretty_node::module_info::{NodeModuleInfo, SignatureInfo, SignatureKind};
+use pretty_node::module_info::{NodeModuleInfo, SignatureInfo, SignatureKind, SymbolSource};
 use pretty_node::parser::signature::extract_signature;
 use tempfile::TempDir;
 
@@ -108,7 +109,10 @@ mod explorer_tests {
             kind: SignatureKind::Function,
             parameters: vec![],
             return_type: Some("boolean".to_string()),
+            type_params: vec![],
             doc_comment: None,
+            doc_tags: None,
+            source: SymbolSource::Package,
         };
 
         assert_eq!(sig.name, "testFunction");
@@ -153,6 +157,189 @@ mod explorer_tests {
         assert_eq!(explorer.max_depth(), 1);
     }
 
+    #[tokio::test]
+    async fn test_exports_map_entry_points() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("esm-package");
+        std::fs::create_dir_all(package_dir.join("dist/plugins")).unwrap();
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{
+                "name": "esm-package",
+                "version": "2.0.0",
+                "main": "legacy.js",
+                "exports": {
+                    ".": {"browser": "./dist/browser.js", "import": "./dist/index.js"},
+                    "./plugins/*": "./dist/plugins/*.js",
+                    "./plugins/internal": null
+                }
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(package_dir.join("legacy.js"), "export function legacy() {}").unwrap();
+        std::fs::write(package_dir.join("dist/index.js"), "export function run() {}").unwrap();
+        std::fs::write(package_dir.join("dist/browser.js"), "export function runInBrowser() {}").unwrap();
+        std::fs::write(package_dir.join("dist/plugins/cache.js"), "export function cache() {}").unwrap();
+        std::fs::write(package_dir.join("dist/plugins/internal.js"), "export function secret() {}").unwrap();
+
+        let explorer = NodeModuleExplorer::new("esm-package".to_string(), 2, true);
+        let module = explorer.explore_path(&package_dir).await.unwrap();
+        assert_eq!(module.exports, vec!["run"]);
+        assert_eq!(module.main.as_deref(), Some("dist/index.js"));
+        assert!(module.submodules.contains_key("plugins/cache"));
+        assert!(!module.submodules.contains_key("plugins/internal"));
+
+        let browser = NodeModuleExplorer::new("esm-package".to_string(), 1, true)
+            .with_conditions(vec!["browser".to_string()]);
+        let module = browser.explore_path(&package_dir).await.unwrap();
+        assert_eq!(module.exports, vec!["runInBrowser"]);
+    }
+
+    #[tokio::test]
+    async fn test_barrel_reexports_record_definition_site() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("barrel");
+        std::fs::create_dir_all(package_dir.join("lib")).unwrap();
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{"name": "barrel", "version": "1.0.0", "main": "index.js"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            package_dir.join("index.js"),
+            "export * from './lib/a';
+export { b as renamed } from './lib/b.js';
+export * as ns from './lib/ns';
+export function local() {}",
+        )
+        .unwrap();
+        std::fs::write(package_dir.join("lib/a.js"), "export * from './deep';
+export class A {}").unwrap();
+        std::fs::write(package_dir.join("lib/deep.js"), "export function deep() {}").unwrap();
+        std::fs::write(package_dir.join("lib/b.js"), "export function b(x) {}").unwrap();
+        std::fs::write(package_dir.join("lib/ns.js"), "export const VERSION = '1';").unwrap();
+
+        let explorer = NodeModuleExplorer::new("barrel".to_string(), 2, true);
+        let module = explorer.explore_path(&package_dir).await.unwrap();
+        let defined_in = |name: &str| {
+            module
+                .functions
+                .iter()
+                .find(|f| f.name == name)
+                .and_then(|f| f.defined_in.clone())
+        };
+        assert_eq!(defined_in("local").as_deref(), Some("index.js"));
+        assert_eq!(defined_in("deep").as_deref(), Some("lib/deep.js"));
+        assert_eq!(defined_in("renamed").as_deref(), Some("lib/b.js"));
+        assert_eq!(module.classes[0].defined_in.as_deref(), Some("lib/a.js"));
+        assert_eq!(
+            module.submodules["ns"].constants[0].defined_in.as_deref(),
+            Some("lib/ns.js")
+        );
+        assert!(module.exports.contains(&"ns".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_submodules_follow_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_dir = temp_dir.path().join("router-lib");
+        let write = |path: &str, content: &str| {
+            let path = package_dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "package.json",
+            r#"{"name": "router-lib", "version": "1.0.0", "main": "lib/main.js"}"#,
+        );
+        write("lib/main.js", "export function app() {}");
+        write("lib/index.js", "export function libIndex() {}");
+        write("lib/router/index.js", "export function Router() {}");
+        write("lib/router/route.js", "export class Route {}");
+        write("lib/router/layers/layer.js", "export class Layer {}");
+        write("lib/utils.js", "export function merge(a, b) {}");
+        write("lib/utils.d.ts", "export declare function merge(a: object, b: object): object;");
+        write("lib/empty/README.md", "nothing to import");
+        write("lib/node_modules/dep/index.js", "export function dep() {}");
+
+        let explorer = NodeModuleExplorer::new("router-lib".to_string(), 5, true);
+        let module = explorer.explore_path(&package_dir).await.unwrap();
+        assert_eq!(module.functions[0].name, "app");
+
+        let lib = &module.submodules["lib"];
+        assert_eq!(lib.functions[0].name, "libIndex");
+        let mut children: Vec<&String> = lib.submodules.keys().collect();
+        children.sort();
+        assert_eq!(children, vec!["router", "utils"]);
+
+        let router = &lib.submodules["router"];
+        assert_eq!(router.name, "router");
+        assert_eq!(router.functions[0].name, "Router");
+        assert_eq!(router.submodules["route"].classes[0].name, "Route");
+        assert_eq!(router.submodules["layers"].submodules["layer"].classes[0].name, "Layer");
+        assert_eq!(
+            lib.submodules["utils"].functions[0].parameters[0].param_type.as_deref(),
+            Some("object")
+        );
+
+        // Each level of nesting takes one more level of depth
+        let shallow = NodeModuleExplorer::new("router-lib".to_string(), 3, true);
+        let module = shallow.explore_path(&package_dir).await.unwrap();
+        let router = &module.submodules["lib"].submodules["router"];
+        assert_eq!(router.functions[0].name, "Router");
+        assert!(router.submodules.is_empty());
+
+        let flat = NodeModuleExplorer::new("router-lib".to_string(), 1, true);
+        let module = flat.explore_path(&package_dir).await.unwrap();
+        assert!(module.submodules.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_api_diff_between_versions() {
+        let temp_dir = TempDir::new().unwrap();
+        let write_version = |version: &str, source: &str| {
+            let package_dir = temp_dir.path().join(version);
+            std::fs::create_dir_all(&package_dir).unwrap();
+            std::fs::write(
+                package_dir.join("package.json"),
+                format!(r#"{{"name": "api", "version": "{}", "main": "index.js"}}"#, version),
+            )
+            .unwrap();
+            std::fs::write(package_dir.join("index.js"), source).unwrap();
+            package_dir
+        };
+        let old_dir = write_version(
+            "1.0.0",
+            "export function render(template, data) {}\nexport function compile(source) {}\nexport class App { use(fn) {} }",
+        );
+        let new_dir = write_version(
+            "2.0.0",
+            "export function render(template, data, options = {}) {}\nexport class App { use(path, fn) {} listen(port) {} }",
+        );
+
+        let explorer = NodeModuleExplorer::new("api".to_string(), 1, true);
+        let old = explorer.explore_path(&old_dir).await.unwrap();
+        let new = explorer.explore_path(&new_dir).await.unwrap();
+        let diff = ApiDiff::compare(&old, &new);
+
+        assert_eq!(diff.from_version.as_deref(), Some("1.0.0"));
+        assert_eq!(diff.to_version.as_deref(), Some("2.0.0"));
+        let summary: Vec<_> = diff
+            .changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.change, change.severity))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("api:render", ChangeType::Changed, Severity::Additive),
+                ("api:compile", ChangeType::Removed, Severity::Breaking),
+                ("api:App.use", ChangeType::Changed, Severity::Breaking),
+                ("api:App.listen", ChangeType::Added, Severity::Additive),
+            ]
+        );
+    }
+
     #[test]
     fn test_depth_limiting() {
         let explorer = NodeModuleExplorer::new("test-package".to_string(), 1, false);